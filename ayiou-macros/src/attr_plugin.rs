@@ -11,6 +11,8 @@ struct PluginAttrs {
     version: Option<String>,
     prefixes: Vec<String>,
     register: bool,
+    parse_error: Option<ParseErrorPolicy>,
+    on_error: Option<syn::Ident>,
 }
 
 impl Default for PluginAttrs {
@@ -21,6 +23,8 @@ impl Default for PluginAttrs {
             version: None,
             prefixes: Vec::new(),
             register: true,
+            parse_error: None,
+            on_error: None,
         }
     }
 }
@@ -28,6 +32,7 @@ impl Default for PluginAttrs {
 #[derive(Default)]
 struct CommandAttrs {
     name: Option<String>,
+    group: Option<String>,
     aliases: Vec<String>,
     summary: Option<String>,
     usage: Option<String>,
@@ -35,25 +40,83 @@ struct CommandAttrs {
     priority: Option<i32>,
     block: Option<bool>,
     permissions: Vec<String>,
+    parse_error: Option<ParseErrorPolicy>,
+    rate_limits: Vec<RateLimitAttrs>,
+    rate_limit_scope: Option<syn::Ident>,
+    rate_limit_action: Option<syn::Ident>,
+    timeout_ms: Option<u64>,
+    on_error: Option<syn::Ident>,
+    channel_kinds: Vec<syn::Ident>,
+    platforms: Vec<String>,
+    bots: Vec<String>,
+    to_me: bool,
+}
+
+/// `calls` per `window_ms`, from `cooldown = ".."` or `rate_limit = "N/.."`.
+struct RateLimitAttrs {
+    calls: u32,
+    window_ms: u64,
+}
+
+#[derive(Clone, Copy)]
+enum ParseErrorPolicy {
+    Reply,
+    Silent,
+    Propagate,
+}
+
+#[derive(Default)]
+struct NamedArgAttrs {
+    long: Option<String>,
+    short: Option<char>,
+    default: Option<String>,
+}
+
+struct PluginMethods {
+    commands: Vec<CommandMethod>,
+    regexes: Vec<RegexMethod>,
 }
 
 struct CommandMethod {
     fn_name: syn::Ident,
     labels: Vec<String>,
     meta: CommandMetaAttrs,
+    options: HandlerOptions,
     parser_stmts: Vec<TokenStream>,
     call_args: Vec<syn::Ident>,
 }
 
+/// A `#[regex("...")]` method, run when any of its patterns matches.
+struct RegexMethod {
+    fn_name: syn::Ident,
+    patterns: Vec<String>,
+    options: HandlerOptions,
+    capture_stmts: Vec<TokenStream>,
+    call_args: Vec<syn::Ident>,
+}
+
 struct CommandMetaAttrs {
     command: String,
+    group: Option<String>,
     aliases: Vec<String>,
     summary: Option<String>,
     usage: Option<String>,
     examples: Vec<String>,
+}
+
+/// Dispatch options shared by command and regex handlers.
+struct HandlerOptions {
     priority: Option<i32>,
     block: bool,
     permissions: Vec<String>,
+    parse_error: Option<ParseErrorPolicy>,
+    rate_limits: Vec<TokenStream>,
+    timeout_ms: Option<u64>,
+    on_error: Option<syn::Ident>,
+    channel_kinds: Vec<syn::Ident>,
+    platforms: Vec<String>,
+    bots: Vec<String>,
+    to_me: bool,
 }
 
 struct PluginIdentity {
@@ -68,7 +131,7 @@ pub fn expand_plugin(args: Vec<Meta>, mut item_impl: ItemImpl) -> Result<TokenSt
     let plugin_ty = item_impl.self_ty.clone();
     let plugin_ident = extract_self_type_ident(&plugin_ty)?;
     let ctx_ty: Type = syn::parse_quote!(ayiou::Context);
-    let methods = collect_command_methods(&mut item_impl)?;
+    let methods = collect_command_methods(&mut item_impl, &plugin_attrs)?;
     let identity = plugin_identity(&plugin_attrs, &plugin_ident);
 
     Ok(render_plugin_impl(
@@ -81,8 +144,12 @@ pub fn expand_plugin(args: Vec<Meta>, mut item_impl: ItemImpl) -> Result<TokenSt
     ))
 }
 
-fn collect_command_methods(item_impl: &mut ItemImpl) -> Result<Vec<CommandMethod>> {
-    let mut methods = Vec::new();
+fn collect_command_methods(
+    item_impl: &mut ItemImpl,
+    plugin_attrs: &PluginAttrs,
+) -> Result<PluginMethods> {
+    let mut commands = Vec::new();
+    let mut regexes: Vec<RegexMethod> = Vec::new();
 
     for impl_item in &mut item_impl.items {
         let ImplItem::Fn(method) = impl_item else {
@@ -99,22 +166,48 @@ fn collect_command_methods(item_impl: &mut ItemImpl) -> Result<Vec<CommandMethod
         } else {
             CommandAttrs::default()
         };
+        let patterns = take_regex_attrs(method)?;
 
         method
             .attrs
             .push(syn::parse_quote!(#[allow(clippy::unused_async)]));
 
-        methods.push(parse_command_method(method, cmd_attrs)?);
+        if patterns.is_empty() {
+            commands.push(parse_command_method(method, cmd_attrs, plugin_attrs)?);
+        } else {
+            let regex_method = parse_regex_method(method, patterns, &cmd_attrs, plugin_attrs)?;
+            for pattern in &regex_method.patterns {
+                if let Some(other) = regexes
+                    .iter()
+                    .find(|other| other.patterns.contains(pattern))
+                {
+                    return Err(syn::Error::new_spanned(
+                        &method.sig.ident,
+                        format!(
+                            "regex `{pattern}` is already handled by `{}`",
+                            other.fn_name
+                        ),
+                    ));
+                }
+            }
+            regexes.push(regex_method);
+        }
+
+        for input in &mut method.sig.inputs {
+            if let FnArg::Typed(pat_type) = input {
+                pat_type.attrs.retain(|attr| !attr.path().is_ident("arg"));
+            }
+        }
     }
 
-    if methods.is_empty() {
+    if commands.is_empty() && regexes.is_empty() {
         return Err(syn::Error::new_spanned(
             &item_impl,
             "#[plugin] requires at least one async command method",
         ));
     }
 
-    Ok(methods)
+    Ok(PluginMethods { commands, regexes })
 }
 
 fn command_attr_index(method: &syn::ImplItemFn) -> Option<usize> {
@@ -124,6 +217,27 @@ fn command_attr_index(method: &syn::ImplItemFn) -> Option<usize> {
         .position(|attr| attr.path().is_ident("command"))
 }
 
+/// Remove the method's `#[regex("...")]` attributes, returning their patterns.
+fn take_regex_attrs(method: &mut syn::ImplItemFn) -> Result<Vec<String>> {
+    let mut patterns = Vec::new();
+    let mut error = None;
+    method.attrs.retain(|attr| {
+        if !attr.path().is_ident("regex") {
+            return true;
+        }
+        match attr.parse_args::<syn::LitStr>() {
+            Ok(pattern) => patterns.push(pattern.value()),
+            Err(_) => {
+                error.get_or_insert_with(|| {
+                    syn::Error::new_spanned(attr, "Expected `#[regex(\"pattern\")]`")
+                });
+            }
+        }
+        false
+    });
+    error.map_or(Ok(patterns), Err)
+}
+
 fn plugin_identity(attrs: &PluginAttrs, plugin_ident: &syn::Ident) -> PluginIdentity {
     PluginIdentity {
         name: attrs
@@ -142,9 +256,9 @@ fn render_plugin_impl(
     ctx_ty: &Type,
     identity: PluginIdentity,
     prefixes: &[String],
-    methods: &[CommandMethod],
+    methods: &PluginMethods,
 ) -> TokenStream {
-    let handler_decls = methods.iter().map(|method| {
+    let command_decls = methods.commands.iter().map(|method| {
         let command_values = method
             .labels
             .iter()
@@ -152,6 +266,7 @@ fn render_plugin_impl(
         let prefix_values = prefixes.iter().map(|value| quote! { #value.to_string() });
         let meta = &method.meta;
         let command = &meta.command;
+        let group = meta.group.as_ref().map(|value| quote! { .group(#value) });
         let aliases = meta
             .aliases
             .iter()
@@ -166,29 +281,35 @@ fn render_plugin_impl(
             .examples
             .iter()
             .map(|value| quote! { #value.to_string() });
-        let permissions = meta.permissions.iter().map(|value| {
-            quote! { ayiou::core::plugin::Permission::custom(#value) }
-        });
-        let priority = meta.priority.map(|value| quote! { .priority(#value) });
-        let block = meta.block;
+        let options = method.options.render();
 
         quote! {
             ayiou::core::plugin::HandlerDecl::message_commands(
                 vec![#(#command_values),*],
                 Vec::<String>::from([#(#prefix_values),*]),
             )
+            #group
             .command_meta([
                 ayiou::core::plugin::CommandMeta::new(#command)
+                    #group
                     .aliases(Vec::<String>::from([#(#aliases),*]))
                     #summary
                     #usage
                     .examples(Vec::<String>::from([#(#examples),*]))
             ])
-            .require_permissions(Vec::<ayiou::core::plugin::Permission>::from([#(#permissions),*]))
-            #priority
-            .block(#block)
+            #options
         }
     });
+    let regex_decls = methods.regexes.iter().map(|method| {
+        let patterns = &method.patterns;
+        let options = method.options.render();
+
+        quote! {
+            ayiou::core::plugin::HandlerDecl::message_regex([#(#patterns),*])
+            #options
+        }
+    });
+    let handler_decls = command_decls.chain(regex_decls);
     let plugin_name = identity.name;
     let plugin_description = identity.description;
     let plugin_version = identity.version;
@@ -207,21 +328,88 @@ fn render_plugin_impl(
         quote! {}
     };
 
-    let dispatch_arms = methods.iter().map(|method| {
+    let dispatch_arms = methods.commands.iter().map(|method| {
         let fn_name = &method.fn_name;
         let labels = method.labels.iter();
         let parser_stmts = &method.parser_stmts;
         let call_args = &method.call_args;
-        let block = method.meta.block;
+        let block = method.options.block;
+        let group = method.meta.group.as_ref().map_or_else(
+            || quote! { ::std::option::Option::None },
+            |group| quote! { ::std::option::Option::Some(#group) },
+        );
 
         quote! {
-            #(#labels)|* => {
+            (#group, #(#labels)|*) => {
                 #(#parser_stmts)*
                 self.#fn_name(ctx, #(#call_args),*).await?;
                 Ok(ayiou::core::plugin::HandleOutcome::from_block(#block))
             }
         }
     });
+    let command_dispatch = (!methods.commands.is_empty()).then(|| {
+        quote! {
+            async fn handle_with_invocation(
+                &self,
+                ctx: &#ctx_ty,
+                invocation: Option<ayiou::core::model::CommandInvocation>,
+            ) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
+                let Some(line) = invocation else {
+                    return Ok(ayiou::core::plugin::HandleOutcome::pass());
+                };
+                self.__ayiou_dispatch_command(ctx, line.group(), line.command(), line.args())
+                    .await
+            }
+        }
+    });
+    let command_dispatcher = (!methods.commands.is_empty()).then(|| {
+        quote! {
+            impl #plugin_ty {
+                async fn __ayiou_dispatch_command(
+                    &self,
+                    ctx: &#ctx_ty,
+                    group: ::std::option::Option<&str>,
+                    command: &str,
+                    args: &str,
+                ) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
+                    match (group, command) {
+                        #(#dispatch_arms,)*
+                        _ => Ok(ayiou::core::plugin::HandleOutcome::pass()),
+                    }
+                }
+            }
+        }
+    });
+
+    let regex_arms = methods.regexes.iter().map(|method| {
+        let fn_name = &method.fn_name;
+        let patterns = &method.patterns;
+        let capture_stmts = &method.capture_stmts;
+        let call_args = &method.call_args;
+        let block = method.options.block;
+
+        quote! {
+            #(#patterns)|* => {
+                #(#capture_stmts)*
+                self.#fn_name(ctx, #(#call_args),*).await?;
+                Ok(ayiou::core::plugin::HandleOutcome::from_block(#block))
+            }
+        }
+    });
+    let regex_dispatch = (!methods.regexes.is_empty()).then(|| {
+        quote! {
+            async fn handle_regex(
+                &self,
+                ctx: &#ctx_ty,
+                regex_match: ayiou::core::model::RegexMatch,
+            ) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
+                match regex_match.pattern() {
+                    #(#regex_arms,)*
+                    _ => Ok(ayiou::core::plugin::HandleOutcome::pass()),
+                }
+            }
+        }
+    });
 
     quote! {
         #item_impl
@@ -242,40 +430,100 @@ fn render_plugin_impl(
                 vec![#(#handler_decls),*]
             }
 
-            async fn handle_with_invocation(
-                &self,
-                ctx: &#ctx_ty,
-                invocation: Option<ayiou::core::model::CommandInvocation>,
-            ) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
-                let Some(line) = invocation else {
-                    return Ok(ayiou::core::plugin::HandleOutcome::pass());
-                };
-                self.__ayiou_dispatch_command(ctx, line.command(), line.args()).await
-            }
+            #command_dispatch
+
+            #regex_dispatch
 
             async fn handle(&self, _ctx: &#ctx_ty) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
                 Ok(ayiou::core::plugin::HandleOutcome::pass())
             }
         }
 
-        impl #plugin_ty {
-            async fn __ayiou_dispatch_command(
-                &self,
-                ctx: &#ctx_ty,
-                command: &str,
-                args: &str,
-            ) -> anyhow::Result<ayiou::core::plugin::HandleOutcome> {
-                match command {
-                    #(#dispatch_arms,)*
-                    _ => Ok(ayiou::core::plugin::HandleOutcome::pass()),
-                }
-            }
-        }
+        #command_dispatcher
 
         #registration
     }
 }
 
+impl HandlerOptions {
+    fn new(attrs: &CommandAttrs, plugin_attrs: &PluginAttrs) -> Self {
+        Self {
+            priority: attrs.priority,
+            block: attrs.block.unwrap_or(true),
+            permissions: attrs.permissions.clone(),
+            parse_error: attrs.parse_error.or(plugin_attrs.parse_error),
+            rate_limits: rate_limit_decls(
+                &attrs.rate_limits,
+                attrs.rate_limit_scope.as_ref(),
+                attrs.rate_limit_action.as_ref(),
+            ),
+            timeout_ms: attrs.timeout_ms,
+            on_error: attrs
+                .on_error
+                .clone()
+                .or_else(|| plugin_attrs.on_error.clone()),
+            channel_kinds: attrs.channel_kinds.clone(),
+            platforms: attrs.platforms.clone(),
+            bots: attrs.bots.clone(),
+            to_me: attrs.to_me,
+        }
+    }
+
+    /// `HandlerDecl` builder calls applying these options.
+    fn render(&self) -> TokenStream {
+        let permissions = self.permissions.iter().map(|value| {
+            quote! { ayiou::core::plugin::Permission::custom(#value) }
+        });
+        let priority = self.priority.map(|value| quote! { .priority(#value) });
+        let block = self.block;
+        let parse_error = self.parse_error.map(|policy| {
+            let variant = match policy {
+                ParseErrorPolicy::Reply => quote! { Reply },
+                ParseErrorPolicy::Silent => quote! { Silent },
+                ParseErrorPolicy::Propagate => quote! { Propagate },
+            };
+            quote! { .parse_error(ayiou::core::command::ParseErrorPolicy::#variant) }
+        });
+        let rate_limits = self
+            .rate_limits
+            .iter()
+            .map(|limit| quote! { .rate_limit(#limit) });
+        let timeout = self
+            .timeout_ms
+            .map(|ms| quote! { .timeout(::std::time::Duration::from_millis(#ms)) });
+        let on_error = self.on_error.as_ref().map(|variant| {
+            quote! { .on_error(ayiou::core::plugin::DispatchErrorPolicy::#variant) }
+        });
+        let channel_kinds = (!self.channel_kinds.is_empty()).then(|| {
+            let kinds = &self.channel_kinds;
+            quote! { .channel_kinds([#(ayiou::core::model::ChannelKind::#kinds),*]) }
+        });
+        let platforms = (!self.platforms.is_empty()).then(|| {
+            let platforms = &self.platforms;
+            quote! { .platforms([#(#platforms),*]) }
+        });
+        let bots = (!self.bots.is_empty()).then(|| {
+            let bots = &self.bots;
+            quote! { .bots([#(#bots),*]) }
+        });
+        let to_me = self.to_me.then(|| quote! { .to_me() });
+
+        quote! {
+            .require_permissions(Vec::<ayiou::core::plugin::Permission>::from([#(#permissions),*]))
+            #priority
+            .block(#block)
+            #parse_error
+            #(#rate_limits)*
+            #timeout
+            #on_error
+            #channel_kinds
+            #platforms
+            #bots
+            #to_me
+        }
+    }
+}
+
 fn parse_plugin_attrs(args: Vec<Meta>) -> Result<PluginAttrs> {
     let mut out = PluginAttrs::default();
 
@@ -293,6 +541,8 @@ fn parse_plugin_attrs(args: Vec<Meta>) -> Result<PluginAttrs> {
                     "version" => out.version = Some(expect_string_expr(value)?),
                     "prefix" => out.prefixes.push(expect_string_expr(value)?),
                     "register" => out.register = expect_bool_expr(value)?,
+                    "parse_error" => out.parse_error = Some(expect_parse_error_expr(value)?),
+                    "on_error" => out.on_error = Some(expect_on_error_expr(value)?),
                     _ => {
                         return Err(syn::Error::new(
                             Span::call_site(),
@@ -332,6 +582,10 @@ fn parse_command_attr(attr: &syn::Attribute) -> Result<CommandAttrs> {
                 let value: Expr = meta.value()?.parse()?;
                 out.name = Some(expect_string_expr(value)?);
             }
+            "group" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.group = Some(expect_string_expr(value)?);
+            }
             "alias" => {
                 let value: Expr = meta.value()?.parse()?;
                 out.aliases.push(expect_string_expr(value)?);
@@ -364,6 +618,66 @@ fn parse_command_attr(attr: &syn::Attribute) -> Result<CommandAttrs> {
                 let value: Expr = meta.value()?.parse()?;
                 out.permissions.extend(expect_string_array_expr(value)?);
             }
+            "parse_error" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.parse_error = Some(expect_parse_error_expr(value)?);
+            }
+            "cooldown" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.rate_limits.push(RateLimitAttrs {
+                    calls: 1,
+                    window_ms: expect_duration_expr(value)?,
+                });
+            }
+            "timeout" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.timeout_ms = Some(expect_duration_expr(value)?);
+            }
+            "on_error" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.on_error = Some(expect_on_error_expr(value)?);
+            }
+            "rate_limit" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.rate_limits.push(expect_rate_limit_expr(value)?);
+            }
+            "rate_limit_scope" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.rate_limit_scope = Some(expect_variant_expr(
+                    value,
+                    &[("user", "User"), ("group", "Group"), ("global", "Global")],
+                )?);
+            }
+            "channel" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.channel_kinds.push(expect_variant_expr(
+                    value,
+                    &[
+                        ("group", "Group"),
+                        ("direct", "Direct"),
+                        ("channel", "Channel"),
+                    ],
+                )?);
+            }
+            "platform" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.platforms.push(expect_string_expr(value)?);
+            }
+            "bot" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.bots.push(expect_string_expr(value)?);
+            }
+            "to_me" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.to_me = expect_bool_expr(value)?;
+            }
+            "rate_limit_action" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.rate_limit_action = Some(expect_variant_expr(
+                    value,
+                    &[("silent", "Silent"), ("reply", "Reply")],
+                )?);
+            }
             _ => {
                 return Err(syn::Error::new(
                     Span::call_site(),
@@ -378,23 +692,152 @@ fn parse_command_attr(attr: &syn::Attribute) -> Result<CommandAttrs> {
     Ok(out)
 }
 
-fn parse_command_method(method: &syn::ImplItemFn, attrs: CommandAttrs) -> Result<CommandMethod> {
+fn parse_command_method(
+    method: &syn::ImplItemFn,
+    attrs: CommandAttrs,
+    plugin_attrs: &PluginAttrs,
+) -> Result<CommandMethod> {
     let fn_name = method.sig.ident.clone();
+    let options = HandlerOptions::new(&attrs, plugin_attrs);
     let command = attrs.name.unwrap_or_else(|| fn_name.to_string());
     let labels = std::iter::once(command.clone())
         .chain(attrs.aliases.iter().cloned())
         .collect();
-    let meta = CommandMetaAttrs {
+    let mut meta = CommandMetaAttrs {
         command,
+        group: attrs.group,
         aliases: attrs.aliases,
-        summary: attrs.summary,
+        summary: attrs.summary.or_else(|| doc_summary(&method.attrs)),
         usage: attrs.usage,
         examples: attrs.examples,
-        priority: attrs.priority,
-        block: attrs.block.unwrap_or(true),
-        permissions: attrs.permissions,
     };
 
+    let args_inputs = handler_args(method)?;
+
+    let mut positional = Vec::new();
+    let mut named = Vec::new();
+    let mut call_args = Vec::new();
+    for arg in args_inputs {
+        let FnArg::Typed(pat_type) = arg else {
+            return Err(syn::Error::new_spanned(arg, "Invalid command argument"));
+        };
+
+        let Pat::Ident(PatIdent { ident, .. }) = pat_type.pat.as_ref() else {
+            return Err(syn::Error::new_spanned(
+                &pat_type.pat,
+                "argument pattern must be an identifier",
+            ));
+        };
+
+        call_args.push(ident.clone());
+        match parse_named_arg_attr(pat_type)? {
+            Some(attrs) => named.push((pat_type, ident, attrs)),
+            None => positional.push((pat_type, ident)),
+        }
+    }
+
+    let tokens_binding = if named.is_empty() {
+        quote! { __ayiou_tokens }
+    } else {
+        quote! { mut __ayiou_tokens }
+    };
+    let mut parser_stmts = vec![quote! {
+        let #tokens_binding = ayiou::core::command::tokenize_command_args(args)?;
+    }];
+    let mut usage_args = Vec::new();
+
+    for (pat_type, ident, attrs) in &named {
+        let (stmt, usage) = named_arg_parser(ident, &pat_type.ty, attrs)?;
+        parser_stmts.push(stmt);
+        usage_args.push(usage);
+    }
+    if !named.is_empty() {
+        parser_stmts.push(quote! {
+            ayiou::core::command::ensure_no_unknown_options(&mut __ayiou_tokens)?;
+        });
+    }
+    let args_binding = if positional.is_empty() {
+        quote! { __ayiou_args }
+    } else {
+        quote! { mut __ayiou_args }
+    };
+    parser_stmts.push(quote! {
+        let #args_binding = ayiou::core::command::CommandArgs::new(ctx, __ayiou_tokens);
+    });
+
+    for (idx, (pat_type, ident)) in positional.iter().enumerate() {
+        let var = ident;
+        let arg_name = arg_display_name(ident);
+        let ty = &pat_type.ty;
+        let is_last = idx == positional.len() - 1;
+
+        // `AtBot` checks the message mentions; users never type it.
+        if !is_at_bot_type(ty) {
+            usage_args.push(if unwrap_option_type(ty).is_some() {
+                format!("[{arg_name}]")
+            } else if is_last && is_string_type(ty) {
+                format!("<{arg_name}...>")
+            } else if is_last && is_vec_string_type(ty) {
+                format!("[{arg_name}...]")
+            } else {
+                format!("<{arg_name}>")
+            });
+        }
+
+        if let Some(inner_ty) = unwrap_option_type(ty) {
+            parser_stmts.push(quote! {
+                let #var = if <#inner_ty as ayiou::core::command::FromCommandArg>::is_present(&__ayiou_args) {
+                    Some(<#inner_ty as ayiou::core::command::FromCommandArg>::from_command_arg(
+                        &mut __ayiou_args,
+                        #arg_name,
+                    )?)
+                } else {
+                    None
+                };
+            });
+        } else if is_last && is_string_type(ty) {
+            parser_stmts.push(quote! {
+                let #var = __ayiou_args.rest().join(" ");
+            });
+        } else if is_last && is_vec_string_type(ty) {
+            parser_stmts.push(quote! {
+                let #var = __ayiou_args.rest();
+            });
+        } else {
+            parser_stmts.push(quote! {
+                let #var = <#ty as ayiou::core::command::FromCommandArg>::from_command_arg(
+                    &mut __ayiou_args,
+                    #arg_name,
+                )?;
+            });
+        }
+    }
+
+    parser_stmts.push(quote! {
+        __ayiou_args.finish()?;
+    });
+
+    if meta.usage.is_none() && !usage_args.is_empty() {
+        let prefix = plugin_attrs.prefixes.first().map_or("", String::as_str);
+        let full_name = meta.group.as_ref().map_or_else(
+            || meta.command.clone(),
+            |group| format!("{group} {}", meta.command),
+        );
+        meta.usage = Some(format!("{prefix}{full_name} {}", usage_args.join(" ")));
+    }
+
+    Ok(CommandMethod {
+        fn_name,
+        labels,
+        meta,
+        options,
+        parser_stmts,
+        call_args,
+    })
+}
+
+/// Arguments after `&self` and the context reference.
+fn handler_args(method: &syn::ImplItemFn) -> Result<Vec<&FnArg>> {
     let mut inputs = method.sig.inputs.iter();
 
     match inputs.next() {
@@ -424,80 +867,229 @@ fn parse_command_method(method: &syn::ImplItemFn, attrs: CommandAttrs) -> Result
         }
     }
 
-    let args_inputs: Vec<_> = inputs.collect();
+    Ok(inputs.collect())
+}
 
-    let mut parser_stmts = vec![quote! {
-        let __ayiou_tokens = ayiou::core::command::tokenize_command_args(args)?;
-        let mut __ayiou_index = 0usize;
-    }];
+/// Bind a `#[regex]` method's arguments to the named captures of the same name.
+///
+/// `Option<T>` arguments accept a group that did not take part in the match,
+/// and a `RegexMatch` argument receives the whole match.
+fn parse_regex_method(
+    method: &syn::ImplItemFn,
+    patterns: Vec<String>,
+    attrs: &CommandAttrs,
+    plugin_attrs: &PluginAttrs,
+) -> Result<RegexMethod> {
+    let command_only = [
+        ("name", attrs.name.is_some()),
+        ("group", attrs.group.is_some()),
+        ("alias", !attrs.aliases.is_empty()),
+        ("summary", attrs.summary.is_some()),
+        ("usage", attrs.usage.is_some()),
+        ("examples", !attrs.examples.is_empty()),
+    ];
+    if let Some((key, _)) = command_only.iter().find(|(_, set)| *set) {
+        return Err(syn::Error::new_spanned(
+            &method.sig.ident,
+            format!("`{key}` does not apply to a #[regex] handler"),
+        ));
+    }
 
+    let mut capture_stmts = Vec::new();
     let mut call_args = Vec::new();
-
-    for (idx, arg) in args_inputs.iter().enumerate() {
+    for arg in handler_args(method)? {
         let FnArg::Typed(pat_type) = arg else {
-            return Err(syn::Error::new_spanned(arg, "Invalid command argument"));
+            return Err(syn::Error::new_spanned(arg, "Invalid regex argument"));
         };
-
         let Pat::Ident(PatIdent { ident, .. }) = pat_type.pat.as_ref() else {
             return Err(syn::Error::new_spanned(
                 &pat_type.pat,
                 "argument pattern must be an identifier",
             ));
         };
+        if let Some(attr) = pat_type
+            .attrs
+            .iter()
+            .find(|attr| attr.path().is_ident("arg"))
+        {
+            return Err(syn::Error::new_spanned(
+                attr,
+                "#[arg] does not apply to a #[regex] handler",
+            ));
+        }
 
-        let var = ident.clone();
-        let arg_name = ident.to_string();
+        call_args.push(ident.clone());
         let ty = &pat_type.ty;
-        let is_last = idx == args_inputs.len() - 1;
-
-        if let Some(inner_ty) = unwrap_option_type(ty) {
-            parser_stmts.push(quote! {
-                let #var = if __ayiou_index < __ayiou_tokens.len() {
-                    Some(ayiou::core::command::parse_typed_arg::<#inner_ty>(
-                        &__ayiou_tokens,
-                        &mut __ayiou_index,
-                        #arg_name,
-                    )?)
-                } else {
-                    None
-                };
+        if is_regex_match_type(ty) {
+            capture_stmts.push(quote! {
+                let #ident = regex_match.clone();
             });
-        } else if is_last && is_string_type(ty) {
-            parser_stmts.push(quote! {
-                let #var = __ayiou_tokens[__ayiou_index..].join(" ");
-                __ayiou_index = __ayiou_tokens.len();
-            });
-        } else if is_last && is_vec_string_type(ty) {
-            parser_stmts.push(quote! {
-                let #var = __ayiou_tokens[__ayiou_index..].to_vec();
-                __ayiou_index = __ayiou_tokens.len();
-            });
-        } else {
-            parser_stmts.push(quote! {
-                let #var = ayiou::core::command::parse_typed_arg::<#ty>(
-                    &__ayiou_tokens,
-                    &mut __ayiou_index,
-                    #arg_name,
+            continue;
+        }
+
+        let name = arg_display_name(ident);
+        if !patterns
+            .iter()
+            .any(|pattern| declares_capture(pattern, &name))
+        {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!("no pattern of this handler declares a `(?<{name}>...)` group"),
+            ));
+        }
+        capture_stmts.push(match unwrap_option_type(ty) {
+            Some(inner_ty) => quote! {
+                let #ident = ayiou::core::command::parse_capture::<#inner_ty>(&regex_match, #name)?;
+            },
+            None => quote! {
+                let #ident = ayiou::core::command::required_capture(
+                    ayiou::core::command::parse_capture::<#ty>(&regex_match, #name)?,
+                    #name,
                 )?;
-            });
+            },
+        });
+    }
+
+    Ok(RegexMethod {
+        fn_name: method.sig.ident.clone(),
+        patterns,
+        options: HandlerOptions::new(attrs, plugin_attrs),
+        capture_stmts,
+        call_args,
+    })
+}
+
+fn declares_capture(pattern: &str, name: &str) -> bool {
+    pattern.contains(&format!("(?<{name}>")) || pattern.contains(&format!("(?P<{name}>"))
+}
+
+fn parse_named_arg_attr(pat_type: &syn::PatType) -> Result<Option<NamedArgAttrs>> {
+    let Some(attr) = pat_type
+        .attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("arg"))
+    else {
+        return Ok(None);
+    };
+
+    let mut out = NamedArgAttrs::default();
+    if matches!(&attr.meta, Meta::Path(_)) {
+        return Ok(Some(out));
+    }
+
+    attr.parse_nested_meta(|meta| {
+        let key = meta
+            .path
+            .get_ident()
+            .map(std::string::ToString::to_string)
+            .ok_or_else(|| syn::Error::new_spanned(&meta.path, "Unsupported arg key"))?;
+
+        match key.as_str() {
+            "long" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.long = Some(expect_string_expr(value)?);
+            }
+            "short" => {
+                let value: syn::LitChar = meta.value()?.parse()?;
+                out.short = Some(value.value());
+            }
+            "default" => {
+                let value: Expr = meta.value()?.parse()?;
+                out.default = Some(expect_string_expr(value)?);
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    Span::call_site(),
+                    format!("Unsupported arg key `{key}`"),
+                ));
+            }
         }
 
-        call_args.push(var);
+        Ok(())
+    })?;
+
+    Ok(Some(out))
+}
+
+/// Parser statement and usage fragment of a `#[arg]` flag or option.
+fn named_arg_parser(
+    ident: &syn::Ident,
+    ty: &Type,
+    attrs: &NamedArgAttrs,
+) -> Result<(TokenStream, String)> {
+    let name = arg_display_name(ident);
+    let long = attrs.long.clone().unwrap_or_else(|| name.replace('_', "-"));
+    let short = attrs.short.map_or_else(
+        || quote! { ::std::option::Option::None },
+        |short| quote! { ::std::option::Option::Some(#short) },
+    );
+    let switch = match attrs.short {
+        Some(short) => format!("-{short}|--{long}"),
+        None => format!("--{long}"),
+    };
+
+    if is_bool_type(ty) {
+        if attrs.default.is_some() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "boolean flags cannot declare a default",
+            ));
+        }
+        return Ok((
+            quote! {
+                let #ident = ayiou::core::command::take_flag(&mut __ayiou_tokens, #long, #short)?;
+            },
+            format!("[{switch}]"),
+        ));
     }
 
-    parser_stmts.push(quote! {
-        ayiou::core::command::ensure_no_extra_args(&__ayiou_tokens, __ayiou_index)?;
-    });
+    if let Some(inner_ty) = unwrap_option_type(ty) {
+        if attrs.default.is_some() {
+            return Err(syn::Error::new_spanned(
+                ident,
+                "optional options cannot declare a default",
+            ));
+        }
+        return Ok((
+            quote! {
+                let #ident = ayiou::core::command::parse_option::<#inner_ty>(
+                    &mut __ayiou_tokens,
+                    #long,
+                    #short,
+                )?;
+            },
+            format!("[{switch} <{name}>]"),
+        ));
+    }
 
-    Ok(CommandMethod {
-        fn_name,
-        labels,
-        meta,
-        parser_stmts,
-        call_args,
+    let parsed = quote! {
+        ayiou::core::command::parse_option::<#ty>(&mut __ayiou_tokens, #long, #short)?
+    };
+    Ok(match &attrs.default {
+        Some(default) => (
+            quote! {
+                let #ident = match #parsed {
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => {
+                        ayiou::core::command::parse_option_value::<#ty>(#default, #long)?
+                    }
+                };
+            },
+            format!("[{switch} <{name}={default}>]"),
+        ),
+        None => (
+            quote! {
+                let #ident = ayiou::core::command::required_option(#parsed, #long)?;
+            },
+            format!("{switch} <{name}>"),
+        ),
     })
 }
 
+fn arg_display_name(ident: &syn::Ident) -> String {
+    ident.to_string().trim_start_matches('_').to_string()
+}
+
 fn expect_string_expr(value: Expr) -> Result<String> {
     if let Expr::Lit(ExprLit {
         lit: Lit::Str(value),
@@ -522,6 +1114,117 @@ fn expect_bool_expr(value: Expr) -> Result<bool> {
     }
 }
 
+fn expect_parse_error_expr(value: Expr) -> Result<ParseErrorPolicy> {
+    let span_value = value.clone();
+    match expect_string_expr(value)?.as_str() {
+        "reply" => Ok(ParseErrorPolicy::Reply),
+        "silent" => Ok(ParseErrorPolicy::Silent),
+        "propagate" => Ok(ParseErrorPolicy::Propagate),
+        _ => Err(syn::Error::new_spanned(
+            span_value,
+            "Expected one of \"reply\", \"silent\" or \"propagate\"",
+        )),
+    }
+}
+
+fn expect_on_error_expr(value: Expr) -> Result<syn::Ident> {
+    expect_variant_expr(value, &[("stop", "Stop"), ("continue", "Continue")])
+}
+
+fn expect_variant_expr(value: Expr, variants: &[(&str, &str)]) -> Result<syn::Ident> {
+    let span_value = value.clone();
+    let name = expect_string_expr(value)?;
+    variants
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, variant)| syn::Ident::new(variant, Span::call_site()))
+        .ok_or_else(|| {
+            let expected: Vec<String> =
+                variants.iter().map(|(key, _)| format!("{key:?}")).collect();
+            syn::Error::new_spanned(
+                span_value,
+                format!("Expected one of {}", expected.join(", ")),
+            )
+        })
+}
+
+fn expect_duration_expr(value: Expr) -> Result<u64> {
+    let span_value = value.clone();
+    parse_duration_ms(&expect_string_expr(value)?).ok_or_else(|| {
+        syn::Error::new_spanned(
+            span_value,
+            "Expected a duration such as \"30s\", \"10m\" or \"1h30m\"",
+        )
+    })
+}
+
+fn expect_rate_limit_expr(value: Expr) -> Result<RateLimitAttrs> {
+    let span_value = value.clone();
+    let text = expect_string_expr(value)?;
+    text.split_once('/')
+        .and_then(|(calls, window)| {
+            Some(RateLimitAttrs {
+                calls: calls.trim().parse().ok().filter(|calls| *calls > 0)?,
+                window_ms: parse_duration_ms(window.trim())?,
+            })
+        })
+        .ok_or_else(|| {
+            syn::Error::new_spanned(span_value, "Expected `calls/window`, such as \"5/1m\"")
+        })
+}
+
+/// Milliseconds in `90`, `45s`, `10m`, `1h30m`, `2d` or `500ms`; a bare number counts seconds.
+fn parse_duration_ms(input: &str) -> Option<u64> {
+    if let Ok(seconds) = input.parse::<u64>() {
+        return seconds.checked_mul(1000).filter(|ms| *ms > 0);
+    }
+
+    let mut total: u64 = 0;
+    let mut rest = input;
+    while !rest.is_empty() {
+        let digits = rest.find(|ch: char| !ch.is_ascii_digit())?;
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|ch: char| ch.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let unit_ms = match &rest[..unit_len] {
+            "ms" => 1,
+            "s" => 1000,
+            "m" => 60 * 1000,
+            "h" => 60 * 60 * 1000,
+            "d" => 24 * 60 * 60 * 1000,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total = total.checked_add(value.checked_mul(unit_ms)?)?;
+    }
+    (total > 0).then_some(total)
+}
+
+fn rate_limit_decls(
+    limits: &[RateLimitAttrs],
+    scope: Option<&syn::Ident>,
+    action: Option<&syn::Ident>,
+) -> Vec<TokenStream> {
+    let scope = scope.map(|scope| quote! { .scope(ayiou::core::plugin::RateLimitScope::#scope) });
+    let action =
+        action.map(|action| quote! { .action(ayiou::core::plugin::RateLimitAction::#action) });
+    limits
+        .iter()
+        .map(|RateLimitAttrs { calls, window_ms }| {
+            quote! {
+                ayiou::core::plugin::RateLimit::new(
+                    #calls,
+                    ::std::time::Duration::from_millis(#window_ms),
+                )
+                #scope
+                #action
+            }
+        })
+        .collect()
+}
+
 fn expect_i32_expr(value: Expr) -> Result<i32> {
     match &value {
         Expr::Lit(ExprLit {
@@ -576,6 +1279,18 @@ fn extract_self_type_ident(plugin_ty: &Type) -> Result<syn::Ident> {
     ))
 }
 
+fn is_bool_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.is_ident("bool"))
+}
+
+fn is_regex_match_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|seg| seg.ident == "RegexMatch"))
+}
+
+fn is_at_bot_type(ty: &Type) -> bool {
+    matches!(ty, Type::Path(path) if path.path.segments.last().is_some_and(|seg| seg.ident == "AtBot"))
+}
+
 fn is_string_type(ty: &Type) -> bool {
     if let Type::Path(path) = ty
         && let Some(seg) = path.path.segments.last()
@@ -613,3 +1328,31 @@ fn unwrap_option_type(ty: &Type) -> Option<Type> {
 
     None
 }
+
+pub(crate) fn doc_comment(attrs: &[syn::Attribute]) -> Option<String> {
+    let lines: Vec<String> = attrs
+        .iter()
+        .filter_map(|attr| match &attr.meta {
+            Meta::NameValue(MetaNameValue {
+                path,
+                value:
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(value),
+                        ..
+                    }),
+                ..
+            }) if path.is_ident("doc") => Some(value.value().trim().to_string()),
+            _ => None,
+        })
+        .collect();
+    let doc = lines.join("\n").trim().to_string();
+
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// First paragraph of the doc comment, joined into one line.
+fn doc_summary(attrs: &[syn::Attribute]) -> Option<String> {
+    let doc = doc_comment(attrs)?;
+    let paragraph = doc.split("\n\n").next().unwrap_or_default();
+    Some(paragraph.lines().collect::<Vec<_>>().join(" "))
+}
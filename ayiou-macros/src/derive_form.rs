@@ -0,0 +1,122 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Result};
+
+use crate::attr_plugin::doc_comment;
+
+struct FormFieldAttrs {
+    ident: syn::Ident,
+    ty: syn::Type,
+    prompt: String,
+    validate: Option<syn::Path>,
+}
+
+pub fn expand_form(input: &DeriveInput) -> Result<TokenStream> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            input,
+            "#[derive(Form)] only supports structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &data.fields,
+            "#[derive(Form)] requires named fields",
+        ));
+    };
+
+    let fields = fields
+        .named
+        .iter()
+        .map(parse_form_field)
+        .collect::<Result<Vec<_>>>()?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let field_decls = fields.iter().map(|field| {
+        let name = field.ident.to_string();
+        let prompt = &field.prompt;
+        quote! { ayiou::core::form::FormField::new(#name, #prompt) }
+    });
+    let validate_arms = fields.iter().map(|field| {
+        let name = field.ident.to_string();
+        let ty = &field.ty;
+        let check = field.validate.as_ref().map_or_else(
+            || quote! { ::std::result::Result::Ok(()) },
+            |validate| quote! { #validate(&__ayiou_value) },
+        );
+        quote! {
+            #name => {
+                let __ayiou_value = ayiou::core::form::parse_field::<#ty>(input)?;
+                #check
+            }
+        }
+    });
+    let build_fields = fields.iter().map(|field| {
+        let field_ident = &field.ident;
+        let name = field_ident.to_string();
+        let ty = &field.ty;
+        quote! {
+            #field_ident: ayiou::core::form::parse_answer::<#ty>(answers, #name)?
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ayiou::core::form::Form for #ident #ty_generics #where_clause {
+            fn fields() -> &'static [ayiou::core::form::FormField] {
+                const FIELDS: &[ayiou::core::form::FormField] = &[#(#field_decls),*];
+                FIELDS
+            }
+
+            fn validate(field: &str, input: &str) -> ::std::result::Result<(), String> {
+                match field {
+                    #(#validate_arms,)*
+                    _ => ::std::result::Result::Ok(()),
+                }
+            }
+
+            fn build(answers: &ayiou::core::form::FormAnswers) -> ayiou::anyhow::Result<Self> {
+                ::std::result::Result::Ok(Self {
+                    #(#build_fields),*
+                })
+            }
+        }
+    })
+}
+
+fn parse_form_field(field: &syn::Field) -> Result<FormFieldAttrs> {
+    let ident = field
+        .ident
+        .clone()
+        .ok_or_else(|| syn::Error::new_spanned(field, "form fields must be named"))?;
+    let mut prompt = None;
+    let mut validate = None;
+
+    for attr in &field.attrs {
+        if attr.path().is_ident("form") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("prompt") {
+                    let value: syn::LitStr = meta.value()?.parse()?;
+                    prompt = Some(value.value());
+                    Ok(())
+                } else if meta.path.is_ident("validate") {
+                    validate = Some(meta.value()?.parse::<syn::Path>()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("Unsupported form key"))
+                }
+            })?;
+        }
+    }
+
+    let prompt = prompt
+        .or_else(|| doc_comment(&field.attrs))
+        .unwrap_or_else(|| format!("{ident}?"));
+
+    Ok(FormFieldAttrs {
+        ident,
+        ty: field.ty.clone(),
+        prompt,
+        validate,
+    })
+}
@@ -1,16 +1,19 @@
 #![allow(clippy::multiple_crate_versions)]
 
 use proc_macro::TokenStream;
-use syn::{Meta, parse_macro_input, punctuated::Punctuated};
+use syn::{DeriveInput, Meta, parse_macro_input, punctuated::Punctuated};
 
 mod attr_plugin;
+mod derive_form;
 
 use attr_plugin::expand_plugin;
+use derive_form::expand_form;
 
 /// Generate a `RuntimePlugin` implementation from an `impl` block.
 ///
 /// `#[plugin]` is the default authoring entrypoint. Put it on an `impl` block;
-/// async methods in the impl become command handlers named after the method.
+/// async methods in the impl become command handlers named after the method,
+/// or regex handlers when marked with `#[regex("...")]`.
 ///
 /// # Example
 ///
@@ -23,7 +26,8 @@ use attr_plugin::expand_plugin;
 /// #[plugin(name = "echo", prefix = "/")]
 /// impl EchoPlugin {
 ///     async fn echo(&self, ctx: &Context, text: String) -> anyhow::Result<()> {
-///         ctx.reply_text(format!("Echo: {}", text)).await
+///         ctx.reply_text(format!("Echo: {}", text)).await?;
+///         Ok(())
 ///     }
 /// }
 /// ```
@@ -36,6 +40,10 @@ use attr_plugin::expand_plugin;
 /// - `prefix`: Command prefix accepted by this plugin (repeatable)
 /// - `context`: Custom context type (defaults to `Context`)
 /// - `register`: Whether to auto-register this plugin (defaults to `true`)
+/// - `parse_error`: `"reply"`, `"silent"` or `"propagate"` when arguments fail to parse
+///   (defaults to `"reply"`; commands can override it)
+/// - `on_error`: `"stop"` or `"continue"` running the event's remaining handlers after a
+///   command fails (defaults to the bot's policy; commands can override it)
 #[proc_macro_attribute]
 pub fn plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attrs = parse_macro_input!(attr with Punctuated::<Meta, syn::Token![,]>::parse_terminated);
@@ -52,3 +60,48 @@ pub fn plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
 pub fn command(_attr: TokenStream, item: TokenStream) -> TokenStream {
     item
 }
+
+/// Mark a method in `#[plugin] impl` as a regex handler for `pattern`.
+///
+/// Repeat it to match several patterns. Arguments after the context bind to
+/// the named capture groups of the same name.
+///
+/// This attribute is only consumed by `#[plugin]` and is otherwise a no-op.
+#[proc_macro_attribute]
+pub fn regex(_attr: TokenStream, item: TokenStream) -> TokenStream {
+    item
+}
+
+/// Implement `ayiou::core::form::Form` for a struct with named fields.
+///
+/// Fields are asked in declaration order and parsed with `FromStr`.
+///
+/// # Example
+///
+/// ```ignore
+/// use ayiou::Form;
+///
+/// #[derive(Form)]
+/// struct Signup {
+///     /// What should we call you?
+///     name: String,
+///     #[form(prompt = "How old are you?", validate = adult)]
+///     age: u8,
+/// }
+///
+/// fn adult(age: &u8) -> Result<(), String> {
+///     if *age >= 18 { Ok(()) } else { Err("must be an adult".to_string()) }
+/// }
+/// ```
+///
+/// # Attributes
+///
+/// - `prompt`: Question for the field (defaults to its doc comment)
+/// - `validate`: Path to `fn(&T) -> Result<(), String>` run after parsing
+#[proc_macro_derive(Form, attributes(form))]
+pub fn derive_form(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    expand_form(&input)
+        .unwrap_or_else(|err| err.to_compile_error())
+        .into()
+}
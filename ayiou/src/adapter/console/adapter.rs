@@ -9,6 +9,7 @@ use crate::{
         adapter::{Adapter, AdapterRuntime},
         context::Context,
         driver::Driver,
+        model::{BotId, EventEnvelope, KernelEvent, PlatformId},
         plugin::{Capability, OutboundSender},
     },
     driver::console::ConsoleDriver,
@@ -55,6 +56,12 @@ impl ConsoleProtocol {
 
         Some(Ctx::new(raw).into_context(Some(self.sender.clone())))
     }
+
+    fn lifecycle(&self, event: KernelEvent) -> Context {
+        let envelope = EventEnvelope::new(BotId::new("console"), PlatformId::new("console"))
+            .with_kernel_event(event);
+        Context::new(envelope, Some(self.sender.clone()), Ctx::new(String::new()))
+    }
 }
 
 #[async_trait]
@@ -76,6 +83,15 @@ impl Adapter for ConsoleAdapter {
                 }
             });
 
+            if ctx_tx
+                .send(protocol.lifecycle(KernelEvent::BotConnected))
+                .await
+                .is_err()
+            {
+                driver_handle.abort();
+                return;
+            }
+
             while let Some(raw) = raw_rx.recv().await {
                 if let Some(ctx) = protocol.handle_packet(raw)
                     && ctx_tx.send(ctx).await.is_err()
@@ -84,13 +100,67 @@ impl Adapter for ConsoleAdapter {
                 }
             }
 
+            let _ = ctx_tx
+                .send(protocol.lifecycle(KernelEvent::BotDisconnected))
+                .await;
+
             driver_handle.abort();
         });
 
         AdapterRuntime {
             events: ctx_rx,
             sender: Some(sender),
-            capabilities: vec![Capability::ProactiveSend, Capability::custom("console")],
+            capabilities: vec![
+                Capability::ProactiveSend,
+                Capability::MessageDelete,
+                Capability::Reaction,
+                Capability::MessageEdit,
+                Capability::custom("console"),
+            ],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::ConsoleAdapter;
+    use crate::core::{adapter::Adapter, driver::Driver, model::KernelEvent};
+
+    struct LinesDriver(Vec<&'static str>);
+
+    #[async_trait::async_trait]
+    impl Driver for LinesDriver {
+        type Inbound = String;
+        type Outbound = String;
+
+        async fn run(
+            self: Box<Self>,
+            inbound_tx: mpsc::Sender<String>,
+            _outbound_rx: mpsc::Receiver<String>,
+        ) -> anyhow::Result<()> {
+            for line in self.0 {
+                inbound_tx.send(line.to_string()).await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn console_adapter_brackets_lines_with_lifecycle_events() {
+        let mut runtime = ConsoleAdapter::with_driver(LinesDriver(vec!["hello"]))
+            .start()
+            .await;
+
+        let connected = runtime.events.recv().await.unwrap();
+        assert_eq!(connected.kernel_event(), Some(&KernelEvent::BotConnected));
+        let line = runtime.events.recv().await.unwrap();
+        assert_eq!(line.text(), "hello");
+        let disconnected = runtime.events.recv().await.unwrap();
+        assert_eq!(
+            disconnected.kernel_event(),
+            Some(&KernelEvent::BotDisconnected)
+        );
+    }
+}
@@ -1,21 +1,34 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU64, Ordering},
+};
+
 use anyhow::Result;
 use async_trait::async_trait;
 use tokio::sync::mpsc;
 
 use crate::core::{
-    model::{MessageSegment, OutboundMessage, OutboundReceipt},
-    plugin::OutboundSender,
+    model::{ChannelRef, MessageSegment, OutboundMessage, OutboundReceipt},
+    plugin::{MessageActions, OutboundSender},
 };
 
+/// Console sender.
+///
+/// Outbound messages get sequential ids so message actions can refer to them;
+/// deletes, reactions and edits are echoed as annotated lines.
 #[derive(Clone)]
 pub struct ConsoleSender {
     outgoing_tx: mpsc::Sender<String>,
+    next_message_id: Arc<AtomicU64>,
 }
 
 impl ConsoleSender {
     #[must_use]
-    pub const fn new(outgoing_tx: mpsc::Sender<String>) -> Self {
-        Self { outgoing_tx }
+    pub fn new(outgoing_tx: mpsc::Sender<String>) -> Self {
+        Self {
+            outgoing_tx,
+            next_message_id: Arc::new(AtomicU64::new(1)),
+        }
     }
 }
 
@@ -23,45 +36,143 @@ impl ConsoleSender {
 impl OutboundSender for ConsoleSender {
     async fn send(&self, message: OutboundMessage) -> Result<OutboundReceipt> {
         let mut rendered = String::new();
+        render_segments(&mut rendered, &message.segments);
 
-        for segment in message.segments {
-            match segment {
-                MessageSegment::Text { text } => rendered.push_str(&text),
-                MessageSegment::Mention { user_id } => {
-                    if !rendered.is_empty() {
-                        rendered.push(' ');
-                    }
-                    rendered.push('@');
-                    rendered.push_str(&user_id);
-                }
-                MessageSegment::Image { url } => {
-                    if !rendered.is_empty() {
-                        rendered.push(' ');
-                    }
-                    rendered.push_str("[image:");
-                    rendered.push_str(&url);
-                    rendered.push(']');
-                }
-                MessageSegment::Attachment { name, .. } => {
-                    if !rendered.is_empty() {
-                        rendered.push(' ');
-                    }
-                    rendered.push_str("[attachment:");
-                    rendered.push_str(name.as_deref().unwrap_or("unnamed"));
-                    rendered.push(']');
-                }
-                MessageSegment::Unknown { kind, .. } => {
-                    if !rendered.is_empty() {
-                        rendered.push(' ');
-                    }
-                    rendered.push('[');
-                    rendered.push_str(&kind);
-                    rendered.push(']');
+        self.outgoing_tx.send(rendered).await?;
+        let message_id = self.next_message_id.fetch_add(1, Ordering::Relaxed);
+        Ok(OutboundReceipt::default().with_message_id(message_id.to_string()))
+    }
+
+    fn message_actions(&self) -> Option<&dyn MessageActions> {
+        Some(self)
+    }
+}
+
+#[async_trait]
+impl MessageActions for ConsoleSender {
+    async fn delete_message(&self, _channel: &ChannelRef, message_id: &str) -> Result<()> {
+        self.outgoing_tx
+            .send(format!("[deleted #{message_id}]"))
+            .await?;
+        Ok(())
+    }
+
+    async fn react(&self, _channel: &ChannelRef, message_id: &str, reaction: &str) -> Result<()> {
+        self.outgoing_tx
+            .send(format!("[reacted {reaction} to #{message_id}]"))
+            .await?;
+        Ok(())
+    }
+
+    async fn edit_message(
+        &self,
+        _channel: &ChannelRef,
+        message_id: &str,
+        segments: Vec<MessageSegment>,
+    ) -> Result<OutboundReceipt> {
+        let mut rendered = format!("[edited #{message_id}] ");
+        render_segments(&mut rendered, &segments);
+        self.outgoing_tx.send(rendered).await?;
+        Ok(OutboundReceipt::default().with_message_id(message_id))
+    }
+}
+
+fn render_segments(rendered: &mut String, segments: &[MessageSegment]) {
+    for segment in segments {
+        match segment {
+            MessageSegment::Text { text } => rendered.push_str(text),
+            MessageSegment::Mention { user_id } => {
+                separate(rendered);
+                rendered.push('@');
+                rendered.push_str(user_id);
+            }
+            MessageSegment::Image { url } => push_tag(rendered, "image", url),
+            MessageSegment::Attachment { name, .. } => {
+                push_tag(rendered, "attachment", name.as_deref().unwrap_or("unnamed"));
+            }
+            MessageSegment::Reply { message_id } => push_tag(rendered, "reply", message_id),
+            MessageSegment::Emoji { id } => push_tag(rendered, "emoji", id),
+            MessageSegment::Audio { url } => push_tag(rendered, "audio", url),
+            MessageSegment::Video { url } => push_tag(rendered, "video", url),
+            MessageSegment::Link { url, title, .. } => {
+                separate(rendered);
+                rendered.push_str("[link:");
+                rendered.push_str(title);
+                rendered.push_str(" <");
+                rendered.push_str(url);
+                rendered.push_str(">]");
+            }
+            MessageSegment::Card { format, .. } => push_tag(rendered, "card", format),
+            MessageSegment::Forward { id, nodes } if nodes.is_empty() => {
+                push_tag(rendered, "forward", id.as_deref().unwrap_or("empty"));
+            }
+            MessageSegment::Forward { nodes, .. } => {
+                separate(rendered);
+                rendered.push_str("[forward]");
+                for node in nodes {
+                    rendered.push_str("\n> ");
+                    rendered.push_str(&node.nickname);
+                    rendered.push_str(": ");
+                    render_segments(rendered, &node.content);
                 }
             }
+            MessageSegment::Unknown { kind, .. } => {
+                separate(rendered);
+                rendered.push('[');
+                rendered.push_str(kind);
+                rendered.push(']');
+            }
         }
+    }
+}
 
-        self.outgoing_tx.send(rendered).await?;
-        Ok(OutboundReceipt::default())
+fn separate(rendered: &mut String) {
+    if !rendered.is_empty() && !rendered.ends_with([' ', '\n']) {
+        rendered.push(' ');
+    }
+}
+
+fn push_tag(rendered: &mut String, tag: &str, value: &str) {
+    separate(rendered);
+    rendered.push('[');
+    rendered.push_str(tag);
+    rendered.push(':');
+    rendered.push_str(value);
+    rendered.push(']');
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::ConsoleSender;
+    use crate::core::{
+        model::{ChannelRef, MessageSegment, OutboundMessage},
+        plugin::OutboundSender,
+    };
+
+    #[tokio::test]
+    async fn console_sender_numbers_messages_and_echoes_actions() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let sender = ConsoleSender::new(tx);
+        let channel = ChannelRef::direct("console", "console-user");
+
+        let receipt = sender
+            .send(OutboundMessage::text(channel.clone(), "hello"))
+            .await
+            .unwrap();
+        assert_eq!(receipt.message_id.as_deref(), Some("1"));
+        assert_eq!(rx.recv().await.unwrap(), "hello");
+
+        let actions = sender.message_actions().unwrap();
+        actions.react(&channel, "1", "👍").await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "[reacted 👍 to #1]");
+        actions
+            .edit_message(&channel, "1", vec![MessageSegment::text("bye")])
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "[edited #1] bye");
+        actions.delete_message(&channel, "1").await.unwrap();
+        assert_eq!(rx.recv().await.unwrap(), "[deleted #1]");
     }
 }
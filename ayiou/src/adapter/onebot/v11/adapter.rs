@@ -3,6 +3,7 @@ use std::sync::{Arc, RwLock, atomic::AtomicU64};
 use async_trait::async_trait;
 use dashmap::DashMap;
 use log::{info, warn};
+use serde::Deserialize;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::{
@@ -24,7 +25,7 @@ use crate::{
 /// Adapter responsibilities:
 /// - convert raw packets to `OneBot` events
 /// - map `OneBot` responses by echo
-/// - build plugin context from message events
+/// - build plugin context from message, notice, request and meta events
 pub struct OneBotV11Adapter {
     driver: Box<dyn Driver<Inbound = String, Outbound = String>>,
 }
@@ -124,19 +125,30 @@ impl OneBotV11Protocol {
             return None;
         }
 
-        match serde_json::from_str::<OneBotEvent>(&raw) {
+        let value = match serde_json::from_str::<serde_json::Value>(&raw) {
+            Ok(value) => value,
+            Err(err) => {
+                warn!("Failed to parse: {err}, raw: {raw}");
+                return None;
+            }
+        };
+
+        match OneBotEvent::deserialize(&value) {
             Ok(event) => {
                 if let OneBotEvent::Message(msg_event) = &event {
                     OneBotV11Adapter::log_message(msg_event);
                 }
 
-                let event = Arc::new(event);
-                let raw_ctx = Ctx::new(
-                    event,
+                let is_message = matches!(event, OneBotEvent::Message(_));
+                let mut raw_ctx = Ctx::new(
+                    Arc::new(event),
                     outgoing_tx,
                     self.pending_api.clone(),
                     self.echo_seq.clone(),
-                )?;
+                );
+                if !is_message {
+                    raw_ctx = raw_ctx.with_raw_event(value);
+                }
                 Some(raw_ctx.into_context(Some(sender)))
             }
             Err(err) => {
@@ -189,8 +201,170 @@ impl Adapter for OneBotV11Adapter {
             capabilities: vec![
                 Capability::ProactiveSend,
                 Capability::RichSegments,
+                Capability::MessageDelete,
+                Capability::Reaction,
                 Capability::custom("onebot/v11"),
             ],
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use tokio::sync::mpsc;
+
+    use super::OneBotV11Protocol;
+    use crate::{
+        adapter::onebot::v11::{ctx::Ctx, sender::OneBotSender},
+        core::{
+            model::{
+                ChannelKind, ChannelRef, KernelEvent, KernelEventKind, PlatformEventKind,
+                PlatformId, UserRef,
+            },
+            plugin::{HandlerEventKind, OutboundSender},
+        },
+    };
+
+    fn handle(raw: &str) -> Option<crate::core::context::Context> {
+        let mut protocol = OneBotV11Protocol::new();
+        let (outgoing_tx, _outgoing_rx) = mpsc::channel(8);
+        let (sender, _rx) = OneBotSender::test_pair();
+        protocol.handle_packet(
+            raw.to_string(),
+            outgoing_tx,
+            Arc::new(sender) as Arc<dyn OutboundSender>,
+        )
+    }
+
+    #[test]
+    fn notice_packets_become_platform_event_contexts() {
+        let ctx = handle(
+            r#"{"time":1,"self_id":42,"post_type":"notice","notice_type":"group_increase",
+                "sub_type":"approve","group_id":100,"operator_id":7,"user_id":10001}"#,
+        )
+        .expect("notice should produce a context");
+
+        assert!(ctx.message().is_none());
+        assert_eq!(HandlerEventKind::of(ctx.event()), HandlerEventKind::Notice);
+        let event = ctx.platform_event().expect("platform event");
+        assert_eq!(event.kind, PlatformEventKind::Notice);
+        assert_eq!(event.name, "group_increase");
+        assert_eq!(event.sub_type.as_deref(), Some("approve"));
+        assert_eq!(ctx.user_id(), "10001");
+        assert_eq!(ctx.group_id().as_deref(), Some("100"));
+        assert_eq!(ctx.event().bot_id.as_str(), "42");
+        assert_eq!(event.data["operator_id"], 7);
+        assert_eq!(
+            ctx.channel().map(|channel| channel.kind()),
+            Some(ChannelKind::Group)
+        );
+        assert_eq!(
+            ctx.extension::<Ctx>()
+                .and_then(Ctx::notice)
+                .map(|notice| notice.notice_type()),
+            Some(Some("group_increase"))
+        );
+    }
+
+    #[test]
+    fn request_and_meta_packets_keep_their_kind() {
+        let request = handle(
+            r#"{"time":1,"self_id":42,"post_type":"request","request_type":"friend",
+                "user_id":10001,"comment":"hi","flag":"f1"}"#,
+        )
+        .expect("request should produce a context");
+        let event = request.platform_event().expect("platform event");
+        assert_eq!(event.kind, PlatformEventKind::Request);
+        assert_eq!(event.name, "friend");
+        assert_eq!(
+            request.channel().map(|channel| channel.kind()),
+            Some(ChannelKind::Direct)
+        );
+
+        let meta = handle(
+            r#"{"time":1,"self_id":42,"post_type":"meta_event","meta_event_type":"lifecycle",
+                "sub_type":"connect"}"#,
+        )
+        .expect("meta event should produce a context");
+        let event = meta.platform_event().expect("platform event");
+        assert_eq!(event.kind, PlatformEventKind::Meta);
+        assert_eq!(event.name, "lifecycle");
+        assert_eq!(event.sub_type.as_deref(), Some("connect"));
+        assert!(meta.user_id().is_empty());
+        assert_eq!(meta.kernel_event(), Some(&KernelEvent::BotConnected));
+    }
+
+    #[test]
+    fn native_notices_map_onto_kernel_events() {
+        let joined = handle(
+            r#"{"time":1,"self_id":42,"post_type":"notice","notice_type":"group_increase",
+                "sub_type":"invite","group_id":100,"operator_id":7,"user_id":10001}"#,
+        )
+        .unwrap();
+        let platform = PlatformId::new("onebot/v11");
+        assert_eq!(
+            joined.kernel_event(),
+            Some(&KernelEvent::MemberJoined {
+                channel: ChannelRef::group(platform.clone(), "100"),
+                user: UserRef::new(platform.clone(), "10001"),
+                operator: Some(UserRef::new(platform.clone(), "7")),
+            })
+        );
+
+        let left = handle(
+            r#"{"time":1,"self_id":42,"post_type":"notice","notice_type":"group_decrease",
+                "sub_type":"kick","group_id":100,"operator_id":7,"user_id":10001}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            left.kernel_event(),
+            Some(KernelEvent::MemberLeft { kicked: true, .. })
+        ));
+
+        let recalled = handle(
+            r#"{"time":1,"self_id":42,"post_type":"notice","notice_type":"friend_recall",
+                "user_id":10001,"message_id":55}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            recalled.kernel_event().map(KernelEvent::kind),
+            Some(KernelEventKind::MessageRecalled)
+        );
+        assert_eq!(
+            recalled.channel().map(ChannelRef::channel_id),
+            Some("10001")
+        );
+
+        let reaction = handle(
+            r#"{"time":1,"self_id":42,"post_type":"notice","notice_type":"group_msg_emoji_like",
+                "group_id":100,"user_id":10001,"message_id":55,
+                "likes":[{"emoji_id":"76","count":1}]}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            reaction.kernel_event(),
+            Some(&KernelEvent::ReactionAdded {
+                channel: ChannelRef::group(platform.clone(), "100"),
+                message_id: "55".to_string(),
+                user: UserRef::new(platform, "10001"),
+                reaction: "76".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn join_requests_keep_their_flag() {
+        let request = handle(
+            r#"{"time":1,"self_id":42,"post_type":"request","request_type":"group",
+                "sub_type":"add","group_id":100,"user_id":10001,"comment":"let me in","flag":"f2"}"#,
+        )
+        .unwrap();
+        let Some(KernelEvent::JoinRequest { flag, comment, .. }) = request.kernel_event() else {
+            panic!("expected a join request");
+        };
+        assert_eq!(flag, "f2");
+        assert_eq!(comment, "let me in");
+    }
+}
@@ -13,23 +13,26 @@ use tokio::sync::{mpsc, oneshot};
 
 use crate::adapter::onebot::v11::model::{
     ApiRequest, ApiResponse, GroupInfoData, GroupMemberInfoData, LoginInfoData, Message,
-    MessageEvent, MessageSegment, OneBotAction, OneBotEvent, SendMessageData, echo_key,
+    MessageEvent, MessageSegment, MetaEvent, NodeData, NoticeEvent, OneBotAction, OneBotEvent,
+    RequestEvent, SendMessageData, echo_key,
 };
 use crate::core::{
     context::Context,
     model::{
-        BotId, ChannelRef, EventEnvelope, MessageEvent as KernelMessageEvent,
-        MessageSegment as KernelMessageSegment, PlatformId, UserRef,
+        BotId, ChannelRef, EventEnvelope, ForwardNode, KernelEvent,
+        MessageEvent as KernelMessageEvent, MessageSegment as KernelMessageSegment, PlatformEvent,
+        PlatformEventKind, PlatformId, UserRef,
     },
     plugin::OutboundSender,
 };
 
 const API_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// Message context
+/// Event context
 #[derive(Clone)]
 pub struct Ctx {
     event: Arc<OneBotEvent>,
+    raw_event: Option<Arc<serde_json::Value>>,
     array_text: Option<String>,
     outgoing_tx: mpsc::Sender<String>,
     pending_api: Arc<DashMap<String, oneshot::Sender<ApiResponse>>>,
@@ -43,35 +46,43 @@ impl Ctx {
         outgoing_tx: mpsc::Sender<String>,
         pending_api: Arc<DashMap<String, oneshot::Sender<ApiResponse>>>,
         echo_seq: Arc<AtomicU64>,
-    ) -> Option<Self> {
-        let OneBotEvent::Message(msg_event) = event.as_ref() else {
-            return None;
-        };
-        let message = message_payload(msg_event);
-        let array_text = if let Message::Array(segments) = message {
-            let mut text = String::new();
-            for segment in segments {
-                if let MessageSegment::Text { text: value } = segment {
-                    text.push_str(value);
+    ) -> Self {
+        let array_text = match event.as_ref() {
+            OneBotEvent::Message(msg_event) => match message_payload(msg_event) {
+                Message::Array(segments) => {
+                    let mut text = String::new();
+                    for segment in segments {
+                        if let MessageSegment::Text { text: value } = segment {
+                            text.push_str(value);
+                        }
+                    }
+                    let trimmed = text.trim();
+                    Some(if trimmed.len() == text.len() {
+                        text
+                    } else {
+                        trimmed.to_string()
+                    })
                 }
-            }
-            let trimmed = text.trim();
-            Some(if trimmed.len() == text.len() {
-                text
-            } else {
-                trimmed.to_string()
-            })
-        } else {
-            None
+                Message::String(_) | Message::Segment(_) => None,
+            },
+            OneBotEvent::Notice(_) | OneBotEvent::Request(_) | OneBotEvent::Meta(_) => None,
         };
 
-        Some(Self {
+        Self {
             event,
+            raw_event: None,
             array_text,
             outgoing_tx,
             pending_api,
             echo_seq,
-        })
+        }
+    }
+
+    /// Attach the raw JSON payload so extension fields survive into the kernel event.
+    #[must_use]
+    pub fn with_raw_event(mut self, raw_event: serde_json::Value) -> Self {
+        self.raw_event = Some(Arc::new(raw_event));
+        self
     }
 
     /// Get raw `OneBot` event
@@ -81,104 +92,140 @@ impl Ctx {
         &self.event
     }
 
-    fn message_event(&self) -> &MessageEvent {
+    /// Raw JSON payload, when the adapter kept it
+    #[must_use]
+    pub fn raw_event(&self) -> Option<&serde_json::Value> {
+        self.raw_event.as_deref()
+    }
+
+    /// Message event, `None` for notice, request and meta events
+    #[must_use]
+    pub fn message_event(&self) -> Option<&MessageEvent> {
         match self.event.as_ref() {
-            OneBotEvent::Message(message) => message,
-            _ => unreachable!("OneBot Ctx only stores message events"),
+            OneBotEvent::Message(message) => Some(message.as_ref()),
+            OneBotEvent::Notice(_) | OneBotEvent::Request(_) | OneBotEvent::Meta(_) => None,
         }
     }
 
-    fn message(&self) -> &Message {
-        message_payload(self.message_event())
+    /// Notice event, if this context carries one
+    #[must_use]
+    pub fn notice(&self) -> Option<&NoticeEvent> {
+        match self.event.as_ref() {
+            OneBotEvent::Notice(notice) => Some(notice),
+            _ => None,
+        }
+    }
+
+    /// Request event, if this context carries one
+    #[must_use]
+    pub fn request(&self) -> Option<&RequestEvent> {
+        match self.event.as_ref() {
+            OneBotEvent::Request(request) => Some(request),
+            _ => None,
+        }
+    }
+
+    /// Meta event, if this context carries one
+    #[must_use]
+    pub fn meta(&self) -> Option<&MetaEvent> {
+        match self.event.as_ref() {
+            OneBotEvent::Meta(meta) => Some(meta),
+            _ => None,
+        }
+    }
+
+    fn message(&self) -> Option<&Message> {
+        self.message_event().map(message_payload)
     }
 
     /// Get plain text from message
     #[must_use]
     pub fn text(&self) -> Cow<'_, str> {
         match self.message() {
-            Message::String(s) => Cow::Borrowed(s.trim()),
-            Message::Segment(seg) => match seg {
-                MessageSegment::Text { text } => Cow::Borrowed(text.trim()),
-                _ => Cow::Borrowed(""),
-            },
-            Message::Array(_) => Cow::Borrowed(self.array_text.as_deref().unwrap_or("")),
+            Some(Message::String(s)) => Cow::Borrowed(s.trim()),
+            Some(Message::Segment(MessageSegment::Text { text })) => Cow::Borrowed(text.trim()),
+            Some(Message::Array(_)) => Cow::Borrowed(self.array_text.as_deref().unwrap_or("")),
+            Some(Message::Segment(_)) | None => Cow::Borrowed(""),
         }
     }
 
-    /// Raw message string
+    /// Raw message string, empty for non-message events
     #[inline]
     #[must_use]
     pub fn raw_message(&self) -> &str {
         match self.message_event() {
-            MessageEvent::Private(p) => &p.raw_message,
-            MessageEvent::Group(g) => &g.raw_message,
+            Some(MessageEvent::Private(p)) => &p.raw_message,
+            Some(MessageEvent::Group(g)) => &g.raw_message,
+            None => "",
         }
     }
 
-    /// Sender user ID
+    /// Sender or subject user ID, `0` for events without a user
     #[inline]
     #[must_use]
     pub fn user_id(&self) -> i64 {
-        match self.message_event() {
-            MessageEvent::Private(p) => p.user_id,
-            MessageEvent::Group(g) => g.user_id,
-        }
+        self.event.user_id().unwrap_or_default()
     }
 
-    /// Group ID (None for private messages)
+    /// Group ID (None for private messages and non-group events)
     #[inline]
     #[must_use]
     pub fn group_id(&self) -> Option<i64> {
-        match self.message_event() {
-            MessageEvent::Private(_) => None,
-            MessageEvent::Group(g) => Some(g.group_id),
-        }
+        self.event.group_id()
     }
 
     /// Check if private message
     #[inline]
     #[must_use]
     pub fn is_private(&self) -> bool {
-        matches!(self.message_event(), MessageEvent::Private(_))
+        matches!(self.message_event(), Some(MessageEvent::Private(_)))
     }
 
     /// Check if group message
     #[inline]
     #[must_use]
     pub fn is_group(&self) -> bool {
-        matches!(self.message_event(), MessageEvent::Group(_))
+        matches!(self.message_event(), Some(MessageEvent::Group(_)))
     }
 
-    /// Sender nickname
+    /// Sender nickname, empty for non-message events
     #[inline]
     #[must_use]
     pub fn nickname(&self) -> &str {
         match self.message_event() {
-            MessageEvent::Private(p) => &p.sender.nickname,
-            MessageEvent::Group(g) => &g.sender.nickname,
+            Some(MessageEvent::Private(p)) => &p.sender.nickname,
+            Some(MessageEvent::Group(g)) => &g.sender.nickname,
+            None => "",
         }
     }
 
     /// Reply with message
+    ///
+    /// Non-message events reply to their group, or privately to their user.
     pub async fn reply(&self, message: impl Into<Message>) -> Result<()> {
         let msg = message.into();
-        match self.message_event() {
-            MessageEvent::Private(p) => {
-                self.send_private_msg(p.user_id, &msg).await?;
-            }
-            MessageEvent::Group(g) => {
-                self.send_group_msg(g.group_id, &msg).await?;
-            }
+        if let Some(group_id) = self.group_id() {
+            self.send_group_msg(group_id, &msg).await
+        } else if let Some(user_id) = self.event.user_id() {
+            self.send_private_msg(user_id, &msg).await
+        } else {
+            Err(anyhow!(
+                "OneBot `{}` event has no reply target",
+                self.event.post_type()
+            ))
         }
-        Ok(())
     }
 
     #[must_use]
     pub fn self_id(&self) -> i64 {
-        match self.message_event() {
-            MessageEvent::Private(p) => p.self_id,
-            MessageEvent::Group(g) => g.self_id,
-        }
+        self.event
+            .self_id()
+            .or_else(|| {
+                self.raw_event()
+                    .and_then(|raw| raw.get("self_id"))
+                    .and_then(serde_json::Value::as_i64)
+            })
+            .unwrap_or_default()
     }
 
     #[must_use]
@@ -194,23 +241,164 @@ impl Ctx {
     #[must_use]
     pub fn to_event_envelope(&self) -> EventEnvelope {
         let platform = self.platform_id();
+        let envelope = EventEnvelope::new(BotId::new(self.self_id().to_string()), platform.clone());
+        let Some(message_event) = self.message_event() else {
+            let envelope = envelope.with_platform_event(self.to_platform_event(platform.clone()));
+            return match self.to_kernel_event(platform) {
+                Some(event) => envelope.with_kernel_event(event),
+                None => envelope,
+            };
+        };
+
         let sender = UserRef::new(platform.clone(), self.user_id().to_string())
             .with_display_name(self.nickname().to_string());
-        let channel = match self.message_event() {
-            MessageEvent::Private(p) => ChannelRef::direct(platform.clone(), p.user_id.to_string()),
-            MessageEvent::Group(g) => ChannelRef::group(platform.clone(), g.group_id.to_string()),
+        let (channel, message_id, message) = match message_event {
+            MessageEvent::Private(p) => (
+                ChannelRef::direct(platform, p.user_id.to_string()),
+                p.message_id,
+                &p.message,
+            ),
+            MessageEvent::Group(g) => (
+                ChannelRef::group(platform, g.group_id.to_string()),
+                g.message_id,
+                &g.message,
+            ),
         };
-        let message_id = match self.message_event() {
-            MessageEvent::Private(p) => p.message_id.to_string(),
-            MessageEvent::Group(g) => g.message_id.to_string(),
-        };
-        let segments = to_kernel_segments(self.message());
+        let segments = to_kernel_segments(message);
 
         let message = KernelMessageEvent::new(sender, channel, self.text())
-            .with_message_id(message_id)
+            .with_message_id(message_id.to_string())
             .with_segments(segments);
 
-        EventEnvelope::new(BotId::new(self.self_id().to_string()), platform).with_message(message)
+        envelope.with_message(message)
+    }
+
+    fn to_platform_event(&self, platform: PlatformId) -> PlatformEvent {
+        let raw = self.raw_event();
+        let raw_str = |key: &str| {
+            raw.and_then(|raw| raw.get(key))
+                .and_then(serde_json::Value::as_str)
+        };
+        let (kind, name, sub_type) = match self.event.as_ref() {
+            OneBotEvent::Notice(notice) => (
+                PlatformEventKind::Notice,
+                notice.notice_type().or_else(|| raw_str("notice_type")),
+                notice.sub_type().or_else(|| raw_str("sub_type")),
+            ),
+            OneBotEvent::Request(request) => (
+                PlatformEventKind::Request,
+                Some(request.request_type()),
+                request.sub_type(),
+            ),
+            OneBotEvent::Meta(meta) => (
+                PlatformEventKind::Meta,
+                Some(meta.meta_event_type()),
+                meta.sub_type(),
+            ),
+            OneBotEvent::Message(_) => unreachable!("message events build a message envelope"),
+        };
+
+        let mut event = PlatformEvent::new(kind, name.unwrap_or("unknown"));
+        if let Some(sub_type) = sub_type {
+            event = event.with_sub_type(sub_type);
+        }
+        let user_id = self.event.user_id();
+        if let Some(user_id) = user_id {
+            event = event.with_user(UserRef::new(platform.clone(), user_id.to_string()));
+        }
+        if let Some(group_id) = self.group_id() {
+            event = event.with_channel(ChannelRef::group(platform, group_id.to_string()));
+        } else if let Some(user_id) = user_id {
+            event = event.with_channel(ChannelRef::direct(platform, user_id.to_string()));
+        }
+        if let Some(raw) = raw {
+            event = event.with_data(raw.clone());
+        }
+        event
+    }
+
+    fn to_kernel_event(&self, platform: PlatformId) -> Option<KernelEvent> {
+        let user = |user_id: i64| UserRef::new(platform.clone(), user_id.to_string());
+        let group = |group_id: i64| ChannelRef::group(platform.clone(), group_id.to_string());
+        let operator = |operator_id: i64, user_id: i64| {
+            (operator_id != 0 && operator_id != user_id).then(|| user(operator_id))
+        };
+
+        match self.event.as_ref() {
+            OneBotEvent::Notice(NoticeEvent::GroupIncrease(e)) => Some(KernelEvent::MemberJoined {
+                channel: group(e.group_id),
+                user: user(e.user_id),
+                operator: operator(e.operator_id, e.user_id),
+            }),
+            OneBotEvent::Notice(NoticeEvent::GroupDecrease(e)) => Some(KernelEvent::MemberLeft {
+                channel: group(e.group_id),
+                user: user(e.user_id),
+                operator: operator(e.operator_id, e.user_id),
+                kicked: e.sub_type.starts_with("kick"),
+            }),
+            OneBotEvent::Notice(NoticeEvent::GroupRecall(e)) => {
+                Some(KernelEvent::MessageRecalled {
+                    channel: group(e.group_id),
+                    message_id: e.message_id.to_string(),
+                    author: Some(user(e.user_id)),
+                    operator: Some(user(e.operator_id)),
+                })
+            }
+            OneBotEvent::Notice(NoticeEvent::FriendRecall(e)) => {
+                Some(KernelEvent::MessageRecalled {
+                    channel: ChannelRef::direct(platform.clone(), e.user_id.to_string()),
+                    message_id: e.message_id.to_string(),
+                    author: Some(user(e.user_id)),
+                    operator: Some(user(e.user_id)),
+                })
+            }
+            OneBotEvent::Notice(NoticeEvent::Unknown) => self.raw_reaction(platform),
+            OneBotEvent::Request(RequestEvent::Friend(e)) => Some(KernelEvent::FriendRequest {
+                user: user(e.user_id),
+                comment: e.comment.clone(),
+                flag: e.flag.clone(),
+            }),
+            OneBotEvent::Request(RequestEvent::Group(e)) if e.sub_type == "add" => {
+                Some(KernelEvent::JoinRequest {
+                    channel: group(e.group_id),
+                    user: user(e.user_id),
+                    comment: e.comment.clone(),
+                    flag: e.flag.clone(),
+                })
+            }
+            OneBotEvent::Meta(MetaEvent::Lifecycle(e)) => match e.sub_type.as_str() {
+                "connect" | "enable" => Some(KernelEvent::BotConnected),
+                "disable" => Some(KernelEvent::BotDisconnected),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Reaction notices are implementation extensions, so they are read from the raw payload:
+    /// `group_msg_emoji_like` (NapCat, LLOneBot) and `reaction` (Lagrange).
+    fn raw_reaction(&self, platform: PlatformId) -> Option<KernelEvent> {
+        let raw = self.raw_event()?;
+        let int = |key: &str| raw.get(key).and_then(serde_json::Value::as_i64);
+        let (user_id, reaction) = match raw.get("notice_type")?.as_str()? {
+            "group_msg_emoji_like" => {
+                let emoji = raw.get("likes")?.as_array()?.first()?.get("emoji_id")?;
+                (int("user_id")?, json_scalar(emoji)?)
+            }
+            "reaction"
+                if raw.get("sub_type").and_then(serde_json::Value::as_str) == Some("add") =>
+            {
+                (int("operator_id")?, json_scalar(raw.get("code")?)?)
+            }
+            _ => return None,
+        };
+
+        Some(KernelEvent::ReactionAdded {
+            channel: ChannelRef::group(platform.clone(), int("group_id")?.to_string()),
+            message_id: json_scalar(raw.get("message_id")?)?,
+            user: UserRef::new(platform, user_id.to_string()),
+            reaction,
+        })
     }
 
     #[must_use]
@@ -220,6 +408,14 @@ impl Ctx {
     }
 }
 
+fn json_scalar(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(value) => Some(value.clone()),
+        serde_json::Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
 fn message_payload(event: &MessageEvent) -> &Message {
     match event {
         MessageEvent::Private(private) => &private.message,
@@ -227,11 +423,30 @@ fn message_payload(event: &MessageEvent) -> &Message {
     }
 }
 
-fn to_kernel_segments(message: &Message) -> Vec<KernelMessageSegment> {
+pub(crate) fn to_kernel_segments(message: &Message) -> Vec<KernelMessageSegment> {
     match message {
         Message::String(text) => vec![KernelMessageSegment::text(text.clone())],
         Message::Segment(segment) => vec![to_kernel_segment(segment)],
-        Message::Array(segments) => segments.iter().map(to_kernel_segment).collect(),
+        Message::Array(segments) => {
+            let mut out: Vec<KernelMessageSegment> = Vec::with_capacity(segments.len());
+            for segment in segments {
+                let segment = to_kernel_segment(segment);
+                // Consecutive custom nodes are one bundle, as they were sent.
+                if let (
+                    Some(KernelMessageSegment::Forward { id: None, nodes }),
+                    KernelMessageSegment::Forward {
+                        id: None,
+                        nodes: next,
+                    },
+                ) = (out.last_mut(), &segment)
+                {
+                    nodes.extend(next.iter().cloned());
+                    continue;
+                }
+                out.push(segment);
+            }
+            out
+        }
     }
 }
 
@@ -244,17 +459,69 @@ fn to_kernel_segment(segment: &MessageSegment) -> KernelMessageSegment {
         MessageSegment::Image { file, url, .. } => KernelMessageSegment::Image {
             url: url.clone().unwrap_or_else(|| file.clone()),
         },
-        MessageSegment::Record { file, url, .. } | MessageSegment::Video { file, url } => {
-            KernelMessageSegment::Attachment {
-                name: None,
-                url: url.clone().or_else(|| Some(file.clone())),
-                mime: None,
+        MessageSegment::Record { file, url, .. } => KernelMessageSegment::Audio {
+            url: url.clone().unwrap_or_else(|| file.clone()),
+        },
+        MessageSegment::Video { file, url } => KernelMessageSegment::Video {
+            url: url.clone().unwrap_or_else(|| file.clone()),
+        },
+        MessageSegment::Reply { id } => KernelMessageSegment::reply(id.clone()),
+        MessageSegment::Face { id } => KernelMessageSegment::Emoji { id: id.clone() },
+        MessageSegment::Forward { id } => KernelMessageSegment::Forward {
+            id: Some(id.clone()),
+            nodes: Vec::new(),
+        },
+        MessageSegment::Node {
+            data: NodeData::Id { id },
+        } => KernelMessageSegment::Forward {
+            id: Some(id.clone()),
+            nodes: Vec::new(),
+        },
+        MessageSegment::Node {
+            data:
+                NodeData::Custom {
+                    user_id,
+                    nickname,
+                    content,
+                },
+        } => KernelMessageSegment::forward(vec![ForwardNode::new(
+            user_id.to_string(),
+            nickname.clone(),
+            to_kernel_segments(content),
+        )]),
+        MessageSegment::Share {
+            url,
+            title,
+            content,
+            image,
+        } => KernelMessageSegment::Link {
+            url: url.clone(),
+            title: title.clone(),
+            content: content.clone(),
+            image: image.clone(),
+        },
+        MessageSegment::Json { data } => KernelMessageSegment::Card {
+            format: "json".to_string(),
+            data: data.clone(),
+        },
+        MessageSegment::Xml { data } => KernelMessageSegment::Card {
+            format: "xml".to_string(),
+            data: data.clone(),
+        },
+        other => {
+            let mut value = serde_json::to_value(other).unwrap_or_default();
+            KernelMessageSegment::Unknown {
+                kind: value
+                    .get("type")
+                    .and_then(serde_json::Value::as_str)
+                    .unwrap_or("unknown")
+                    .to_string(),
+                data: value
+                    .get_mut("data")
+                    .map(serde_json::Value::take)
+                    .unwrap_or_default(),
             }
         }
-        other => KernelMessageSegment::Unknown {
-            kind: serde_json::to_string(other).unwrap_or_else(|_| "unknown".to_string()),
-            data: serde_json::Value::Null,
-        },
     }
 }
 
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use anyhow::anyhow;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
@@ -175,6 +177,58 @@ pub enum OneBotEvent {
     Meta(MetaEvent),
 }
 
+impl OneBotEvent {
+    /// `post_type` of the event.
+    #[must_use]
+    pub const fn post_type(&self) -> &'static str {
+        match self {
+            Self::Message(_) => "message",
+            Self::Notice(_) => "notice",
+            Self::Request(_) => "request",
+            Self::Meta(_) => "meta_event",
+        }
+    }
+
+    #[must_use]
+    pub fn self_id(&self) -> Option<i64> {
+        match self {
+            Self::Message(message) => Some(match message.as_ref() {
+                MessageEvent::Private(p) => p.self_id,
+                MessageEvent::Group(g) => g.self_id,
+            }),
+            Self::Notice(notice) => notice.self_id(),
+            Self::Request(request) => Some(request.self_id()),
+            Self::Meta(meta) => Some(meta.self_id()),
+        }
+    }
+
+    #[must_use]
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            Self::Message(message) => Some(match message.as_ref() {
+                MessageEvent::Private(p) => p.user_id,
+                MessageEvent::Group(g) => g.user_id,
+            }),
+            Self::Notice(notice) => notice.user_id(),
+            Self::Request(request) => Some(request.user_id()),
+            Self::Meta(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub fn group_id(&self) -> Option<i64> {
+        match self {
+            Self::Message(message) => match message.as_ref() {
+                MessageEvent::Private(_) => None,
+                MessageEvent::Group(g) => Some(g.group_id),
+            },
+            Self::Notice(notice) => notice.group_id(),
+            Self::Request(request) => request.group_id(),
+            Self::Meta(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(tag = "message_type")]
 pub enum MessageEvent {
@@ -247,6 +301,124 @@ pub enum NoticeEvent {
     Unknown,
 }
 
+impl NoticeEvent {
+    /// `notice_type` of the event, `None` for unknown extension notices.
+    #[must_use]
+    pub const fn notice_type(&self) -> Option<&'static str> {
+        Some(match self {
+            Self::GroupUpload(_) => "group_upload",
+            Self::GroupAdmin(_) => "group_admin",
+            Self::GroupDecrease(_) => "group_decrease",
+            Self::GroupIncrease(_) => "group_increase",
+            Self::GroupBan(_) => "group_ban",
+            Self::FriendAdd(_) => "friend_add",
+            Self::GroupRecall(_) => "group_recall",
+            Self::FriendRecall(_) => "friend_recall",
+            Self::GroupCard(_) => "group_card",
+            Self::OfflineFile(_) => "offline_file",
+            Self::ClientStatus(_) => "client_status",
+            Self::Essence(_) => "essence",
+            Self::Notify(_) => "notify",
+            Self::Unknown => return None,
+        })
+    }
+
+    #[must_use]
+    pub fn sub_type(&self) -> Option<&str> {
+        match self {
+            Self::GroupAdmin(e) => Some(&e.sub_type),
+            Self::GroupDecrease(e) => Some(&e.sub_type),
+            Self::GroupIncrease(e) => Some(&e.sub_type),
+            Self::GroupBan(e) => Some(&e.sub_type),
+            Self::Essence(e) => Some(&e.sub_type),
+            Self::Notify(NotifyEvent::Poke(_)) => Some("poke"),
+            Self::Notify(NotifyEvent::LuckyKing(_)) => Some("lucky_king"),
+            Self::Notify(NotifyEvent::Honor(_)) => Some("honor"),
+            Self::Notify(NotifyEvent::Title(_)) => Some("title"),
+            Self::GroupUpload(_)
+            | Self::FriendAdd(_)
+            | Self::GroupRecall(_)
+            | Self::FriendRecall(_)
+            | Self::GroupCard(_)
+            | Self::OfflineFile(_)
+            | Self::ClientStatus(_)
+            | Self::Notify(NotifyEvent::Unknown)
+            | Self::Unknown => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn self_id(&self) -> Option<i64> {
+        Some(match self {
+            Self::GroupUpload(e) => e.self_id,
+            Self::GroupAdmin(e) => e.self_id,
+            Self::GroupDecrease(e) => e.self_id,
+            Self::GroupIncrease(e) => e.self_id,
+            Self::GroupBan(e) => e.self_id,
+            Self::FriendAdd(e) => e.self_id,
+            Self::GroupRecall(e) => e.self_id,
+            Self::FriendRecall(e) => e.self_id,
+            Self::GroupCard(e) => e.self_id,
+            Self::OfflineFile(e) => e.self_id,
+            Self::ClientStatus(e) => e.self_id,
+            Self::Essence(e) => e.self_id,
+            Self::Notify(NotifyEvent::Poke(e)) => e.self_id,
+            Self::Notify(NotifyEvent::LuckyKing(e)) => e.self_id,
+            Self::Notify(NotifyEvent::Honor(e)) => e.self_id,
+            Self::Notify(NotifyEvent::Title(e)) => e.self_id,
+            Self::Notify(NotifyEvent::Unknown) | Self::Unknown => return None,
+        })
+    }
+
+    #[must_use]
+    pub const fn user_id(&self) -> Option<i64> {
+        Some(match self {
+            Self::GroupUpload(e) => e.user_id,
+            Self::GroupAdmin(e) => e.user_id,
+            Self::GroupDecrease(e) => e.user_id,
+            Self::GroupIncrease(e) => e.user_id,
+            Self::GroupBan(e) => e.user_id,
+            Self::FriendAdd(e) => e.user_id,
+            Self::GroupRecall(e) => e.user_id,
+            Self::FriendRecall(e) => e.user_id,
+            Self::GroupCard(e) => e.user_id,
+            Self::OfflineFile(e) => e.user_id,
+            Self::Essence(e) => e.sender_id,
+            Self::Notify(NotifyEvent::Poke(e)) => e.user_id,
+            Self::Notify(NotifyEvent::LuckyKing(e)) => e.user_id,
+            Self::Notify(NotifyEvent::Honor(e)) => e.user_id,
+            Self::Notify(NotifyEvent::Title(e)) => e.user_id,
+            Self::ClientStatus(_) | Self::Notify(NotifyEvent::Unknown) | Self::Unknown => {
+                return None;
+            }
+        })
+    }
+
+    #[must_use]
+    pub const fn group_id(&self) -> Option<i64> {
+        match self {
+            Self::GroupUpload(e) => Some(e.group_id),
+            Self::GroupAdmin(e) => Some(e.group_id),
+            Self::GroupDecrease(e) => Some(e.group_id),
+            Self::GroupIncrease(e) => Some(e.group_id),
+            Self::GroupBan(e) => Some(e.group_id),
+            Self::GroupRecall(e) => Some(e.group_id),
+            Self::GroupCard(e) => Some(e.group_id),
+            Self::Essence(e) => Some(e.group_id),
+            Self::Notify(NotifyEvent::Poke(e)) => e.group_id,
+            Self::Notify(NotifyEvent::LuckyKing(e)) => Some(e.group_id),
+            Self::Notify(NotifyEvent::Honor(e)) => Some(e.group_id),
+            Self::Notify(NotifyEvent::Title(e)) => Some(e.group_id),
+            Self::FriendAdd(_)
+            | Self::FriendRecall(_)
+            | Self::OfflineFile(_)
+            | Self::ClientStatus(_)
+            | Self::Notify(NotifyEvent::Unknown)
+            | Self::Unknown => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct GroupUploadNoticeEvent {
     pub time: i64,
@@ -438,6 +610,57 @@ pub enum RequestEvent {
     Group(GroupRequestEvent),
 }
 
+impl RequestEvent {
+    #[must_use]
+    pub const fn request_type(&self) -> &'static str {
+        match self {
+            Self::Friend(_) => "friend",
+            Self::Group(_) => "group",
+        }
+    }
+
+    #[must_use]
+    pub fn sub_type(&self) -> Option<&str> {
+        match self {
+            Self::Friend(_) => None,
+            Self::Group(e) => Some(&e.sub_type),
+        }
+    }
+
+    #[must_use]
+    pub const fn self_id(&self) -> i64 {
+        match self {
+            Self::Friend(e) => e.self_id,
+            Self::Group(e) => e.self_id,
+        }
+    }
+
+    #[must_use]
+    pub const fn user_id(&self) -> i64 {
+        match self {
+            Self::Friend(e) => e.user_id,
+            Self::Group(e) => e.user_id,
+        }
+    }
+
+    #[must_use]
+    pub const fn group_id(&self) -> Option<i64> {
+        match self {
+            Self::Friend(_) => None,
+            Self::Group(e) => Some(e.group_id),
+        }
+    }
+
+    /// Opaque `flag` used to approve or reject the request.
+    #[must_use]
+    pub fn flag(&self) -> &str {
+        match self {
+            Self::Friend(e) => &e.flag,
+            Self::Group(e) => &e.flag,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct FriendRequestEvent {
     pub time: i64,
@@ -467,6 +690,32 @@ pub enum MetaEvent {
     Heartbeat(HeartbeatMetaEvent),
 }
 
+impl MetaEvent {
+    #[must_use]
+    pub const fn meta_event_type(&self) -> &'static str {
+        match self {
+            Self::Lifecycle(_) => "lifecycle",
+            Self::Heartbeat(_) => "heartbeat",
+        }
+    }
+
+    #[must_use]
+    pub fn sub_type(&self) -> Option<&str> {
+        match self {
+            Self::Lifecycle(e) => Some(&e.sub_type),
+            Self::Heartbeat(_) => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn self_id(&self) -> i64 {
+        match self {
+            Self::Lifecycle(e) => e.self_id,
+            Self::Heartbeat(e) => e.self_id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct LifecycleMetaEvent {
     pub time: i64,
@@ -658,6 +907,22 @@ pub enum OneBotAction {
 }
 
 impl OneBotAction {
+    /// `OneBot` action name, as sent in the request's `action` field.
+    #[must_use]
+    pub fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(match self {
+            Self::SendPrivateMsg { .. } => "send_private_msg",
+            Self::SendGroupMsg { .. } => "send_group_msg",
+            Self::SetGroupKick { .. } => "set_group_kick",
+            Self::DeleteMsg { .. } => "delete_msg",
+            Self::GetLoginInfo => "get_login_info",
+            Self::GetGroupInfo { .. } => "get_group_info",
+            Self::GetGroupMemberInfo { .. } => "get_group_member_info",
+            Self::SetGroupBan { .. } => "set_group_ban",
+            Self::Custom { action, .. } => return Cow::Owned(action.clone()),
+        })
+    }
+
     #[must_use]
     pub fn into_request(self) -> ApiRequest {
         use serde_json::json;
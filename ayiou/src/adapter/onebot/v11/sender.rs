@@ -9,34 +9,22 @@ use std::{
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use dashmap::DashMap;
-use serde::Deserialize;
 use tokio::sync::{mpsc, oneshot};
 
 use crate::adapter::onebot::v11::model::{
-    ApiResponse, LoginInfoData, Message, OneBotAction, echo_key,
+    ApiResponse, LoginInfoData, Message, MessageSegment as OneBotSegment, NodeData, OneBotAction,
+    SendMessageData, echo_key,
 };
 use crate::core::{
     model::{
-        ChannelKind, MessageSegment as KernelMessageSegment, OutboundMessage, OutboundReceipt,
+        ChannelKind, ChannelRef, ForwardNode, MessageSegment as KernelMessageSegment,
+        OutboundMessage, OutboundReceipt,
     },
-    plugin::OutboundSender,
+    plugin::{MessageActions, OutboundSender},
 };
 
-const ONEBOT_FORWARD_KIND: &str = "onebot_v11_forward";
 const API_TIMEOUT: Duration = Duration::from_secs(10);
 
-#[derive(Debug, Clone, Deserialize)]
-struct ForwardBundle {
-    nodes: Vec<ForwardNode>,
-}
-
-#[derive(Debug, Clone, Deserialize)]
-struct ForwardNode {
-    user_id: i64,
-    nickname: String,
-    content: Vec<KernelMessageSegment>,
-}
-
 #[derive(Clone)]
 pub struct OneBotSender {
     outgoing_tx: mpsc::Sender<String>,
@@ -128,6 +116,34 @@ impl OneBotSender {
         }
     }
 
+    /// Send a message action and read the message id from its echo-matched response.
+    ///
+    /// Senders built without a runtime cannot map responses, so they send
+    /// fire-and-forget and return an empty receipt.
+    async fn send_message_action(&self, action: OneBotAction) -> Result<OutboundReceipt> {
+        if self.pending_api.is_none() {
+            self.send_action(action).await?;
+            return Ok(OutboundReceipt::default());
+        }
+
+        let action_name = action.name();
+        let response = self.call_action_with_response(action).await?;
+        let data = response.data_as_checked::<SendMessageData>(&action_name)?;
+        Ok(OutboundReceipt::default().with_message_id(data.message_id.to_string()))
+    }
+
+    /// Run a non-message action, surfacing failed responses when they can be observed.
+    async fn perform_action(&self, action: OneBotAction) -> Result<()> {
+        if self.pending_api.is_none() {
+            return self.send_action(action).await;
+        }
+
+        let action_name = action.name();
+        self.call_action_with_response(action)
+            .await?
+            .ensure_ok(&action_name)
+    }
+
     async fn resolve_profile(&self) -> Option<LoginInfoData> {
         let cached_profile = self.profile.read().expect("profile lock").clone();
         if let Some(profile) = cached_profile {
@@ -149,124 +165,174 @@ impl OneBotSender {
 #[async_trait]
 impl OutboundSender for OneBotSender {
     async fn send(&self, message: OutboundMessage) -> Result<OutboundReceipt> {
-        if let Some(bundle) = extract_forward_bundle(&message.segments) {
+        if let Some(nodes) = extract_forward_nodes(&message.segments) {
             let profile = self.resolve_profile().await;
-            match message.target.kind() {
-                ChannelKind::Direct => {
-                    let user_id = message.target.channel_id().parse::<i64>()?;
-                    self.send_action(OneBotAction::Custom {
-                        action: "send_private_forward_msg".to_string(),
-                        params: serde_json::json!({
-                            "user_id": user_id,
-                            "messages": to_onebot_forward_nodes(bundle.nodes, profile.as_ref()),
-                        }),
-                    })
-                    .await?;
-                }
-                ChannelKind::Group | ChannelKind::Channel => {
-                    let group_id = message.target.channel_id().parse::<i64>()?;
-                    self.send_action(OneBotAction::Custom {
-                        action: "send_group_forward_msg".to_string(),
-                        params: serde_json::json!({
-                            "group_id": group_id,
-                            "messages": to_onebot_forward_nodes(bundle.nodes, profile.as_ref()),
-                        }),
-                    })
-                    .await?;
-                }
-            }
+            let messages = to_onebot_forward_nodes(nodes, profile.as_ref())?;
+            let action = match message.target.kind() {
+                ChannelKind::Direct => OneBotAction::Custom {
+                    action: "send_private_forward_msg".to_string(),
+                    params: serde_json::json!({
+                        "user_id": message.target.channel_id().parse::<i64>()?,
+                        "messages": messages,
+                    }),
+                },
+                ChannelKind::Group | ChannelKind::Channel => OneBotAction::Custom {
+                    action: "send_group_forward_msg".to_string(),
+                    params: serde_json::json!({
+                        "group_id": message.target.channel_id().parse::<i64>()?,
+                        "messages": messages,
+                    }),
+                },
+            };
 
-            return Ok(OutboundReceipt::default());
+            return self.send_message_action(action).await;
         }
 
-        let message_body = to_onebot_message(message.segments);
+        let message_body = to_onebot_message(message.segments)?;
+        let action = match message.target.kind() {
+            ChannelKind::Direct => OneBotAction::SendPrivateMsg {
+                user_id: message.target.channel_id().parse::<i64>()?,
+                message: message_body,
+            },
+            ChannelKind::Group | ChannelKind::Channel => OneBotAction::SendGroupMsg {
+                group_id: message.target.channel_id().parse::<i64>()?,
+                message: message_body,
+            },
+        };
 
-        match message.target.kind() {
-            ChannelKind::Direct => {
-                let user_id = message.target.channel_id().parse::<i64>()?;
-                self.send_action(OneBotAction::SendPrivateMsg {
-                    user_id,
-                    message: message_body,
-                })
-                .await?;
-            }
-            ChannelKind::Group | ChannelKind::Channel => {
-                let group_id = message.target.channel_id().parse::<i64>()?;
-                self.send_action(OneBotAction::SendGroupMsg {
-                    group_id,
-                    message: message_body,
-                })
-                .await?;
-            }
-        }
+        self.send_message_action(action).await
+    }
+
+    fn message_actions(&self) -> Option<&dyn MessageActions> {
+        Some(self)
+    }
+}
 
-        Ok(OutboundReceipt::default())
+#[async_trait]
+impl MessageActions for OneBotSender {
+    async fn delete_message(&self, _channel: &ChannelRef, message_id: &str) -> Result<()> {
+        self.perform_action(OneBotAction::DeleteMsg {
+            message_id: message_id.parse()?,
+        })
+        .await
+    }
+
+    /// Reacts through the `set_msg_emoji_like` extension (NapCat, LLOneBot);
+    /// `reaction` is the QQ emoji id.
+    async fn react(&self, _channel: &ChannelRef, message_id: &str, reaction: &str) -> Result<()> {
+        self.perform_action(OneBotAction::Custom {
+            action: "set_msg_emoji_like".to_string(),
+            params: serde_json::json!({
+                "message_id": message_id.parse::<i64>()?,
+                "emoji_id": reaction,
+            }),
+        })
+        .await
     }
 }
 
-fn extract_forward_bundle(segments: &[KernelMessageSegment]) -> Option<ForwardBundle> {
+fn extract_forward_nodes(segments: &[KernelMessageSegment]) -> Option<&[ForwardNode]> {
     match segments {
-        [KernelMessageSegment::Unknown { kind, data }] if kind == ONEBOT_FORWARD_KIND => {
-            serde_json::from_value(data.clone()).ok()
-        }
+        [KernelMessageSegment::Forward { nodes, .. }] if !nodes.is_empty() => Some(nodes),
         _ => None,
     }
 }
 
 fn to_onebot_forward_nodes(
-    nodes: Vec<ForwardNode>,
+    nodes: &[ForwardNode],
     profile: Option<&LoginInfoData>,
-) -> Vec<crate::adapter::onebot::v11::model::MessageSegment> {
+) -> Result<Vec<OneBotSegment>> {
     nodes
-        .into_iter()
-        .map(
-            |node| crate::adapter::onebot::v11::model::MessageSegment::Node {
-                data: crate::adapter::onebot::v11::model::NodeData::Custom {
-                    user_id: profile.map_or(node.user_id, |item| item.user_id),
+        .iter()
+        .map(|node| {
+            Ok(OneBotSegment::Node {
+                data: NodeData::Custom {
+                    user_id: profile.map_or_else(
+                        || node.user_id.parse().unwrap_or_default(),
+                        |item| item.user_id,
+                    ),
                     nickname: profile
-                        .map(|item| item.nickname.clone())
-                        .unwrap_or(node.nickname),
-                    content: Box::new(to_onebot_message(node.content)),
+                        .map_or_else(|| node.nickname.clone(), |item| item.nickname.clone()),
+                    content: Box::new(to_onebot_message(node.content.clone())?),
                 },
-            },
-        )
+            })
+        })
         .collect()
 }
 
-fn to_onebot_message(segments: Vec<KernelMessageSegment>) -> Message {
+/// Fails on segments OneBot v11 cannot send rather than dropping them. A lone
+/// node bundle becomes its `node` segments, as nested in another node.
+fn to_onebot_message(segments: Vec<KernelMessageSegment>) -> Result<Message> {
+    if let Some(nodes) = extract_forward_nodes(&segments) {
+        return Ok(Message::Array(to_onebot_forward_nodes(nodes, None)?));
+    }
     let mut out = Vec::with_capacity(segments.len());
 
     for segment in segments {
-        match segment {
-            KernelMessageSegment::Text { text } => {
-                out.push(crate::adapter::onebot::v11::model::MessageSegment::Text { text });
-            }
-            KernelMessageSegment::Mention { user_id } => {
-                out.push(crate::adapter::onebot::v11::model::MessageSegment::At { qq: user_id });
-            }
-            KernelMessageSegment::Image { url } => {
-                out.push(crate::adapter::onebot::v11::model::MessageSegment::Image {
-                    file: url,
-                    image_type: None,
-                    url: None,
-                });
+        let segment = match segment {
+            KernelMessageSegment::Text { text } => OneBotSegment::Text { text },
+            KernelMessageSegment::Mention { user_id } => OneBotSegment::At { qq: user_id },
+            KernelMessageSegment::Image { url } => OneBotSegment::Image {
+                file: url,
+                image_type: None,
+                url: None,
+            },
+            KernelMessageSegment::Attachment { url, .. } => OneBotSegment::Record {
+                file: url.unwrap_or_default(),
+                magic: None,
+                url: None,
+            },
+            KernelMessageSegment::Reply { message_id } => OneBotSegment::Reply { id: message_id },
+            KernelMessageSegment::Emoji { id } => OneBotSegment::Face { id },
+            KernelMessageSegment::Audio { url } => OneBotSegment::Record {
+                file: url,
+                magic: None,
+                url: None,
+            },
+            KernelMessageSegment::Video { url } => OneBotSegment::Video {
+                file: url,
+                url: None,
+            },
+            KernelMessageSegment::Forward { id: Some(id), .. } => OneBotSegment::Forward { id },
+            KernelMessageSegment::Link {
+                url,
+                title,
+                content,
+                image,
+            } => OneBotSegment::Share {
+                url,
+                title,
+                content,
+                image,
+            },
+            KernelMessageSegment::Card { format, data } => match format.as_str() {
+                "json" => OneBotSegment::Json { data },
+                "xml" => OneBotSegment::Xml { data },
+                _ => return Err(anyhow!("OneBot v11 cannot send `{format}` cards")),
+            },
+            KernelMessageSegment::Unknown { kind, data } => {
+                match serde_json::from_value(serde_json::json!({ "type": kind, "data": data })) {
+                    Ok(OneBotSegment::Unknown) | Err(_) => {
+                        return Err(anyhow!("OneBot v11 cannot send `{kind}` segments"));
+                    }
+                    Ok(segment) => segment,
+                }
             }
-            KernelMessageSegment::Attachment { url, .. } => {
-                out.push(crate::adapter::onebot::v11::model::MessageSegment::Record {
-                    file: url.unwrap_or_default(),
-                    magic: None,
-                    url: None,
-                });
+            // Node bundles are only sendable on their own, through the forward actions.
+            KernelMessageSegment::Forward { id: None, .. } => {
+                return Err(anyhow!(
+                    "OneBot v11 can only send a forward node bundle as the whole message"
+                ));
             }
-            KernelMessageSegment::Unknown { .. } => {}
-        }
+        };
+        out.push(segment);
     }
 
-    match out.len() {
+    Ok(match out.len() {
         0 => Message::String(String::new()),
         1 => Message::Segment(out.remove(0)),
         _ => Message::Array(out),
-    }
+    })
 }
 
 #[cfg(test)]
@@ -278,28 +344,23 @@ mod tests {
     use tokio::sync::oneshot;
 
     use super::*;
-    use crate::core::model::{ChannelRef, MessageSegment, OutboundMessage};
+    use crate::core::model::{ChannelRef, ForwardNode, MessageSegment, OutboundMessage};
 
     #[tokio::test]
     async fn sender_uses_group_forward_action_for_forward_bundle() {
         let (sender, mut rx) = OneBotSender::test_pair();
         let message = OutboundMessage::new(
             ChannelRef::group("onebot/v11", "123456"),
-            vec![MessageSegment::Unknown {
-                kind: ONEBOT_FORWARD_KIND.to_string(),
-                data: serde_json::json!({
-                    "nodes": [
-                        {
-                            "user_id": 42,
-                            "nickname": "B站直播订阅",
-                            "content": [
-                                {"Text": {"text": "B站直播订阅\n当前会话共 1 条"}},
-                                {"Image": {"url": "https://example.com/cover.jpg"}}
-                            ]
-                        }
-                    ]
-                }),
-            }],
+            vec![MessageSegment::forward(vec![ForwardNode::new(
+                "42",
+                "B站直播订阅",
+                vec![
+                    MessageSegment::text("B站直播订阅\n当前会话共 1 条"),
+                    MessageSegment::Image {
+                        url: "https://example.com/cover.jpg".to_string(),
+                    },
+                ],
+            )])],
         );
 
         sender.send(message).await.expect("send should succeed");
@@ -326,6 +387,141 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rich_segments_round_trip_through_onebot_message() {
+        let segments = vec![
+            MessageSegment::reply("1001"),
+            MessageSegment::text("look"),
+            MessageSegment::Emoji {
+                id: "14".to_string(),
+            },
+            MessageSegment::Audio {
+                url: "https://example.com/a.amr".to_string(),
+            },
+            MessageSegment::Video {
+                url: "https://example.com/v.mp4".to_string(),
+            },
+            MessageSegment::Forward {
+                id: Some("fwd-1".to_string()),
+                nodes: Vec::new(),
+            },
+            MessageSegment::Link {
+                url: "https://example.com".to_string(),
+                title: "Example".to_string(),
+                content: Some("desc".to_string()),
+                image: None,
+            },
+            MessageSegment::Card {
+                format: "json".to_string(),
+                data: "{}".to_string(),
+            },
+            MessageSegment::Unknown {
+                kind: "dice".to_string(),
+                data: Value::Null,
+            },
+        ];
+
+        let message = to_onebot_message(segments.clone()).expect("segments should convert");
+        let raw = serde_json::to_value(&message).expect("message should serialize");
+        assert_eq!(raw[0]["type"], Value::from("reply"));
+        assert_eq!(raw[2]["type"], Value::from("face"));
+        assert_eq!(raw[3]["type"], Value::from("record"));
+        assert_eq!(raw[6]["type"], Value::from("share"));
+
+        let decoded: Message = serde_json::from_value(raw).expect("message should decode");
+        assert_eq!(
+            crate::adapter::onebot::v11::ctx::to_kernel_segments(&decoded),
+            segments
+        );
+    }
+
+    #[test]
+    fn received_forward_bundles_are_sent_back_as_one_bundle() {
+        let raw = serde_json::json!([
+            {"type": "node", "data": {"user_id": 1, "nickname": "a", "content": "first"}},
+            {"type": "node", "data": {"user_id": 2, "nickname": "b", "content": [
+                {"type": "text", "data": {"text": "second"}}
+            ]}},
+        ]);
+        let inbound: Message = serde_json::from_value(raw).expect("bundle should decode");
+
+        let segments = crate::adapter::onebot::v11::ctx::to_kernel_segments(&inbound);
+        let [MessageSegment::Forward { id: None, nodes }] = segments.as_slice() else {
+            panic!("nodes should merge into one bundle: {segments:?}");
+        };
+        assert_eq!(nodes.len(), 2);
+
+        let outbound =
+            serde_json::to_value(to_onebot_message(segments).expect("bundle is sendable"))
+                .expect("message should serialize");
+        let outbound = outbound.as_array().expect("bundle is an array");
+        assert_eq!(outbound.len(), 2);
+        assert_eq!(outbound[0]["type"], Value::from("node"));
+        assert_eq!(outbound[1]["data"]["nickname"], Value::from("b"));
+        assert_eq!(
+            outbound[1]["data"]["content"]["data"]["text"],
+            Value::from("second")
+        );
+    }
+
+    #[test]
+    fn unsendable_segments_are_rejected_instead_of_dropped() {
+        let cases = [
+            (
+                MessageSegment::Card {
+                    format: "markdown".to_string(),
+                    data: "# hi".to_string(),
+                },
+                "OneBot v11 cannot send `markdown` cards",
+            ),
+            (
+                MessageSegment::Unknown {
+                    kind: "hologram".to_string(),
+                    data: Value::Null,
+                },
+                "OneBot v11 cannot send `hologram` segments",
+            ),
+            (
+                MessageSegment::forward(vec![ForwardNode::new(
+                    "42",
+                    "bot",
+                    vec![MessageSegment::text("inner")],
+                )]),
+                "OneBot v11 can only send a forward node bundle as the whole message",
+            ),
+        ];
+
+        for (segment, expected) in cases {
+            let err = to_onebot_message(vec![MessageSegment::text("before"), segment])
+                .expect_err("segment should be rejected");
+            assert_eq!(err.to_string(), expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn sender_maps_message_actions_to_onebot_actions() {
+        let (sender, mut rx) = OneBotSender::test_pair();
+        let channel = ChannelRef::group("onebot/v11", "100");
+        let actions = sender.message_actions().expect("onebot supports actions");
+
+        actions.delete_message(&channel, "55").await.unwrap();
+        let request: Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(request["action"], Value::from("delete_msg"));
+        assert_eq!(request["params"]["message_id"], Value::from(55));
+
+        actions.react(&channel, "55", "76").await.unwrap();
+        let request: Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(request["action"], Value::from("set_msg_emoji_like"));
+        assert_eq!(request["params"]["emoji_id"], Value::from("76"));
+
+        assert!(
+            actions
+                .edit_message(&channel, "55", vec![MessageSegment::text("edited")])
+                .await
+                .is_err()
+        );
+    }
+
     #[tokio::test]
     async fn sender_keeps_regular_group_messages_as_send_group_msg() {
         let (sender, mut rx) = OneBotSender::test_pair();
@@ -351,20 +547,11 @@ mod tests {
         );
         let message = OutboundMessage::new(
             ChannelRef::group("onebot/v11", "123456"),
-            vec![MessageSegment::Unknown {
-                kind: ONEBOT_FORWARD_KIND.to_string(),
-                data: serde_json::json!({
-                    "nodes": [
-                        {
-                            "user_id": 42,
-                            "nickname": "原始昵称",
-                            "content": [
-                                {"Text": {"text": "hello"}}
-                            ]
-                        }
-                    ]
-                }),
-            }],
+            vec![MessageSegment::forward(vec![ForwardNode::new(
+                "42",
+                "原始昵称",
+                vec![MessageSegment::text("hello")],
+            )])],
         );
 
         let send_task = tokio::spawn({
@@ -408,7 +595,110 @@ mod tests {
             forward_request["params"]["messages"][0]["data"]["nickname"],
             Value::from("AyiouBot")
         );
+        reply_ok(
+            &pending_api,
+            &forward_request,
+            serde_json::json!({ "message_id": 77, "forward_id": "fwd-77" }),
+        );
+
+        let receipt = send_task.await.expect("send task should finish");
+        assert_eq!(receipt.message_id.as_deref(), Some("77"));
+    }
+
+    fn reply_ok(
+        pending_api: &DashMap<String, oneshot::Sender<ApiResponse>>,
+        request: &Value,
+        data: Value,
+    ) {
+        let echo = request["echo"].clone();
+        let (_, reply_tx) = pending_api
+            .remove(&echo.to_string())
+            .expect("pending response should exist");
+        reply_tx
+            .send(ApiResponse {
+                status: "ok".to_string(),
+                retcode: 0,
+                data,
+                echo: Some(echo),
+            })
+            .expect("response should send");
+    }
+
+    #[tokio::test]
+    async fn sender_returns_message_id_from_echo_matched_response() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let pending_api = Arc::new(DashMap::<String, oneshot::Sender<ApiResponse>>::new());
+        let sender = OneBotSender::with_runtime(
+            tx,
+            pending_api.clone(),
+            Arc::new(AtomicU64::new(1)),
+            Arc::new(RwLock::new(None)),
+        );
+
+        let send_task = tokio::spawn(async move {
+            sender
+                .send(OutboundMessage::text(
+                    ChannelRef::direct("onebot/v11", "10001"),
+                    "hello",
+                ))
+                .await
+        });
+
+        let raw = rx.recv().await.expect("request should be sent");
+        let request: Value = serde_json::from_str(&raw).expect("request json should decode");
+        assert_eq!(request["action"], Value::from("send_private_msg"));
+        reply_ok(
+            &pending_api,
+            &request,
+            serde_json::json!({ "message_id": 4242 }),
+        );
 
-        send_task.await.expect("send task should finish");
+        let receipt = send_task
+            .await
+            .expect("send task should finish")
+            .expect("send should succeed");
+        assert_eq!(receipt.message_id.as_deref(), Some("4242"));
+    }
+
+    #[tokio::test]
+    async fn sender_surfaces_failed_send_responses() {
+        let (tx, mut rx) = mpsc::channel(8);
+        let pending_api = Arc::new(DashMap::<String, oneshot::Sender<ApiResponse>>::new());
+        let sender = OneBotSender::with_runtime(
+            tx,
+            pending_api.clone(),
+            Arc::new(AtomicU64::new(1)),
+            Arc::new(RwLock::new(None)),
+        );
+
+        let send_task = tokio::spawn(async move {
+            sender
+                .send(OutboundMessage::text(
+                    ChannelRef::group("onebot/v11", "100"),
+                    "hello",
+                ))
+                .await
+        });
+
+        let raw = rx.recv().await.expect("request should be sent");
+        let request: Value = serde_json::from_str(&raw).expect("request json should decode");
+        let echo = request["echo"].clone();
+        let (_, reply_tx) = pending_api
+            .remove(&echo.to_string())
+            .expect("pending response should exist");
+        reply_tx
+            .send(ApiResponse {
+                status: "failed".to_string(),
+                retcode: 120,
+                data: Value::Null,
+                echo: Some(echo),
+            })
+            .expect("response should send");
+
+        let err = send_task
+            .await
+            .expect("send task should finish")
+            .expect_err("failed response should surface");
+        assert!(err.to_string().contains("send_group_msg"));
     }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use log::{error, info};
 use tokio::{sync::mpsc, task::JoinHandle};
@@ -10,9 +10,14 @@ use crate::core::control::RuntimeControlHandle;
 use crate::core::{
     adapter::{Adapter, AdapterRuntime},
     context::Context,
+    conversation::ConversationWaiters,
+    help::{HelpOptions, SuggestionOptions},
+    middleware::Middleware,
     plugin::{
+        Capability, CatchUnwind, CircuitBreaker, DispatchErrorPolicy, Dispatcher,
         PermissionService, PluginRuntimeState, RegisteredPlugin, RuntimePlugin,
         RuntimePluginEngine, RuntimePluginServices, discovered_plugins, normalize_command_prefixes,
+        panic_message,
     },
     service::{RuntimeService, ServiceRegistry},
 };
@@ -47,22 +52,36 @@ pub struct Bot<A: Adapter> {
     service_registry: ServiceRegistry,
     permission_service: Option<Arc<dyn PermissionService>>,
     command_prefixes: Arc<[String]>,
+    help: Option<HelpOptions>,
+    suggestions: Option<SuggestionOptions>,
+    handler_timeout: Option<Duration>,
+    circuit_breaker: Option<CircuitBreaker>,
+    error_policy: DispatchErrorPolicy,
+    middleware: Vec<(i32, Arc<dyn Middleware>)>,
     runtime_options: BotRuntimeOptions,
     #[cfg(feature = "control-plane")]
     control_plane_options: Option<ControlPlaneOptions>,
 }
 
 struct BotRuntime {
-    engine: Arc<tokio::sync::RwLock<RuntimePluginEngine>>,
+    dispatcher: Dispatcher,
     options: BotRuntimeOptions,
+    capabilities: Arc<[Capability]>,
+    waiters: ConversationWaiters,
 }
 
 impl BotRuntime {
-    const fn new(
-        engine: Arc<tokio::sync::RwLock<RuntimePluginEngine>>,
+    fn new(
+        dispatcher: Dispatcher,
         options: BotRuntimeOptions,
+        capabilities: Arc<[Capability]>,
     ) -> Self {
-        Self { engine, options }
+        Self {
+            dispatcher,
+            options,
+            capabilities,
+            waiters: ConversationWaiters::new(),
+        }
     }
 
     async fn run(self, mut event_rx: mpsc::Receiver<Context>) {
@@ -80,6 +99,14 @@ impl BotRuntime {
                         info!("Adapter channel closed.");
                         break;
                     };
+                    // Replies to a pending `wait_for` bypass the worker queue, so they
+                    // still arrive while every worker is busy waiting.
+                    let ctx = ctx
+                        .with_capabilities(self.capabilities.clone())
+                        .with_waiters(self.waiters.clone());
+                    let Some(ctx) = self.waiters.deliver(ctx) else {
+                        continue;
+                    };
                     match self.options.overflow_policy {
                         QueueOverflowPolicy::Backpressure => {
                             if work_tx.send(ctx).await.is_err() {
@@ -103,6 +130,7 @@ impl BotRuntime {
         }
 
         drop(work_tx);
+        self.waiters.cancel_all();
         for handle in worker_handles {
             if !drain_workers {
                 handle.abort();
@@ -117,7 +145,7 @@ impl BotRuntime {
     ) -> Vec<JoinHandle<()>> {
         (0..self.options.worker_count)
             .map(|_| {
-                let engine = self.engine.clone();
+                let dispatcher = self.dispatcher.clone();
                 let worker_rx = worker_rx.clone();
                 tokio::spawn(async move {
                     loop {
@@ -130,12 +158,23 @@ impl BotRuntime {
                             break;
                         };
 
-                        let dispatch_result = {
-                            let engine = engine.read().await;
-                            engine.handle_all(&ctx).await
-                        };
-                        if let Err(err) = dispatch_result {
-                            error!("Plugin dispatch error: {err}");
+                        // Handler panics are caught by the engine; this keeps the worker
+                        // alive when dispatch itself panics.
+                        let dispatch_result =
+                            CatchUnwind(Box::pin(dispatcher.dispatch(&ctx))).await;
+                        match dispatch_result {
+                            Ok(Ok(report)) => {
+                                for err in report.errors() {
+                                    error!("Plugin handler error: {err}");
+                                }
+                            }
+                            Ok(Err(err)) => error!("Plugin dispatch error: {err}"),
+                            Err(payload) => {
+                                error!(
+                                    "Plugin dispatch panicked: {}",
+                                    panic_message(payload.as_ref())
+                                );
+                            }
                         }
                     }
                 })
@@ -152,6 +191,12 @@ impl<A: Adapter> Bot<A> {
             service_registry: ServiceRegistry::default(),
             permission_service: None,
             command_prefixes: Arc::from([]),
+            help: None,
+            suggestions: None,
+            handler_timeout: None,
+            circuit_breaker: None,
+            error_policy: DispatchErrorPolicy::Stop,
+            middleware: Vec::new(),
             runtime_options: BotRuntimeOptions::default(),
             #[cfg(feature = "control-plane")]
             control_plane_options: None,
@@ -176,6 +221,29 @@ impl<A: Adapter> Bot<A> {
         self
     }
 
+    /// Cancel handlers that run longer than `timeout` unless they declare their own.
+    #[must_use]
+    pub const fn handler_timeout(mut self, timeout: Duration) -> Self {
+        self.handler_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep running or skip the remaining handlers of an event after one fails.
+    ///
+    /// Handlers may override it with `HandlerDecl::on_error`.
+    #[must_use]
+    pub const fn dispatch_error_policy(mut self, policy: DispatchErrorPolicy) -> Self {
+        self.error_policy = policy;
+        self
+    }
+
+    /// Auto-disable plugins whose handlers fail too many times in a row.
+    #[must_use]
+    pub const fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(breaker);
+        self
+    }
+
     #[must_use]
     pub fn command_prefix(mut self, prefix: impl Into<String>) -> Self {
         self.command_prefixes = normalize_command_prefixes([prefix]);
@@ -191,6 +259,29 @@ impl<A: Adapter> Bot<A> {
         self
     }
 
+    /// Answer `help` and `help <command>` from the plugins' command metadata.
+    #[must_use]
+    pub fn with_help(mut self, options: HelpOptions) -> Self {
+        self.help = Some(options);
+        self
+    }
+
+    /// Reply "did you mean" for prefixed commands that match no plugin.
+    #[must_use]
+    pub fn with_suggestions(mut self, options: SuggestionOptions) -> Self {
+        self.suggestions = Some(options);
+        self
+    }
+
+    /// Run `middleware` around every event and matched handler.
+    ///
+    /// Lower priorities run first; equal priorities keep registration order.
+    #[must_use]
+    pub fn with_middleware<M: Middleware>(mut self, priority: i32, middleware: M) -> Self {
+        self.middleware.push((priority, Arc::new(middleware)));
+        self
+    }
+
     #[must_use]
     pub fn with_service<S>(mut self, service: S) -> Self
     where
@@ -248,6 +339,7 @@ impl<A: Adapter> Bot<A> {
             sender,
             capabilities,
         } = self.adapter.start().await;
+        let advertised: Arc<[Capability]> = capabilities.clone().into();
         let runtime_state = PluginRuntimeState::default();
         let mut engine = RuntimePluginEngine::with_options(
             RuntimePluginServices::new()
@@ -258,6 +350,22 @@ impl<A: Adapter> Bot<A> {
             runtime_state.clone(),
             self.command_prefixes.clone(),
         );
+        if let Some(help) = self.help.take() {
+            engine = engine.with_help(help);
+        }
+        if let Some(suggestions) = self.suggestions.take() {
+            engine = engine.with_suggestions(suggestions);
+        }
+        if let Some(timeout) = self.handler_timeout {
+            engine = engine.with_handler_timeout(timeout);
+        }
+        if let Some(breaker) = self.circuit_breaker {
+            engine = engine.with_circuit_breaker(breaker);
+        }
+        engine = engine.with_error_policy(self.error_policy);
+        for (priority, middleware) in self.middleware.drain(..) {
+            engine = engine.with_middleware(priority, middleware);
+        }
         for registered in self.plugins.drain(..) {
             engine.push_registered(registered);
         }
@@ -273,7 +381,10 @@ impl<A: Adapter> Bot<A> {
         }
 
         info!("Loaded {} plugins", engine.plugins().len());
-        let engine = Arc::new(tokio::sync::RwLock::new(engine));
+        // Workers dispatch from published snapshots; the mutex only serializes
+        // lifecycle actions from the control plane and shutdown.
+        let dispatcher = engine.dispatcher();
+        let engine = Arc::new(tokio::sync::Mutex::new(engine));
         #[cfg(feature = "control-plane")]
         let control = RuntimeControlHandle::new(engine.clone());
         #[cfg(feature = "control-plane")]
@@ -283,10 +394,10 @@ impl<A: Adapter> Bot<A> {
             error!("Control plane configuration error: {err}");
             return;
         }
-        let runtime = BotRuntime::new(engine.clone(), self.runtime_options.clone());
+        let runtime = BotRuntime::new(dispatcher, self.runtime_options.clone(), advertised);
         runtime.run(events).await;
 
-        if let Err(err) = engine.write().await.stop_all().await {
+        if let Err(err) = engine.lock().await.stop_all().await {
             error!("Plugin shutdown error: {err}");
         }
     }
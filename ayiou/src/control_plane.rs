@@ -1,4 +1,4 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{net::SocketAddr, sync::Arc, time::Instant};
 
 use anyhow::{Result, anyhow};
 #[cfg(feature = "embedded-webui")]
@@ -380,6 +380,9 @@ struct PluginInstanceStateDto {
     config_lifecycle_state: String,
     lifecycle_state: String,
     last_error: Option<String>,
+    timeouts: u64,
+    consecutive_failures: u32,
+    auto_reenable_in_ms: Option<u64>,
 }
 
 impl From<PluginInstanceState> for PluginInstanceStateDto {
@@ -391,6 +394,12 @@ impl From<PluginInstanceState> for PluginInstanceStateDto {
             config_lifecycle_state: format!("{:?}", state.config_lifecycle_state),
             lifecycle_state: format!("{:?}", state.lifecycle_state),
             last_error: state.last_error,
+            timeouts: state.timeouts,
+            consecutive_failures: state.consecutive_failures,
+            auto_reenable_in_ms: state.auto_reenable_at.map(|at| {
+                u64::try_from(at.saturating_duration_since(Instant::now()).as_millis())
+                    .unwrap_or(u64::MAX)
+            }),
         }
     }
 }
@@ -417,6 +426,7 @@ fn capability_name(capability: Capability) -> String {
         Capability::Reaction => "reaction".to_string(),
         Capability::GroupModeration => "group_moderation".to_string(),
         Capability::RichSegments => "rich_segments".to_string(),
+        Capability::MessageEdit => "message_edit".to_string(),
         Capability::Custom(name) => name,
     }
 }
@@ -2,8 +2,12 @@ pub mod adapter;
 pub mod command;
 pub mod context;
 pub mod control;
+pub mod conversation;
 pub mod driver;
 pub mod event_bus;
+pub mod form;
+pub mod help;
+pub mod middleware;
 pub mod model;
 pub mod plugin;
 pub mod runtime;
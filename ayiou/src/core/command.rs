@@ -1,4 +1,9 @@
-use crate::core::model::CommandInvocation;
+use std::time::Duration;
+
+use crate::core::{
+    context::Context,
+    model::{CommandInvocation, MessageSegment, RegexMatch, UserRef},
+};
 
 #[derive(Debug, Clone)]
 pub struct ArgsParseError {
@@ -39,6 +44,18 @@ impl std::fmt::Display for ArgsParseError {
 
 impl std::error::Error for ArgsParseError {}
 
+/// What dispatch does when a handler fails with an [`ArgsParseError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum ParseErrorPolicy {
+    /// Reply with the error followed by the command's usage and examples.
+    #[default]
+    Reply,
+    /// Drop the error.
+    Silent,
+    /// Return the error from dispatch like any other handler failure.
+    Propagate,
+}
+
 #[must_use]
 pub fn parse_command_line(text: &str, prefixes: &[&str]) -> Option<CommandInvocation> {
     parse_command_line_with_prefixes(text, prefixes.iter().copied())
@@ -155,6 +172,497 @@ pub fn ensure_no_extra_args(
     Ok(())
 }
 
+/// Remove the boolean switch `--long` / `-s` from `tokens`, returning whether it was given.
+///
+/// Short switches may be clustered, as in `-vq`. Scanning stops at `--`.
+pub fn take_flag(
+    tokens: &mut Vec<String>,
+    long: &str,
+    short: Option<char>,
+) -> std::result::Result<bool, ArgsParseError> {
+    let long_flag = format!("--{long}");
+    let mut found = false;
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        if token == "--" {
+            break;
+        }
+        if *token == long_flag {
+            tokens.remove(index);
+            found = true;
+            continue;
+        }
+        if token
+            .strip_prefix(long_flag.as_str())
+            .is_some_and(|rest| rest.starts_with('='))
+        {
+            return Err(ArgsParseError::new(format!(
+                "Flag `{long_flag}` does not take a value"
+            )));
+        }
+        if let Some(short) = short
+            && is_short_cluster(token)
+            && token.contains(short)
+        {
+            let cluster: String = token.chars().filter(|ch| *ch != short).collect();
+            found = true;
+            if cluster == "-" {
+                tokens.remove(index);
+                continue;
+            }
+            tokens[index] = cluster;
+        }
+        index += 1;
+    }
+
+    Ok(found)
+}
+
+/// Remove the option `--long value`, `--long=value`, `-s value` or `-svalue` from `tokens`.
+///
+/// The last occurrence wins. Scanning stops at `--`.
+pub fn take_option(
+    tokens: &mut Vec<String>,
+    long: &str,
+    short: Option<char>,
+) -> std::result::Result<Option<String>, ArgsParseError> {
+    let long_flag = format!("--{long}");
+    let short_flag = short.map(|short| format!("-{short}"));
+    let mut value = None;
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        if token == "--" {
+            break;
+        }
+        if *token == long_flag || short_flag.as_ref() == Some(token) {
+            let flag = tokens.remove(index);
+            if tokens.get(index).is_none_or(|next| next == "--") {
+                return Err(ArgsParseError::new(format!(
+                    "Missing value for option `{flag}`"
+                )));
+            }
+            value = Some(tokens.remove(index));
+            continue;
+        }
+        if let Some(inline) = token
+            .strip_prefix(long_flag.as_str())
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            value = Some(inline.to_string());
+            tokens.remove(index);
+            continue;
+        }
+        if let Some(short_flag) = &short_flag
+            && let Some(inline) = token.strip_prefix(short_flag.as_str())
+            && !inline.is_empty()
+            && !token.starts_with("--")
+        {
+            value = Some(inline.to_string());
+            tokens.remove(index);
+            continue;
+        }
+        index += 1;
+    }
+
+    Ok(value)
+}
+
+/// [`take_option`] followed by `FromStr` parsing of the value.
+pub fn parse_option<T>(
+    tokens: &mut Vec<String>,
+    long: &str,
+    short: Option<char>,
+) -> std::result::Result<Option<T>, ArgsParseError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    take_option(tokens, long, short)?
+        .map(|value| parse_option_value(&value, long))
+        .transpose()
+}
+
+pub fn parse_option_value<T>(value: &str, long: &str) -> std::result::Result<T, ArgsParseError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|err| ArgsParseError::new(format!("Failed to parse option `--{long}`: {err}")))
+}
+
+pub fn required_option<T>(value: Option<T>, long: &str) -> std::result::Result<T, ArgsParseError> {
+    value.ok_or_else(|| ArgsParseError::new(format!("Missing option: --{long}")))
+}
+
+/// `FromStr` parsing of the named capture `name`, `None` when it did not take part.
+pub fn parse_capture<T>(
+    regex_match: &RegexMatch,
+    name: &str,
+) -> std::result::Result<Option<T>, ArgsParseError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    regex_match
+        .name(name)
+        .map(|value| {
+            value.parse::<T>().map_err(|err| {
+                ArgsParseError::new(format!("Failed to parse capture `{name}`: {err}"))
+            })
+        })
+        .transpose()
+}
+
+pub fn required_capture<T>(value: Option<T>, name: &str) -> std::result::Result<T, ArgsParseError> {
+    value.ok_or_else(|| ArgsParseError::new(format!("Missing capture: {name}")))
+}
+
+/// Reject options nobody declared and drop the `--` separator.
+///
+/// Call this after every declared flag and option was taken.
+pub fn ensure_no_unknown_options(
+    tokens: &mut Vec<String>,
+) -> std::result::Result<(), ArgsParseError> {
+    for (index, token) in tokens.iter().enumerate() {
+        if token == "--" {
+            tokens.remove(index);
+            return Ok(());
+        }
+        let named = token
+            .strip_prefix("--")
+            .or_else(|| token.strip_prefix('-'))
+            .and_then(|rest| rest.chars().next())
+            .is_some_and(|ch| ch.is_ascii_alphabetic());
+        if named {
+            return Err(ArgsParseError::new(format!("Unknown option: {token}")));
+        }
+    }
+
+    Ok(())
+}
+
+fn is_short_cluster(token: &str) -> bool {
+    token
+        .strip_prefix('-')
+        .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|ch| ch.is_ascii_alphabetic()))
+}
+
+/// Command arguments not yet consumed, with the context they were sent in.
+///
+/// Extractors read them in parameter order: tokens from the front, mentions and
+/// attachments from the message segments.
+pub struct CommandArgs<'a> {
+    ctx: &'a Context,
+    tokens: Vec<String>,
+    index: usize,
+    mentions: usize,
+    attachments: usize,
+}
+
+impl<'a> CommandArgs<'a> {
+    #[must_use]
+    pub const fn new(ctx: &'a Context, tokens: Vec<String>) -> Self {
+        Self {
+            ctx,
+            tokens,
+            index: 0,
+            mentions: 0,
+            attachments: 0,
+        }
+    }
+
+    #[must_use]
+    pub const fn context(&self) -> &'a Context {
+        self.ctx
+    }
+
+    /// Segments of the message being parsed, empty for non-message events.
+    #[must_use]
+    pub fn segments(&self) -> &'a [MessageSegment] {
+        self.ctx
+            .message()
+            .map_or(&[], |message| message.segments.as_slice())
+    }
+
+    #[must_use]
+    pub fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.index).map(String::as_str)
+    }
+
+    pub fn next_token(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.index).cloned()?;
+        self.index += 1;
+        Some(token)
+    }
+
+    /// Take every remaining token.
+    pub fn rest(&mut self) -> Vec<String> {
+        let rest = self.tokens[self.index..].to_vec();
+        self.index = self.tokens.len();
+        rest
+    }
+
+    /// Whether all tokens were consumed.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.index >= self.tokens.len()
+    }
+
+    /// Take the next user mentioned by a `Mention` segment, skipping the bot.
+    pub fn next_mention(&mut self) -> Option<String> {
+        let user_id = self.mentioned_users().nth(self.mentions)?.to_string();
+        self.mentions += 1;
+        Some(user_id)
+    }
+
+    fn mentioned_users(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        let bot_id = self.ctx.event().bot_id.as_str();
+        self.segments()
+            .iter()
+            .filter_map(move |segment| match segment {
+                MessageSegment::Mention { user_id } if user_id != bot_id => Some(user_id.as_str()),
+                _ => None,
+            })
+    }
+
+    /// Take the next image, file, audio or video segment.
+    pub fn next_attachment(&mut self) -> Option<Attachment> {
+        let attachment = self
+            .segments()
+            .iter()
+            .filter_map(Attachment::from_segment)
+            .nth(self.attachments)?;
+        self.attachments += 1;
+        Some(attachment)
+    }
+
+    #[must_use]
+    pub fn has_mention(&self) -> bool {
+        self.mentioned_users().nth(self.mentions).is_some()
+    }
+
+    #[must_use]
+    pub fn has_attachment(&self) -> bool {
+        self.segments()
+            .iter()
+            .filter_map(Attachment::from_segment)
+            .nth(self.attachments)
+            .is_some()
+    }
+
+    /// Fail when tokens are left over.
+    pub fn finish(self) -> std::result::Result<(), ArgsParseError> {
+        ensure_no_extra_args(&self.tokens, self.index)
+    }
+}
+
+/// Command parameter type that can be read from [`CommandArgs`].
+///
+/// Every `FromStr` type takes one token. Implement this trait for types that
+/// need the message context, such as mentions or attachments.
+pub trait FromCommandArg: Sized {
+    fn from_command_arg(
+        args: &mut CommandArgs<'_>,
+        name: &str,
+    ) -> std::result::Result<Self, ArgsParseError>;
+
+    /// Whether an `Option<Self>` parameter has a value to read.
+    fn is_present(args: &CommandArgs<'_>) -> bool {
+        !args.is_empty()
+    }
+}
+
+impl<T> FromCommandArg for T
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    fn from_command_arg(
+        args: &mut CommandArgs<'_>,
+        name: &str,
+    ) -> std::result::Result<Self, ArgsParseError> {
+        parse_typed_arg(&args.tokens, &mut args.index, name)
+    }
+}
+
+/// A user on the current platform: the next mention segment, or a typed
+/// `@id` or bare id once no mention is left.
+impl FromCommandArg for UserRef {
+    fn from_command_arg(
+        args: &mut CommandArgs<'_>,
+        name: &str,
+    ) -> std::result::Result<Self, ArgsParseError> {
+        if let Some(user_id) = args.next_mention() {
+            // Adapters that keep mentions in the text also leave an `@id` token.
+            if args.peek() == Some(format!("@{user_id}").as_str()) {
+                args.index += 1;
+            }
+            return Ok(Self::new(args.ctx.event().platform.clone(), user_id));
+        }
+        let token = args
+            .next_token()
+            .ok_or_else(|| ArgsParseError::new(format!("Missing argument: {name}")))?;
+        let user_id = token.strip_prefix('@').unwrap_or(&token);
+        if user_id.is_empty() {
+            return Err(ArgsParseError::new(format!(
+                "Failed to parse argument `{name}`: expected a user"
+            )));
+        }
+
+        Ok(Self::new(args.ctx.event().platform.clone(), user_id))
+    }
+
+    fn is_present(args: &CommandArgs<'_>) -> bool {
+        args.has_mention() || !args.is_empty()
+    }
+}
+
+/// Whether the message mentions the bot.
+///
+/// Consumes no position; `@bot` tokens are dropped from the remaining arguments.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AtBot(pub bool);
+
+impl FromCommandArg for AtBot {
+    fn from_command_arg(
+        args: &mut CommandArgs<'_>,
+        _name: &str,
+    ) -> std::result::Result<Self, ArgsParseError> {
+        let bot_id = args.ctx.event().bot_id.as_str();
+        let mentioned = args.segments().iter().any(
+            |segment| matches!(segment, MessageSegment::Mention { user_id } if user_id == bot_id),
+        );
+        if mentioned {
+            let token = format!("@{bot_id}");
+            let index = args.index;
+            let mut position = 0;
+            args.tokens.retain(|current| {
+                position += 1;
+                position <= index || *current != token
+            });
+        }
+
+        Ok(Self(mentioned))
+    }
+
+    fn is_present(_args: &CommandArgs<'_>) -> bool {
+        true
+    }
+}
+
+/// Kind of media segment an [`Attachment`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentKind {
+    Image,
+    File,
+    Audio,
+    Video,
+}
+
+/// Media sent along with a command, taken from the message segments in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    pub url: Option<String>,
+    pub name: Option<String>,
+    pub mime: Option<String>,
+}
+
+impl Attachment {
+    #[must_use]
+    pub fn from_segment(segment: &MessageSegment) -> Option<Self> {
+        let media = |kind, url: &String| Self {
+            kind,
+            url: Some(url.clone()),
+            name: None,
+            mime: None,
+        };
+        match segment {
+            MessageSegment::Image { url } => Some(media(AttachmentKind::Image, url)),
+            MessageSegment::Audio { url } => Some(media(AttachmentKind::Audio, url)),
+            MessageSegment::Video { url } => Some(media(AttachmentKind::Video, url)),
+            MessageSegment::Attachment { name, url, mime } => Some(Self {
+                kind: AttachmentKind::File,
+                url: url.clone(),
+                name: name.clone(),
+                mime: mime.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+impl FromCommandArg for Attachment {
+    fn from_command_arg(
+        args: &mut CommandArgs<'_>,
+        name: &str,
+    ) -> std::result::Result<Self, ArgsParseError> {
+        args.next_attachment()
+            .ok_or_else(|| ArgsParseError::new(format!("Missing attachment: {name}")))
+    }
+
+    fn is_present(args: &CommandArgs<'_>) -> bool {
+        args.has_attachment()
+    }
+}
+
+/// Duration written as `90`, `45s`, `10m`, `1h30m`, `2d` or `500ms`.
+///
+/// A bare number counts seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(pub Duration);
+
+impl From<HumanDuration> for Duration {
+    fn from(value: HumanDuration) -> Self {
+        value.0
+    }
+}
+
+impl std::str::FromStr for HumanDuration {
+    type Err = ArgsParseError;
+
+    fn from_str(input: &str) -> std::result::Result<Self, Self::Err> {
+        let invalid = || ArgsParseError::new(format!("invalid duration `{input}`"));
+        if let Ok(seconds) = input.parse::<u64>() {
+            return Ok(Self(Duration::from_secs(seconds)));
+        }
+
+        let mut total = Duration::ZERO;
+        let mut rest = input;
+        while !rest.is_empty() {
+            let digits = rest
+                .find(|ch: char| !ch.is_ascii_digit())
+                .ok_or_else(invalid)?;
+            let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+            rest = &rest[digits..];
+            let unit_len = rest
+                .find(|ch: char| ch.is_ascii_digit())
+                .unwrap_or(rest.len());
+            let unit = match &rest[..unit_len] {
+                "ms" => Duration::from_millis(1),
+                "s" => Duration::from_secs(1),
+                "m" => Duration::from_secs(60),
+                "h" => Duration::from_secs(60 * 60),
+                "d" => Duration::from_secs(24 * 60 * 60),
+                _ => return Err(invalid()),
+            };
+            rest = &rest[unit_len..];
+            total = u32::try_from(value)
+                .ok()
+                .and_then(|value| unit.checked_mul(value))
+                .and_then(|part| total.checked_add(part))
+                .ok_or_else(invalid)?;
+        }
+        if input.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self(total))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -172,4 +680,142 @@ mod tests {
             tokenize_command_args("\"hello world\" 'x y' z\\ z").expect("tokenize should succeed");
         assert_eq!(tokens, vec!["hello world", "x y", "z z"]);
     }
+
+    fn tokens(args: &str) -> Vec<String> {
+        tokenize_command_args(args).expect("tokenize should succeed")
+    }
+
+    #[test]
+    fn flags_and_options_are_taken_in_gnu_style() {
+        let mut args = tokens("-vq --count 3 --name=bob -x7 rest --verbose -- --count 9");
+
+        assert_eq!(
+            parse_option::<u32>(&mut args, "count", Some('c')).unwrap(),
+            Some(3)
+        );
+        assert_eq!(
+            take_option(&mut args, "name", None).unwrap().as_deref(),
+            Some("bob")
+        );
+        assert_eq!(
+            take_option(&mut args, "extra", Some('x'))
+                .unwrap()
+                .as_deref(),
+            Some("7")
+        );
+        assert!(take_flag(&mut args, "verbose", Some('v')).unwrap());
+        assert!(take_flag(&mut args, "quiet", Some('q')).unwrap());
+        assert!(!take_flag(&mut args, "force", Some('f')).unwrap());
+        ensure_no_unknown_options(&mut args).unwrap();
+        assert_eq!(args, vec!["rest", "--count", "9"]);
+    }
+
+    fn message_context(segments: Vec<MessageSegment>) -> Context {
+        use crate::core::model::{BotId, ChannelRef, EventEnvelope, MessageEvent, PlatformId};
+
+        let platform = PlatformId::new("test");
+        let message = MessageEvent::new(
+            UserRef::new(platform.clone(), "alice"),
+            ChannelRef::group(platform.clone(), "g1"),
+            "",
+        )
+        .with_segments(segments);
+        Context::new(
+            EventEnvelope::new(BotId::new("bot"), platform).with_message(message),
+            None,
+            (),
+        )
+    }
+
+    #[test]
+    fn extractors_read_mentions_durations_and_attachments() {
+        let ctx = message_context(vec![
+            MessageSegment::text("/mute"),
+            MessageSegment::Mention {
+                user_id: "bob".to_string(),
+            },
+            MessageSegment::Mention {
+                user_id: "bot".to_string(),
+            },
+            MessageSegment::text(" 1h30m"),
+            MessageSegment::Image {
+                url: "https://img".to_string(),
+            },
+        ]);
+        let mut args = CommandArgs::new(&ctx, tokens("1h30m"));
+
+        let user = UserRef::from_command_arg(&mut args, "user").unwrap();
+        assert_eq!((user.platform().as_str(), user.user_id()), ("test", "bob"));
+        assert_eq!(
+            AtBot::from_command_arg(&mut args, "at").unwrap(),
+            AtBot(true)
+        );
+        let duration = HumanDuration::from_command_arg(&mut args, "duration").unwrap();
+        assert_eq!(Duration::from(duration), Duration::from_secs(90 * 60));
+        assert!(!UserRef::is_present(&args));
+
+        assert!(Attachment::is_present(&args));
+        let image = Attachment::from_command_arg(&mut args, "image").unwrap();
+        assert_eq!(image.kind, AttachmentKind::Image);
+        assert_eq!(image.url.as_deref(), Some("https://img"));
+        assert!(!Attachment::is_present(&args));
+        let Err(err) = Attachment::from_command_arg(&mut args, "image") else {
+            panic!("only one attachment was sent");
+        };
+        assert_eq!(err.message(), "Missing attachment: image");
+        args.finish().unwrap();
+    }
+
+    #[test]
+    fn users_fall_back_to_typed_ids_once_mentions_run_out() {
+        let ctx = message_context(vec![
+            MessageSegment::text("/swap"),
+            MessageSegment::Mention {
+                user_id: "alice".to_string(),
+            },
+            MessageSegment::text(" @carol 42"),
+        ]);
+        let mut args = CommandArgs::new(&ctx, tokens("@carol 42"));
+
+        for expected in ["alice", "carol", "42"] {
+            assert!(UserRef::is_present(&args));
+            let user = UserRef::from_command_arg(&mut args, "user").unwrap();
+            assert_eq!(user.user_id(), expected);
+        }
+        assert!(!UserRef::is_present(&args));
+        args.finish().unwrap();
+    }
+
+    #[test]
+    fn human_durations_accept_units_and_bare_seconds() {
+        let parse = |input: &str| input.parse::<HumanDuration>().map(Duration::from);
+        assert_eq!(parse("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse("2d").unwrap(), Duration::from_secs(2 * 24 * 3600));
+        assert_eq!(parse("1s500ms").unwrap(), Duration::from_millis(1500));
+        for invalid in ["", "m", "10x", "5m3", "-1s"] {
+            assert!(parse(invalid).is_err(), "{invalid} should be rejected");
+        }
+    }
+
+    #[test]
+    fn malformed_options_are_reported() {
+        let mut args = tokens("--count");
+        let err = take_option(&mut args, "count", None).unwrap_err();
+        assert_eq!(err.message(), "Missing value for option `--count`");
+
+        let mut args = tokens("--verbose=yes");
+        assert!(take_flag(&mut args, "verbose", None).is_err());
+
+        let mut args = tokens("-5 --color");
+        let err = ensure_no_unknown_options(&mut args).unwrap_err();
+        assert_eq!(err.message(), "Unknown option: --color");
+
+        let mut args = tokens("--count x");
+        let err = parse_option::<u32>(&mut args, "count", None).unwrap_err();
+        assert!(
+            err.message()
+                .starts_with("Failed to parse option `--count`")
+        );
+    }
 }
@@ -1,20 +1,91 @@
-use std::{any::Any, borrow::Cow, sync::Arc};
+use std::{
+    any::{Any, TypeId},
+    borrow::Cow,
+    collections::HashMap,
+    sync::Arc,
+    time::Duration,
+};
 
 use anyhow::{Result, anyhow};
 
 use crate::core::{
-    model::{EventEnvelope, MessageEvent, OutboundMessage},
-    plugin::OutboundSender,
+    conversation::{ConversationWaiters, DEFAULT_PROMPT_TIMEOUT, PendingReply, WaitError},
+    model::{
+        ChannelKind, ChannelRef, EventEnvelope, KernelEvent, MessageEvent, MessageSegment,
+        OutboundMessage, OutboundReceipt, PlatformEvent,
+    },
+    plugin::{Capability, OutboundSender, message_actions_of},
 };
 
+/// Values attached to a [`Context`], at most one per type.
+#[derive(Clone, Default)]
+pub struct Extensions {
+    values: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+}
+
+impl Extensions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach `value`, replacing any earlier value of the same type.
+    pub fn insert<T>(&mut self, value: T)
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.insert(TypeId::of::<T>(), Arc::new(value));
+    }
+
+    #[must_use]
+    pub fn get<T>(&self) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.values
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.as_ref().downcast_ref::<T>())
+    }
+
+    #[must_use]
+    pub fn contains<T>(&self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.contains_key(&TypeId::of::<T>())
+    }
+
+    /// Detach the value of type `T`, returning whether there was one.
+    pub fn remove<T>(&mut self) -> bool
+    where
+        T: Any + Send + Sync,
+    {
+        self.values.remove(&TypeId::of::<T>()).is_some()
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
 #[derive(Clone)]
 pub struct Context {
     envelope: EventEnvelope,
     outbound: Option<Arc<dyn OutboundSender>>,
-    extension: Arc<dyn Any + Send + Sync>,
+    /// What the adapter advertised; `None` when the context was built without it.
+    capabilities: Option<Arc<[Capability]>>,
+    extensions: Extensions,
+    waiters: Option<ConversationWaiters>,
 }
 
 impl Context {
+    /// Context for one event; `extension` is usually the adapter's raw context.
     pub fn new<T>(
         envelope: EventEnvelope,
         outbound: Option<Arc<dyn OutboundSender>>,
@@ -23,13 +94,49 @@ impl Context {
     where
         T: Any + Send + Sync,
     {
+        let mut extensions = Extensions::new();
+        extensions.insert(extension);
         Self {
             envelope,
             outbound,
-            extension: Arc::new(extension),
+            capabilities: None,
+            extensions,
+            waiters: None,
         }
     }
 
+    /// Attach another extension, see [`Self::set_extension`].
+    #[must_use]
+    pub fn with_extension<T>(mut self, extension: T) -> Self
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions.insert(extension);
+        self
+    }
+
+    /// Attach the adapter's advertised capabilities, so message actions it lacks
+    /// fail before reaching the sender.
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Arc<[Capability]>) -> Self {
+        self.capabilities = Some(capabilities);
+        self
+    }
+
+    /// `None` when nothing was advertised, so the sender alone decides.
+    fn advertised_capabilities(&self) -> Option<&[Capability]> {
+        self.capabilities
+            .as_deref()
+            .filter(|capabilities| !capabilities.is_empty())
+    }
+
+    /// Attach the runtime's reply waiters so handlers can `wait_for` follow-up messages.
+    #[must_use]
+    pub fn with_waiters(mut self, waiters: ConversationWaiters) -> Self {
+        self.waiters = Some(waiters);
+        self
+    }
+
     #[must_use]
     pub const fn event(&self) -> &EventEnvelope {
         &self.envelope
@@ -40,6 +147,16 @@ impl Context {
         self.envelope.message()
     }
 
+    #[must_use]
+    pub const fn platform_event(&self) -> Option<&PlatformEvent> {
+        self.envelope.platform_event()
+    }
+
+    #[must_use]
+    pub const fn kernel_event(&self) -> Option<&KernelEvent> {
+        self.envelope.kernel_event()
+    }
+
     #[must_use]
     pub fn text(&self) -> Cow<'_, str> {
         self.message()
@@ -50,43 +167,206 @@ impl Context {
     #[must_use]
     pub fn user_id(&self) -> Cow<'_, str> {
         self.message()
-            .map(|msg| Cow::Borrowed(msg.sender.user_id()))
-            .unwrap_or_else(|| Cow::Borrowed(""))
+            .map(|msg| msg.sender.user_id())
+            .or_else(|| {
+                self.platform_event()
+                    .and_then(|event| event.user.as_ref())
+                    .or_else(|| self.kernel_event().and_then(KernelEvent::user))
+                    .map(|user| user.user_id())
+            })
+            .map_or_else(|| Cow::Borrowed(""), Cow::Borrowed)
     }
 
     #[must_use]
     pub fn group_id(&self) -> Option<Cow<'_, str>> {
-        self.message().and_then(|msg| match msg.channel.kind() {
-            crate::core::model::ChannelKind::Group => Some(Cow::Borrowed(msg.channel.channel_id())),
-            crate::core::model::ChannelKind::Direct | crate::core::model::ChannelKind::Channel => {
-                None
-            }
+        self.channel().and_then(|channel| match channel.kind() {
+            ChannelKind::Group => Some(Cow::Borrowed(channel.channel_id())),
+            ChannelKind::Direct | ChannelKind::Channel => None,
         })
     }
 
+    /// Whether the message is addressed to the bot: a direct message, or one
+    /// that mentions it.
+    #[must_use]
+    pub fn is_to_me(&self) -> bool {
+        let Some(message) = self.message() else {
+            return false;
+        };
+        let bot_id = self.event().bot_id.as_str();
+        message.channel.kind() == ChannelKind::Direct
+            || message.segments.iter().any(
+                |segment| matches!(segment, MessageSegment::Mention { user_id } if user_id == bot_id),
+            )
+    }
+
+    /// Channel the current event happened in, for message and non-message events.
+    #[must_use]
+    pub fn channel(&self) -> Option<&ChannelRef> {
+        self.message()
+            .map(|msg| &msg.channel)
+            .or_else(|| {
+                self.platform_event()
+                    .and_then(|event| event.channel.as_ref())
+            })
+            .or_else(|| self.kernel_event().and_then(KernelEvent::channel))
+    }
+
+    /// Extension of type `T`, such as the adapter's raw context or data a
+    /// middleware attached.
     #[must_use]
     pub fn extension<T>(&self) -> Option<&T>
     where
         T: Any + Send + Sync + 'static,
     {
-        self.extension.as_ref().downcast_ref::<T>()
+        self.extensions.get::<T>()
     }
 
-    pub async fn reply(&self, message: OutboundMessage) -> Result<()> {
+    /// Attach `extension` for handlers to read with [`Self::extension`],
+    /// replacing any earlier value of the same type.
+    pub fn set_extension<T>(&mut self, extension: T)
+    where
+        T: Any + Send + Sync,
+    {
+        self.extensions.insert(extension);
+    }
+
+    #[must_use]
+    pub const fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn extensions_mut(&mut self) -> &mut Extensions {
+        &mut self.extensions
+    }
+
+    /// Send `message` through the adapter, returning the receipt with the sent message id
+    /// when the platform reports one.
+    pub async fn reply(&self, message: OutboundMessage) -> Result<OutboundReceipt> {
         let sender = self
             .outbound
             .as_ref()
             .ok_or_else(|| anyhow!("adapter does not provide proactive message sending"))?;
 
-        sender.send(message).await?;
-        Ok(())
+        sender.send(message).await
     }
 
-    pub async fn reply_text(&self, text: impl Into<String>) -> Result<()> {
-        let message = self
-            .message()
-            .ok_or_else(|| anyhow!("current event does not carry a message context"))?;
-        self.reply(OutboundMessage::text(message.channel.clone(), text))
+    pub async fn reply_text(&self, text: impl Into<String>) -> Result<OutboundReceipt> {
+        self.reply(OutboundMessage::text(self.require_channel()?.clone(), text))
             .await
     }
+
+    /// Delete (recall) a message in the current channel.
+    pub async fn delete_message(&self, message_id: &str) -> Result<()> {
+        message_actions_of(
+            self.outbound.as_ref(),
+            self.advertised_capabilities(),
+            Capability::MessageDelete,
+            "delete_message",
+        )?
+        .delete_message(self.require_channel()?, message_id)
+        .await
+    }
+
+    /// React to a message in the current channel with a platform emoji id or character.
+    pub async fn react(&self, message_id: &str, reaction: &str) -> Result<()> {
+        message_actions_of(
+            self.outbound.as_ref(),
+            self.advertised_capabilities(),
+            Capability::Reaction,
+            "react",
+        )?
+        .react(self.require_channel()?, message_id, reaction)
+        .await
+    }
+
+    pub async fn edit_message(
+        &self,
+        message_id: &str,
+        segments: Vec<MessageSegment>,
+    ) -> Result<OutboundReceipt> {
+        message_actions_of(
+            self.outbound.as_ref(),
+            self.advertised_capabilities(),
+            Capability::MessageEdit,
+            "edit_message",
+        )?
+        .edit_message(self.require_channel()?, message_id, segments)
+        .await
+    }
+
+    /// Wait for the next message from the same user in the same channel that passes `filter`.
+    ///
+    /// The matching message is handed to this call instead of being dispatched to
+    /// handlers. Fails with [`WaitError`] on timeout, shutdown, or when the context was
+    /// not dispatched by a bot runtime.
+    pub async fn wait_for<F>(&self, filter: F, timeout: Duration) -> Result<Self>
+    where
+        F: Fn(&Self) -> bool + Send + Sync + 'static,
+    {
+        let pending = self.register_waiter(filter)?;
+        Ok(pending.recv(timeout).await?)
+    }
+
+    /// Ask a question and wait for the answer, see [`Context::wait_for`].
+    ///
+    /// The waiter is registered before the question is sent, so a fast answer is not lost.
+    pub async fn prompt(&self, text: impl Into<String>) -> Result<Self> {
+        self.prompt_with_timeout(text, DEFAULT_PROMPT_TIMEOUT).await
+    }
+
+    /// Like [`Context::prompt`], waiting at most `timeout` for the answer.
+    pub async fn prompt_with_timeout(
+        &self,
+        text: impl Into<String>,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let pending = self.register_waiter(|_| true)?;
+        self.reply_text(text).await?;
+        Ok(pending.recv(timeout).await?)
+    }
+
+    fn register_waiter<F>(&self, filter: F) -> Result<PendingReply, WaitError>
+    where
+        F: Fn(&Self) -> bool + Send + Sync + 'static,
+    {
+        self.waiters
+            .as_ref()
+            .ok_or(WaitError::Unavailable)?
+            .register(self, Arc::new(filter))
+    }
+
+    fn require_channel(&self) -> Result<&ChannelRef> {
+        self.channel()
+            .ok_or_else(|| anyhow!("current event does not carry a channel context"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Context;
+    use crate::core::model::{BotId, EventEnvelope, PlatformId};
+
+    struct RawCtx(u32);
+    struct TraceId(&'static str);
+
+    #[test]
+    fn extensions_are_keyed_by_type() {
+        let mut ctx = Context::new(
+            EventEnvelope::new(BotId::new("bot"), PlatformId::new("test")),
+            None,
+            RawCtx(7),
+        )
+        .with_extension(TraceId("a"));
+        ctx.set_extension(TraceId("b"));
+        ctx.set_extension(vec!["admin".to_string()]);
+
+        assert_eq!(ctx.extension::<RawCtx>().map(|raw| raw.0), Some(7));
+        assert_eq!(ctx.extension::<TraceId>().map(|id| id.0), Some("b"));
+        assert_eq!(ctx.extensions().len(), 3);
+        assert!(ctx.extension::<String>().is_none());
+
+        assert!(ctx.extensions_mut().remove::<TraceId>());
+        assert!(!ctx.extensions().contains::<TraceId>());
+        assert!(ctx.extension::<Vec<String>>().is_some());
+    }
 }
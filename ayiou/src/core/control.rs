@@ -1,43 +1,63 @@
 use std::sync::Arc;
 
 use anyhow::Result;
-use tokio::sync::RwLock;
+use tokio::sync::Mutex;
 
-use crate::core::plugin::{RuntimePluginEngine, RuntimePluginSnapshot};
+use crate::core::plugin::{
+    ApplyConfigOutcome, ConfigUpdate, RuntimePluginEngine, RuntimePluginSnapshot,
+};
 
+/// Serializes lifecycle actions on the engine; dispatch runs from the engine's
+/// [`Dispatcher`](crate::core::plugin::Dispatcher) and never takes this lock.
 #[derive(Clone)]
 pub struct RuntimeControlHandle {
-    engine: Arc<RwLock<RuntimePluginEngine>>,
+    engine: Arc<Mutex<RuntimePluginEngine>>,
 }
 
 impl RuntimeControlHandle {
     #[must_use]
-    pub const fn new(engine: Arc<RwLock<RuntimePluginEngine>>) -> Self {
+    pub const fn new(engine: Arc<Mutex<RuntimePluginEngine>>) -> Self {
         Self { engine }
     }
 
     pub async fn plugin_snapshots(&self) -> Vec<RuntimePluginSnapshot> {
-        self.engine.read().await.plugin_snapshots()
+        self.engine.lock().await.plugin_snapshots()
     }
 
     pub async fn enable_plugin(&self, instance_id: &str) -> Result<()> {
-        self.engine.write().await.enable_plugin(instance_id).await
+        self.engine.lock().await.enable_plugin(instance_id).await
     }
 
     pub async fn disable_plugin(&self, instance_id: &str) -> Result<()> {
-        self.engine.write().await.disable_plugin(instance_id).await
+        self.engine.lock().await.disable_plugin(instance_id).await
     }
 
     pub async fn start_plugin(&self, instance_id: &str) -> Result<()> {
-        self.engine.write().await.start_plugin(instance_id).await
+        self.engine.lock().await.start_plugin(instance_id).await
     }
 
     pub async fn stop_plugin(&self, instance_id: &str) -> Result<()> {
-        self.engine.write().await.stop_plugin(instance_id).await
+        self.engine.lock().await.stop_plugin(instance_id).await
     }
 
     pub async fn reload_plugin(&self, instance_id: &str) -> Result<()> {
-        self.engine.write().await.reload_plugin(instance_id).await
+        self.engine.lock().await.reload_plugin(instance_id).await
+    }
+
+    /// Apply `update`, releasing the engine while the plugin's in-flight
+    /// handlers finish.
+    pub async fn apply_config(
+        &self,
+        instance_id: &str,
+        update: ConfigUpdate,
+    ) -> Result<ApplyConfigOutcome> {
+        let application = self
+            .engine
+            .lock()
+            .await
+            .prepare_config(instance_id, update)
+            .await?;
+        application.apply().await
     }
 }
 
@@ -85,7 +105,7 @@ mod tests {
         let services = RuntimePluginServices::new();
         let mut engine = RuntimePluginEngine::new(services, state);
         engine.push_as("control-plugin", Box::new(ControlPlugin { stopped }));
-        RuntimeControlHandle::new(Arc::new(RwLock::new(engine)))
+        RuntimeControlHandle::new(Arc::new(tokio::sync::Mutex::new(engine)))
     }
 
     #[tokio::test]
@@ -0,0 +1,349 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::Result;
+use dashmap::DashMap;
+use tokio::sync::oneshot;
+
+use crate::core::{
+    context::Context,
+    model::{BotId, ChannelRef},
+};
+
+/// Default time `Context::prompt` waits for an answer.
+pub const DEFAULT_PROMPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+type WaitFilter = Arc<dyn Fn(&Context) -> bool + Send + Sync>;
+
+/// Why a `wait_for` call ended without a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WaitError {
+    /// No message arrived before the timeout.
+    TimedOut(Duration),
+    /// The runtime shut down or dropped the pending wait.
+    Cancelled,
+    /// The context was not dispatched by a runtime that intercepts replies.
+    Unavailable,
+    /// The current event has no user and channel to wait on.
+    NoConversation,
+}
+
+impl std::fmt::Display for WaitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TimedOut(timeout) => write!(f, "no reply received within {timeout:?}"),
+            Self::Cancelled => write!(f, "waiting for a reply was cancelled"),
+            Self::Unavailable => write!(f, "context does not support waiting for replies"),
+            Self::NoConversation => write!(f, "current event has no conversation to wait on"),
+        }
+    }
+}
+
+impl std::error::Error for WaitError {}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct WaiterKey {
+    bot_id: BotId,
+    channel: ChannelRef,
+    user_id: String,
+}
+
+impl WaiterKey {
+    fn of(ctx: &Context) -> Option<Self> {
+        let message = ctx.message()?;
+        Some(Self {
+            bot_id: ctx.event().bot_id.clone(),
+            channel: message.channel.clone(),
+            user_id: message.sender.user_id().to_string(),
+        })
+    }
+}
+
+struct Waiter {
+    id: u64,
+    filter: WaitFilter,
+    tx: oneshot::Sender<Context>,
+}
+
+/// Pending `wait_for` calls, keyed by the bot, user and channel they listen to.
+///
+/// The bot runtime offers every inbound message here before dispatch; a
+/// message taken by a waiter is not routed to any handler.
+#[derive(Clone, Default)]
+pub struct ConversationWaiters {
+    next_id: Arc<AtomicU64>,
+    waiters: Arc<DashMap<WaiterKey, Vec<Waiter>>>,
+}
+
+impl ConversationWaiters {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of handlers currently waiting for a reply.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.waiters.iter().map(|entry| entry.value().len()).sum()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+
+    /// Hand `ctx` to the oldest matching waiter of its conversation.
+    ///
+    /// Returns the context back when nobody took it, so it can be dispatched.
+    /// Filters run without the registry locked, so they may wait on it themselves.
+    pub fn deliver(&self, mut ctx: Context) -> Option<Context> {
+        let key = WaiterKey::of(&ctx)?;
+        let candidates: Vec<(u64, WaitFilter)> = match self.waiters.get_mut(&key) {
+            Some(mut waiters) => {
+                waiters.retain(|waiter| !waiter.tx.is_closed());
+                waiters
+                    .iter()
+                    .map(|waiter| (waiter.id, waiter.filter.clone()))
+                    .collect()
+            }
+            None => return Some(ctx),
+        };
+
+        let mut undelivered = Some(ctx);
+        for (id, filter) in candidates {
+            ctx = undelivered.take()?;
+            if !filter(&ctx) {
+                undelivered = Some(ctx);
+                continue;
+            }
+            // The waiter may have been withdrawn while its filter ran.
+            let waiter = self.waiters.get_mut(&key).and_then(|mut waiters| {
+                let index = waiters.iter().position(|waiter| waiter.id == id)?;
+                Some(waiters.remove(index))
+            });
+            undelivered = match waiter {
+                Some(waiter) => waiter.tx.send(ctx).err(),
+                None => Some(ctx),
+            };
+            if undelivered.is_none() {
+                break;
+            }
+        }
+        self.waiters
+            .remove_if(&key, |_, waiters| waiters.is_empty());
+
+        undelivered
+    }
+
+    /// Wake every pending wait with [`WaitError::Cancelled`].
+    pub fn cancel_all(&self) {
+        self.waiters.clear();
+    }
+
+    pub(crate) fn register(
+        &self,
+        ctx: &Context,
+        filter: WaitFilter,
+    ) -> Result<PendingReply, WaitError> {
+        let key = WaiterKey::of(ctx).ok_or(WaitError::NoConversation)?;
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.waiters
+            .entry(key.clone())
+            .or_default()
+            .push(Waiter { id, filter, tx });
+
+        Ok(PendingReply {
+            waiters: self.clone(),
+            key,
+            id,
+            rx,
+        })
+    }
+
+    fn remove(&self, key: &WaiterKey, id: u64) {
+        if let Some(mut waiters) = self.waiters.get_mut(key) {
+            waiters.retain(|waiter| waiter.id != id);
+        }
+        self.waiters.remove_if(key, |_, waiters| waiters.is_empty());
+    }
+}
+
+/// Registered wait; dropping it withdraws the waiter.
+pub(crate) struct PendingReply {
+    waiters: ConversationWaiters,
+    key: WaiterKey,
+    id: u64,
+    rx: oneshot::Receiver<Context>,
+}
+
+impl PendingReply {
+    pub(crate) async fn recv(mut self, timeout: Duration) -> Result<Context, WaitError> {
+        match tokio::time::timeout(timeout, &mut self.rx).await {
+            Ok(Ok(ctx)) => Ok(ctx),
+            Ok(Err(_)) => Err(WaitError::Cancelled),
+            Err(_) => Err(WaitError::TimedOut(timeout)),
+        }
+    }
+}
+
+impl Drop for PendingReply {
+    fn drop(&mut self) {
+        self.waiters.remove(&self.key, self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{ConversationWaiters, WaitError};
+    use crate::core::{
+        context::Context,
+        model::{BotId, ChannelRef, EventEnvelope, MessageEvent, PlatformId, UserRef},
+    };
+
+    fn message(text: &str, user_id: &str) -> Context {
+        message_to("bot", text, user_id)
+    }
+
+    fn message_to(bot_id: &str, text: &str, user_id: &str) -> Context {
+        let platform = PlatformId::new("test");
+        let message = MessageEvent::new(
+            UserRef::new(platform.clone(), user_id),
+            ChannelRef::group(platform.clone(), "g1"),
+            text,
+        );
+        Context::new(
+            EventEnvelope::new(BotId::new(bot_id), platform).with_message(message),
+            None,
+            (),
+        )
+    }
+
+    #[tokio::test]
+    async fn waiter_takes_next_message_from_same_user_and_channel() {
+        let waiters = ConversationWaiters::new();
+        let ctx = message("/bind", "alice").with_waiters(waiters.clone());
+
+        let wait = tokio::spawn(async move {
+            ctx.wait_for(|reply| reply.text() != "skip", Duration::from_secs(5))
+                .await
+        });
+        while waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(waiters.deliver(message("hello", "bob")).is_some());
+        assert!(waiters.deliver(message("skip", "alice")).is_some());
+        assert!(waiters.deliver(message("group 42", "alice")).is_none());
+
+        let reply = wait.await.unwrap().unwrap();
+        assert_eq!(reply.text(), "group 42");
+        assert!(waiters.is_empty());
+    }
+
+    #[tokio::test]
+    async fn waiters_only_take_messages_sent_to_their_bot() {
+        let waiters = ConversationWaiters::new();
+        let ctx = message_to("bot-a", "/bind", "alice").with_waiters(waiters.clone());
+
+        let wait =
+            tokio::spawn(async move { ctx.wait_for(|_| true, Duration::from_secs(5)).await });
+        while waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(
+            waiters
+                .deliver(message_to("bot-b", "other", "alice"))
+                .is_some()
+        );
+        assert!(
+            waiters
+                .deliver(message_to("bot-a", "mine", "alice"))
+                .is_none()
+        );
+        assert_eq!(wait.await.unwrap().unwrap().text(), "mine");
+    }
+
+    #[tokio::test]
+    async fn filters_may_inspect_the_registry() {
+        let waiters = ConversationWaiters::new();
+        let ctx = message("/bind", "alice").with_waiters(waiters.clone());
+
+        let registry = waiters.clone();
+        let wait = tokio::spawn(async move {
+            ctx.wait_for(move |_| registry.len() == 1, Duration::from_secs(5))
+                .await
+        });
+        while waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        assert!(waiters.deliver(message("hello", "alice")).is_none());
+        assert_eq!(wait.await.unwrap().unwrap().text(), "hello");
+    }
+
+    #[tokio::test]
+    async fn timed_out_and_dropped_waits_are_withdrawn() {
+        let waiters = ConversationWaiters::new();
+        let ctx = message("/bind", "alice").with_waiters(waiters.clone());
+
+        let Err(err) = ctx.wait_for(|_| true, Duration::from_millis(10)).await else {
+            panic!("nobody replies");
+        };
+        assert_eq!(
+            err.downcast_ref::<WaitError>(),
+            Some(&WaitError::TimedOut(Duration::from_millis(10)))
+        );
+        assert!(waiters.is_empty());
+
+        let wait =
+            tokio::spawn(async move { ctx.wait_for(|_| true, Duration::from_secs(5)).await });
+        while waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        wait.abort();
+        let _ = wait.await;
+        assert!(waiters.is_empty());
+        assert!(waiters.deliver(message("late", "alice")).is_some());
+    }
+
+    #[tokio::test]
+    async fn cancel_all_wakes_pending_waits() {
+        let waiters = ConversationWaiters::new();
+        let ctx = message("/bind", "alice").with_waiters(waiters.clone());
+
+        let wait =
+            tokio::spawn(async move { ctx.wait_for(|_| true, Duration::from_secs(5)).await });
+        while waiters.is_empty() {
+            tokio::task::yield_now().await;
+        }
+        waiters.cancel_all();
+
+        let Err(err) = wait.await.unwrap() else {
+            panic!("wait was cancelled");
+        };
+        assert_eq!(err.downcast_ref::<WaitError>(), Some(&WaitError::Cancelled));
+    }
+
+    #[tokio::test]
+    async fn contexts_outside_a_runtime_cannot_wait() {
+        let Err(err) = message("/bind", "alice")
+            .wait_for(|_| true, Duration::from_secs(1))
+            .await
+        else {
+            panic!("no waiter registry");
+        };
+        assert_eq!(
+            err.downcast_ref::<WaitError>(),
+            Some(&WaitError::Unavailable)
+        );
+    }
+}
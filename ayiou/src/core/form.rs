@@ -0,0 +1,469 @@
+use std::{
+    collections::BTreeMap, fmt::Display, marker::PhantomData, str::FromStr, sync::Arc,
+    time::Duration,
+};
+
+use crate::core::{
+    context::Context,
+    conversation::{DEFAULT_PROMPT_TIMEOUT, WaitError},
+    plugin::{ConversationKey, ConversationStore},
+};
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+
+/// Raw answers collected so far, keyed by field name.
+pub type FormAnswers = BTreeMap<String, String>;
+
+/// One question of a [`Form`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormField {
+    pub name: &'static str,
+    pub prompt: &'static str,
+}
+
+impl FormField {
+    #[must_use]
+    pub const fn new(name: &'static str, prompt: &'static str) -> Self {
+        Self { name, prompt }
+    }
+}
+
+/// A typed multi-step form, usually implemented with `#[derive(Form)]`.
+pub trait Form: Sized + Send {
+    /// Questions in the order they are asked.
+    fn fields() -> &'static [FormField];
+
+    /// Check a single answer; the error is shown before the field is asked again.
+    fn validate(field: &str, input: &str) -> Result<(), String>;
+
+    /// Build the form once every field has a valid answer.
+    fn build(answers: &FormAnswers) -> Result<Self>;
+}
+
+/// Parse one answer with `FromStr`, rendering the parse error for the user.
+pub fn parse_field<T>(input: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    input.trim().parse::<T>().map_err(|err| err.to_string())
+}
+
+/// Parse the stored answer of `field`, see [`Form::build`].
+pub fn parse_answer<T>(answers: &FormAnswers, field: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let input = answers
+        .get(field)
+        .ok_or_else(|| anyhow!("form field `{field}` has no answer"))?;
+    parse_field(input).map_err(|err| anyhow!("invalid answer for `{field}`: {err}"))
+}
+
+/// How a form dialog ended.
+#[derive(Debug)]
+pub enum FormOutcome<F> {
+    Completed(F),
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct FormState {
+    step: usize,
+    answers: FormAnswers,
+}
+
+enum Answer {
+    Accepted,
+    Rejected(String),
+    Cancelled,
+}
+
+/// Drives a [`Form`] across messages of one user in one conversation.
+///
+/// Progress is saved to the [`ConversationStore`] after every answer, so a
+/// persistent store lets the dialog continue after a restart through
+/// [`FormRunner::resume`].
+pub struct FormRunner<F> {
+    plugin_id: String,
+    store: Option<Arc<dyn ConversationStore>>,
+    cancel_keywords: Vec<String>,
+    step_timeout: Duration,
+    state_ttl: Option<Duration>,
+    cancel_message: String,
+    timeout_message: String,
+    form: PhantomData<fn() -> F>,
+}
+
+impl<F: Form> FormRunner<F> {
+    /// `plugin_id` scopes the saved progress; use a distinct id per form.
+    pub fn new(plugin_id: impl Into<String>) -> Self {
+        Self {
+            plugin_id: plugin_id.into(),
+            store: None,
+            cancel_keywords: vec!["cancel".to_string(), "取消".to_string()],
+            step_timeout: DEFAULT_PROMPT_TIMEOUT,
+            state_ttl: None,
+            cancel_message: "Cancelled.".to_string(),
+            timeout_message: "No answer received, form closed.".to_string(),
+            form: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn with_store(mut self, store: Arc<dyn ConversationStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    #[must_use]
+    pub fn with_cancel_keywords<I, S>(mut self, keywords: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.cancel_keywords = keywords.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// How long each question waits for an answer.
+    #[must_use]
+    pub const fn with_step_timeout(mut self, timeout: Duration) -> Self {
+        self.step_timeout = timeout;
+        self
+    }
+
+    /// Expiry of saved progress in the store.
+    #[must_use]
+    pub const fn with_state_ttl(mut self, ttl: Duration) -> Self {
+        self.state_ttl = Some(ttl);
+        self
+    }
+
+    #[must_use]
+    pub fn with_cancel_message(mut self, message: impl Into<String>) -> Self {
+        self.cancel_message = message.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_timeout_message(mut self, message: impl Into<String>) -> Self {
+        self.timeout_message = message.into();
+        self
+    }
+
+    /// Whether the sender of `ctx` has saved progress for this form.
+    pub async fn has_pending(&self, ctx: &Context) -> Result<bool> {
+        Ok(self.load(&self.key(ctx)).await?.is_some())
+    }
+
+    /// Run the dialog, continuing saved progress by asking its current field again.
+    pub async fn run(&self, ctx: &Context) -> Result<FormOutcome<F>> {
+        let key = self.key(ctx);
+        let state = self.load(&key).await?.unwrap_or_default();
+        self.save(&key, &state).await?;
+        self.drive(ctx, key, state, None).await
+    }
+
+    /// Continue saved progress, taking the text of `ctx` as the pending answer.
+    ///
+    /// Returns `None` when the sender has no form in progress.
+    pub async fn resume(&self, ctx: &Context) -> Result<Option<FormOutcome<F>>> {
+        let key = self.key(ctx);
+        let Some(mut state) = self.load(&key).await? else {
+            return Ok(None);
+        };
+
+        let rejected = match self.accept(&mut state, &ctx.text()) {
+            Answer::Accepted => {
+                self.save(&key, &state).await?;
+                None
+            }
+            Answer::Rejected(err) => Some(err),
+            Answer::Cancelled => return self.cancel(ctx, &key).await.map(Some),
+        };
+        self.drive(ctx, key, state, rejected).await.map(Some)
+    }
+
+    async fn drive(
+        &self,
+        ctx: &Context,
+        key: ConversationKey,
+        mut state: FormState,
+        mut rejected: Option<String>,
+    ) -> Result<FormOutcome<F>> {
+        loop {
+            let Some(field) = F::fields().get(state.step) else {
+                self.clear(&key).await?;
+                return F::build(&state.answers).map(FormOutcome::Completed);
+            };
+
+            let question = match rejected.take() {
+                Some(err) => format!("{err}\n{}", field.prompt),
+                None => field.prompt.to_string(),
+            };
+            let reply = match ctx.prompt_with_timeout(question, self.step_timeout).await {
+                Ok(reply) => reply,
+                Err(err) if matches!(err.downcast_ref(), Some(WaitError::TimedOut(_))) => {
+                    self.clear(&key).await?;
+                    ctx.reply_text(self.timeout_message.as_str()).await?;
+                    return Ok(FormOutcome::TimedOut);
+                }
+                Err(err) => return Err(err),
+            };
+
+            match self.accept(&mut state, &reply.text()) {
+                Answer::Accepted => self.save(&key, &state).await?,
+                Answer::Rejected(err) => rejected = Some(err),
+                Answer::Cancelled => return self.cancel(ctx, &key).await,
+            }
+        }
+    }
+
+    fn accept(&self, state: &mut FormState, input: &str) -> Answer {
+        let input = input.trim();
+        if self
+            .cancel_keywords
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(input))
+        {
+            return Answer::Cancelled;
+        }
+        let Some(field) = F::fields().get(state.step) else {
+            return Answer::Accepted;
+        };
+        if let Err(err) = F::validate(field.name, input) {
+            return Answer::Rejected(err);
+        }
+
+        state
+            .answers
+            .insert(field.name.to_string(), input.to_string());
+        state.step += 1;
+        Answer::Accepted
+    }
+
+    async fn cancel(&self, ctx: &Context, key: &ConversationKey) -> Result<FormOutcome<F>> {
+        self.clear(key).await?;
+        ctx.reply_text(self.cancel_message.as_str()).await?;
+        Ok(FormOutcome::Cancelled)
+    }
+
+    fn key(&self, ctx: &Context) -> ConversationKey {
+        ConversationKey::from_context(self.plugin_id.as_str(), ctx)
+    }
+
+    async fn load(&self, key: &ConversationKey) -> Result<Option<FormState>> {
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        store
+            .get(key)
+            .await?
+            .map(serde_json::from_value)
+            .transpose()
+            .map_err(Into::into)
+    }
+
+    async fn save(&self, key: &ConversationKey, state: &FormState) -> Result<()> {
+        if let Some(store) = &self.store {
+            store
+                .put(key.clone(), serde_json::to_value(state)?, self.state_ttl)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn clear(&self, key: &ConversationKey) -> Result<()> {
+        if let Some(store) = &self.store {
+            store.remove(key).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use anyhow::Result;
+    use async_trait::async_trait;
+    use tokio::sync::mpsc;
+
+    use super::{Form, FormAnswers, FormField, FormOutcome, FormRunner, parse_answer, parse_field};
+    use crate::core::{
+        context::Context,
+        conversation::ConversationWaiters,
+        model::{
+            BotId, ChannelRef, EventEnvelope, MessageEvent, OutboundMessage, OutboundReceipt,
+            PlatformId, UserRef,
+        },
+        plugin::{MemoryConversationStore, OutboundSender},
+    };
+
+    #[derive(Debug)]
+    struct Signup {
+        name: String,
+        age: u8,
+    }
+
+    impl Form for Signup {
+        fn fields() -> &'static [FormField] {
+            const FIELDS: &[FormField] = &[
+                FormField::new("name", "Your name?"),
+                FormField::new("age", "Your age?"),
+            ];
+            FIELDS
+        }
+
+        fn validate(field: &str, input: &str) -> Result<(), String> {
+            match field {
+                "age" => parse_field::<u8>(input).and_then(|age| {
+                    if age >= 18 {
+                        Ok(())
+                    } else {
+                        Err("must be an adult".to_string())
+                    }
+                }),
+                _ => parse_field::<String>(input).map(drop),
+            }
+        }
+
+        fn build(answers: &FormAnswers) -> Result<Self> {
+            Ok(Self {
+                name: parse_answer(answers, "name")?,
+                age: parse_answer(answers, "age")?,
+            })
+        }
+    }
+
+    struct Recorder(mpsc::UnboundedSender<String>);
+
+    #[async_trait]
+    impl OutboundSender for Recorder {
+        async fn send(&self, message: OutboundMessage) -> Result<OutboundReceipt> {
+            self.0.send(message.plain_text()).unwrap();
+            Ok(OutboundReceipt::default())
+        }
+    }
+
+    fn message(text: &str, sender: &Arc<dyn OutboundSender>) -> Context {
+        let platform = PlatformId::new("test");
+        let message = MessageEvent::new(
+            UserRef::new(platform.clone(), "alice"),
+            ChannelRef::group(platform.clone(), "g1"),
+            text,
+        );
+        Context::new(
+            EventEnvelope::new(BotId::new("bot"), platform).with_message(message),
+            Some(sender.clone()),
+            (),
+        )
+    }
+
+    fn harness() -> (
+        Arc<dyn OutboundSender>,
+        mpsc::UnboundedReceiver<String>,
+        ConversationWaiters,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (Arc::new(Recorder(tx)), rx, ConversationWaiters::new())
+    }
+
+    async fn answer(
+        waiters: &ConversationWaiters,
+        outgoing: &mut mpsc::UnboundedReceiver<String>,
+        sender: &Arc<dyn OutboundSender>,
+        expected_question: &str,
+        text: &str,
+    ) {
+        assert_eq!(outgoing.recv().await.unwrap(), expected_question);
+        assert!(waiters.deliver(message(text, sender)).is_none());
+    }
+
+    #[tokio::test]
+    async fn form_reasks_invalid_answers_until_complete() {
+        let (sender, mut outgoing, waiters) = harness();
+        let ctx = message("/signup", &sender).with_waiters(waiters.clone());
+        let run = tokio::spawn(async move { FormRunner::<Signup>::new("signup").run(&ctx).await });
+
+        answer(&waiters, &mut outgoing, &sender, "Your name?", "Alice").await;
+        answer(&waiters, &mut outgoing, &sender, "Your age?", "abc").await;
+        let retry = "invalid digit found in string\nYour age?";
+        answer(&waiters, &mut outgoing, &sender, retry, "12").await;
+        answer(
+            &waiters,
+            &mut outgoing,
+            &sender,
+            "must be an adult\nYour age?",
+            "30",
+        )
+        .await;
+
+        let FormOutcome::Completed(signup) = run.await.unwrap().unwrap() else {
+            panic!("form completes");
+        };
+        assert_eq!(signup.name, "Alice");
+        assert_eq!(signup.age, 30);
+    }
+
+    #[tokio::test]
+    async fn cancel_keyword_and_timeout_end_the_form() {
+        let (sender, mut outgoing, waiters) = harness();
+        let store = Arc::new(MemoryConversationStore::default());
+        let ctx = message("/signup", &sender).with_waiters(waiters.clone());
+        let runner = FormRunner::<Signup>::new("signup").with_store(store.clone());
+        let run = tokio::spawn(async move { runner.run(&ctx).await });
+
+        answer(&waiters, &mut outgoing, &sender, "Your name?", " CANCEL ").await;
+        assert!(matches!(
+            run.await.unwrap().unwrap(),
+            FormOutcome::Cancelled
+        ));
+        assert_eq!(outgoing.recv().await.unwrap(), "Cancelled.");
+
+        let runner = FormRunner::<Signup>::new("signup")
+            .with_store(store)
+            .with_step_timeout(Duration::from_millis(10));
+        let ctx = message("/signup", &sender).with_waiters(waiters.clone());
+        assert!(!runner.has_pending(&ctx).await.unwrap());
+        assert!(matches!(
+            runner.run(&ctx).await.unwrap(),
+            FormOutcome::TimedOut
+        ));
+        assert!(!runner.has_pending(&ctx).await.unwrap());
+        assert_eq!(outgoing.recv().await.unwrap(), "Your name?");
+        assert_eq!(
+            outgoing.recv().await.unwrap(),
+            "No answer received, form closed."
+        );
+    }
+
+    #[tokio::test]
+    async fn saved_progress_resumes_after_restart() {
+        let (sender, mut outgoing, waiters) = harness();
+        let store = Arc::new(MemoryConversationStore::default());
+        let runner = FormRunner::<Signup>::new("signup").with_store(store.clone());
+        let ctx = message("/signup", &sender).with_waiters(waiters.clone());
+        let run = tokio::spawn(async move { runner.run(&ctx).await });
+
+        answer(&waiters, &mut outgoing, &sender, "Your name?", "Alice").await;
+        assert_eq!(outgoing.recv().await.unwrap(), "Your age?");
+        waiters.cancel_all();
+        assert!(run.await.unwrap().is_err());
+
+        let waiters = ConversationWaiters::new();
+        let runner = FormRunner::<Signup>::new("signup").with_store(store);
+        let ctx = message("42", &sender).with_waiters(waiters);
+        assert!(runner.has_pending(&ctx).await.unwrap());
+
+        let Some(FormOutcome::Completed(signup)) = runner.resume(&ctx).await.unwrap() else {
+            panic!("saved form resumes");
+        };
+        assert_eq!(signup.name, "Alice");
+        assert_eq!(signup.age, 42);
+        assert!(runner.resume(&ctx).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,362 @@
+use crate::core::plugin::CommandMeta;
+
+/// Built-in help command, enabled with `Bot::with_help`.
+///
+/// Plugin commands with the same name take precedence over it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpOptions {
+    command: String,
+    aliases: Vec<String>,
+}
+
+impl Default for HelpOptions {
+    fn default() -> Self {
+        Self {
+            command: "help".to_string(),
+            aliases: Vec::new(),
+        }
+    }
+}
+
+impl HelpOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_command(mut self, command: impl Into<String>) -> Self {
+        self.command = command.into();
+        self
+    }
+
+    #[must_use]
+    pub fn with_aliases(mut self, aliases: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.aliases = aliases.into_iter().map(Into::into).collect();
+        self
+    }
+
+    #[must_use]
+    pub fn command(&self) -> &str {
+        &self.command
+    }
+
+    pub(crate) fn matches(&self, command: &str) -> bool {
+        self.names().any(|name| name == command)
+    }
+
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.command.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+/// "Did you mean" replies for unknown prefixed commands, enabled with
+/// `Bot::with_suggestions`.
+///
+/// Only commands the sender may run are suggested.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SuggestionOptions {
+    max_distance: usize,
+    limit: usize,
+}
+
+impl Default for SuggestionOptions {
+    fn default() -> Self {
+        Self {
+            max_distance: 2,
+            limit: 3,
+        }
+    }
+}
+
+impl SuggestionOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Most edits a suggestion may be away from the typed name, default 2.
+    #[must_use]
+    pub const fn with_max_distance(mut self, max_distance: usize) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Most suggestions in one reply, default 3.
+    #[must_use]
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = limit.max(1);
+        self
+    }
+
+    #[must_use]
+    pub const fn max_distance(&self) -> usize {
+        self.max_distance
+    }
+
+    #[must_use]
+    pub const fn limit(&self) -> usize {
+        self.limit
+    }
+}
+
+/// Names close enough to `query` to be a typo of it, closest first.
+///
+/// A name qualifies within `max_distance` edits and when at most half of
+/// `query` has to change.
+#[must_use]
+pub fn rank_suggestions<'a>(
+    query: &str,
+    names: impl IntoIterator<Item = &'a str>,
+    max_distance: usize,
+) -> Vec<&'a str> {
+    let query_len = query.chars().count();
+    let mut ranked: Vec<(usize, &str)> = names
+        .into_iter()
+        .filter(|name| *name != query)
+        .map(|name| (edit_distance(query, name), name))
+        .filter(|(distance, _)| *distance <= max_distance && distance * 2 <= query_len)
+        .collect();
+    ranked.sort_unstable();
+    ranked.dedup();
+    ranked.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Reply for an unknown command, listing the suggested labels.
+#[must_use]
+pub fn render_suggestions(label: &str, suggestions: &[String]) -> String {
+    format!(
+        "Unknown command {label}. Did you mean {}?",
+        suggestions.join(", ")
+    )
+}
+
+/// Levenshtein distance counted in chars.
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    let mut current = vec![0; right.len() + 1];
+    for (i, left_ch) in left.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, right_ch) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_ch != *right_ch);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[right.len()]
+}
+
+/// Commands of one enabled plugin that the asking user may run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpSection {
+    pub plugin_id: String,
+    pub description: String,
+    pub commands: Vec<HelpEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub prefix: String,
+    pub meta: CommandMeta,
+}
+
+impl HelpEntry {
+    /// Command as typed by users, e.g. `/weather` or `/admin ban`.
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!("{}{}", self.prefix, self.meta.full_name())
+    }
+
+    fn names(&self) -> impl Iterator<Item = String> + '_ {
+        std::iter::once(&self.meta.name)
+            .chain(&self.meta.aliases)
+            .map(|name| match &self.meta.group {
+                Some(group) => format!("{group} {name}"),
+                None => name.clone(),
+            })
+    }
+
+    fn matches(&self, query: &str) -> bool {
+        let query = query.strip_prefix(self.prefix.as_str()).unwrap_or(query);
+        let query = query.split_whitespace().collect::<Vec<_>>().join(" ");
+        self.names().any(|name| name == query)
+    }
+}
+
+/// Render the command list, one block per plugin.
+#[must_use]
+pub fn render_catalog(sections: &[HelpSection], help_label: &str) -> String {
+    if sections.is_empty() {
+        return "No commands available.".to_string();
+    }
+
+    let mut lines = vec!["Available commands:".to_string()];
+    for section in sections {
+        if section.description.is_empty() {
+            lines.push(section.plugin_id.clone());
+        } else {
+            lines.push(format!("{} - {}", section.plugin_id, section.description));
+        }
+        for entry in &section.commands {
+            if entry.meta.summary.is_empty() {
+                lines.push(format!("  {}", entry.label()));
+            } else {
+                lines.push(format!("  {}  {}", entry.label(), entry.meta.summary));
+            }
+        }
+    }
+    lines.push(format!("Send {help_label} <command> for details."));
+    lines.join("\n")
+}
+
+/// Render the detailed help of a single command.
+#[must_use]
+pub fn render_command(entry: &HelpEntry) -> String {
+    let mut lines = vec![entry.label()];
+    if !entry.meta.aliases.is_empty() {
+        let aliases: Vec<String> = entry
+            .names()
+            .skip(1)
+            .map(|alias| format!("{}{alias}", entry.prefix))
+            .collect();
+        lines[0].push_str(&format!(" (aliases: {})", aliases.join(", ")));
+    }
+    if !entry.meta.summary.is_empty() {
+        lines.push(entry.meta.summary.clone());
+    }
+    if let Some(help) = entry.meta.help_text() {
+        lines.push(help);
+    }
+    lines.join("\n")
+}
+
+/// Render the subcommands of `group`, `None` when no visible command uses it.
+#[must_use]
+pub fn render_group(sections: &[HelpSection], group: &str) -> Option<String> {
+    let group = group.trim();
+    let mut lines = Vec::new();
+    for entry in sections.iter().flat_map(|section| section.commands.iter()) {
+        let query = group.strip_prefix(entry.prefix.as_str()).unwrap_or(group);
+        if entry.meta.group.as_deref() != Some(query) {
+            continue;
+        }
+        if lines.is_empty() {
+            lines.push(format!("{}{query} subcommands:", entry.prefix));
+        }
+        if entry.meta.summary.is_empty() {
+            lines.push(format!("  {}", entry.label()));
+        } else {
+            lines.push(format!("  {}  {}", entry.label(), entry.meta.summary));
+        }
+    }
+    (!lines.is_empty()).then(|| lines.join("\n"))
+}
+
+/// Find a command by name, alias or prefixed label.
+#[must_use]
+pub fn find_command<'a>(sections: &'a [HelpSection], query: &str) -> Option<&'a HelpEntry> {
+    sections
+        .iter()
+        .flat_map(|section| section.commands.iter())
+        .find(|entry| entry.matches(query))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        HelpEntry, HelpSection, edit_distance, find_command, rank_suggestions, render_catalog,
+        render_command, render_group,
+    };
+    use crate::core::plugin::CommandMeta;
+
+    fn sections() -> Vec<HelpSection> {
+        vec![HelpSection {
+            plugin_id: "weather".to_string(),
+            description: "Weather commands".to_string(),
+            commands: vec![
+                HelpEntry {
+                    prefix: "/".to_string(),
+                    meta: CommandMeta::new("weather")
+                        .aliases(["wx"])
+                        .summary("Show a forecast")
+                        .usage("/weather <city>")
+                        .examples(["/weather Taipei"]),
+                },
+                HelpEntry {
+                    prefix: "/".to_string(),
+                    meta: CommandMeta::new("cities"),
+                },
+                HelpEntry {
+                    prefix: "/".to_string(),
+                    meta: CommandMeta::new("add")
+                        .group("alert")
+                        .aliases(["new"])
+                        .summary("Subscribe to alerts"),
+                },
+            ],
+        }]
+    }
+
+    #[test]
+    fn catalog_lists_commands_grouped_by_plugin() {
+        assert_eq!(
+            render_catalog(&sections(), "/help"),
+            "Available commands:\n\
+             weather - Weather commands\n  \
+             /weather  Show a forecast\n  \
+             /cities\n  \
+             /alert add  Subscribe to alerts\n\
+             Send /help <command> for details."
+        );
+        assert_eq!(render_catalog(&[], "/help"), "No commands available.");
+    }
+
+    #[test]
+    fn command_help_is_found_by_alias_or_label() {
+        let sections = sections();
+        let entry = find_command(&sections, "/wx").unwrap();
+        assert_eq!(find_command(&sections, "weather"), Some(entry));
+        assert!(find_command(&sections, "rain").is_none());
+        assert!(find_command(&sections, "add").is_none());
+
+        assert_eq!(
+            render_command(entry),
+            "/weather (aliases: /wx)\n\
+             Show a forecast\n\
+             Usage: /weather <city>\n\
+             Examples:\n  /weather Taipei"
+        );
+    }
+
+    #[test]
+    fn group_commands_are_listed_and_found_by_full_name() {
+        let sections = sections();
+        let entry = find_command(&sections, "/alert  new").unwrap();
+        assert_eq!(
+            render_command(entry),
+            "/alert add (aliases: /alert new)\nSubscribe to alerts"
+        );
+        assert_eq!(
+            render_group(&sections, "/alert").as_deref(),
+            Some("/alert subcommands:\n  /alert add  Subscribe to alerts")
+        );
+        assert!(render_group(&sections, "weather").is_none());
+    }
+
+    #[test]
+    fn suggestions_are_ranked_by_edit_distance() {
+        assert_eq!(edit_distance("weather", "wether"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "ban"), 3);
+
+        let names = ["weather", "whether", "wealth", "ban", "help"];
+        assert_eq!(
+            rank_suggestions("wether", names, 2),
+            vec!["weather", "whether"]
+        );
+        assert_eq!(rank_suggestions("hlep", names, 2), vec!["help"]);
+        assert!(rank_suggestions("ab", names, 2).is_empty());
+        assert!(rank_suggestions("ban", names, 2).is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use async_trait::async_trait;
+
+use crate::core::{
+    context::Context,
+    plugin::{DispatchReport, HandleOutcome, HandlerDecl},
+};
+
+/// Whether an event or handler goes on past a middleware hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum MiddlewareFlow {
+    #[default]
+    Continue,
+    Stop,
+}
+
+/// Matched handler a middleware hook runs around.
+#[derive(Clone, Copy, Debug)]
+pub struct HandlerInfo<'a> {
+    pub instance_id: &'a str,
+    pub handler_index: usize,
+    pub decl: &'a HandlerDecl,
+    /// Command name the handler was matched on, if any.
+    pub command: Option<&'a str>,
+}
+
+/// Cross-cutting hooks the engine runs around every event and every matched
+/// handler, such as blocklists, logging or metrics.
+///
+/// `before_*` hooks run in priority order and `after_*` hooks in reverse.
+#[async_trait]
+pub trait Middleware: Send + Sync + 'static {
+    /// Runs before routing and may enrich `ctx`; `Stop` drops the event as blocked.
+    async fn before_event(&self, ctx: &mut Context) -> Result<MiddlewareFlow> {
+        let _ = ctx;
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    /// Runs after dispatch, also for events a middleware stopped.
+    async fn after_event(&self, ctx: &Context, report: &DispatchReport) {
+        let _ = (ctx, report);
+    }
+
+    /// Runs before a matched handler; `Stop` skips only this handler.
+    async fn before_handler(
+        &self,
+        ctx: &Context,
+        handler: HandlerInfo<'_>,
+    ) -> Result<MiddlewareFlow> {
+        let _ = (ctx, handler);
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    /// Runs after a handler ran, with its outcome or error.
+    async fn after_handler(
+        &self,
+        ctx: &Context,
+        handler: HandlerInfo<'_>,
+        result: &Result<HandleOutcome>,
+    ) {
+        let _ = (ctx, handler, result);
+    }
+}
+
+/// Middleware sorted by priority, lower first; equal priorities keep
+/// registration order.
+#[derive(Clone, Default)]
+pub(crate) struct MiddlewareStack {
+    layers: Vec<(i32, Arc<dyn Middleware>)>,
+}
+
+impl MiddlewareStack {
+    pub(crate) fn insert(&mut self, priority: i32, middleware: Arc<dyn Middleware>) {
+        let index = self
+            .layers
+            .partition_point(|(existing, _)| *existing <= priority);
+        self.layers.insert(index, (priority, middleware));
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+
+    fn iter(&self) -> impl DoubleEndedIterator<Item = &dyn Middleware> {
+        self.layers
+            .iter()
+            .map(|(_, middleware)| middleware.as_ref())
+    }
+
+    pub(crate) async fn before_event(&self, ctx: &mut Context) -> Result<MiddlewareFlow> {
+        for middleware in self.iter() {
+            if middleware.before_event(ctx).await? == MiddlewareFlow::Stop {
+                return Ok(MiddlewareFlow::Stop);
+            }
+        }
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    pub(crate) async fn after_event(&self, ctx: &Context, report: &DispatchReport) {
+        for middleware in self.iter().rev() {
+            middleware.after_event(ctx, report).await;
+        }
+    }
+
+    pub(crate) async fn before_handler(
+        &self,
+        ctx: &Context,
+        handler: HandlerInfo<'_>,
+    ) -> Result<MiddlewareFlow> {
+        for middleware in self.iter() {
+            if middleware.before_handler(ctx, handler).await? == MiddlewareFlow::Stop {
+                return Ok(MiddlewareFlow::Stop);
+            }
+        }
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    pub(crate) async fn after_handler(
+        &self,
+        ctx: &Context,
+        handler: HandlerInfo<'_>,
+        result: &Result<HandleOutcome>,
+    ) {
+        for middleware in self.iter().rev() {
+            middleware.after_handler(ctx, handler, result).await;
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::SystemTime;
 
 use serde::{Deserialize, Serialize};
@@ -164,6 +165,7 @@ pub struct CommandInvocation {
     command: String,
     args: String,
     prefix: Option<String>,
+    group: Option<String>,
 }
 
 impl CommandInvocation {
@@ -176,9 +178,17 @@ impl CommandInvocation {
             command: command.into(),
             args: args.into(),
             prefix: prefix.map(Into::into),
+            group: None,
         }
     }
 
+    /// Mark `command` as a subcommand of `group`, as in `/admin ban 123`.
+    #[must_use]
+    pub fn with_group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
     #[must_use]
     pub fn command(&self) -> &str {
         &self.command
@@ -193,6 +203,86 @@ impl CommandInvocation {
     pub fn prefix(&self) -> Option<&str> {
         self.prefix.as_deref()
     }
+
+    #[must_use]
+    pub fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+}
+
+/// What a regex handler's pattern matched in the message text.
+///
+/// Group `0` is the whole match; groups that did not take part are `None`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegexMatch {
+    pattern: String,
+    groups: Vec<Option<String>>,
+    names: HashMap<String, usize>,
+}
+
+impl RegexMatch {
+    pub fn new(
+        pattern: impl Into<String>,
+        groups: impl IntoIterator<Item = Option<impl Into<String>>>,
+    ) -> Self {
+        Self {
+            pattern: pattern.into(),
+            groups: groups
+                .into_iter()
+                .map(|group| group.map(Into::into))
+                .collect(),
+            names: HashMap::new(),
+        }
+    }
+
+    /// Name group `index`, as `(?<name>...)` does.
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>, index: usize) -> Self {
+        self.names.insert(name.into(), index);
+        self
+    }
+
+    /// Run `regex` against `text`, `None` when it does not match.
+    pub(crate) fn capture(regex: &regex::Regex, text: &str) -> Option<Self> {
+        let captures = regex.captures(text)?;
+        let groups = captures
+            .iter()
+            .map(|group| group.map(|group| group.as_str()));
+        let mut matched = Self::new(regex.as_str(), groups);
+        for (index, name) in regex.capture_names().enumerate() {
+            if let Some(name) = name {
+                matched = matched.with_name(name, index);
+            }
+        }
+        Some(matched)
+    }
+
+    /// The pattern, as declared by the handler.
+    #[must_use]
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    #[must_use]
+    pub fn matched(&self) -> &str {
+        self.get(0).unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.groups.get(index)?.as_deref()
+    }
+
+    #[must_use]
+    pub fn name(&self, name: &str) -> Option<&str> {
+        self.get(*self.names.get(name)?)
+    }
+
+    /// Every group in pattern order, starting with the whole match.
+    #[must_use]
+    pub fn groups(&self) -> &[Option<String>] {
+        &self.groups
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -211,6 +301,39 @@ pub enum MessageSegment {
         url: Option<String>,
         mime: Option<String>,
     },
+    /// Quote of an earlier message in the same channel.
+    Reply {
+        message_id: String,
+    },
+    /// Platform-native emoji or sticker, identified by the platform's own id.
+    Emoji {
+        id: String,
+    },
+    Audio {
+        url: String,
+    },
+    Video {
+        url: String,
+    },
+    /// Forwarded message bundle.
+    ///
+    /// Inbound bundles usually only carry the platform `id`; outbound bundles
+    /// are built from `nodes`.
+    Forward {
+        id: Option<String>,
+        nodes: Vec<ForwardNode>,
+    },
+    Link {
+        url: String,
+        title: String,
+        content: Option<String>,
+        image: Option<String>,
+    },
+    /// Structured card such as a JSON or XML mini-app message.
+    Card {
+        format: String,
+        data: String,
+    },
     Unknown {
         kind: String,
         data: serde_json::Value,
@@ -221,6 +344,48 @@ impl MessageSegment {
     pub fn text(text: impl Into<String>) -> Self {
         Self::Text { text: text.into() }
     }
+
+    pub fn reply(message_id: impl Into<String>) -> Self {
+        Self::Reply {
+            message_id: message_id.into(),
+        }
+    }
+
+    #[must_use]
+    pub const fn forward(nodes: Vec<ForwardNode>) -> Self {
+        Self::Forward { id: None, nodes }
+    }
+
+    pub fn link(url: impl Into<String>, title: impl Into<String>) -> Self {
+        Self::Link {
+            url: url.into(),
+            title: title.into(),
+            content: None,
+            image: None,
+        }
+    }
+}
+
+/// One message inside a [`MessageSegment::Forward`] bundle.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForwardNode {
+    pub user_id: String,
+    pub nickname: String,
+    pub content: Vec<MessageSegment>,
+}
+
+impl ForwardNode {
+    pub fn new(
+        user_id: impl Into<String>,
+        nickname: impl Into<String>,
+        content: Vec<MessageSegment>,
+    ) -> Self {
+        Self {
+            user_id: user_id.into(),
+            nickname: nickname.into(),
+            content,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -258,12 +423,171 @@ impl MessageEvent {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PlatformEventKind {
+    Notice,
+    Request,
+    Meta,
+}
+
+/// Non-message event forwarded by an adapter.
+///
+/// `name` is the adapter-native event type (for example `group_increase` on
+/// `OneBot` v11) and `data` keeps the raw payload for adapter-specific fields.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformEvent {
+    pub kind: PlatformEventKind,
+    pub name: String,
+    pub sub_type: Option<String>,
+    pub user: Option<UserRef>,
+    pub channel: Option<ChannelRef>,
+    pub data: serde_json::Value,
+}
+
+impl PlatformEvent {
+    pub fn new(kind: PlatformEventKind, name: impl Into<String>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            sub_type: None,
+            user: None,
+            channel: None,
+            data: serde_json::Value::Null,
+        }
+    }
+
+    #[must_use]
+    pub fn with_sub_type(mut self, sub_type: impl Into<String>) -> Self {
+        self.sub_type = Some(sub_type.into());
+        self
+    }
+
+    #[must_use]
+    pub fn with_user(mut self, user: UserRef) -> Self {
+        self.user = Some(user);
+        self
+    }
+
+    #[must_use]
+    pub fn with_channel(mut self, channel: ChannelRef) -> Self {
+        self.channel = Some(channel);
+        self
+    }
+
+    #[must_use]
+    pub fn with_data(mut self, data: serde_json::Value) -> Self {
+        self.data = data;
+        self
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KernelEventKind {
+    MemberJoined,
+    MemberLeft,
+    MessageRecalled,
+    FriendRequest,
+    JoinRequest,
+    ReactionAdded,
+    BotConnected,
+    BotDisconnected,
+}
+
+/// Platform-neutral non-message event.
+///
+/// Adapters map their native notices, requests and lifecycle events onto these
+/// variants so plugins can react to them without downcasting adapter contexts.
+/// `flag` on requests is the adapter token needed to approve or reject them.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum KernelEvent {
+    MemberJoined {
+        channel: ChannelRef,
+        user: UserRef,
+        operator: Option<UserRef>,
+    },
+    MemberLeft {
+        channel: ChannelRef,
+        user: UserRef,
+        operator: Option<UserRef>,
+        kicked: bool,
+    },
+    MessageRecalled {
+        channel: ChannelRef,
+        message_id: String,
+        author: Option<UserRef>,
+        operator: Option<UserRef>,
+    },
+    FriendRequest {
+        user: UserRef,
+        comment: String,
+        flag: String,
+    },
+    JoinRequest {
+        channel: ChannelRef,
+        user: UserRef,
+        comment: String,
+        flag: String,
+    },
+    ReactionAdded {
+        channel: ChannelRef,
+        message_id: String,
+        user: UserRef,
+        reaction: String,
+    },
+    BotConnected,
+    BotDisconnected,
+}
+
+impl KernelEvent {
+    #[must_use]
+    pub const fn kind(&self) -> KernelEventKind {
+        match self {
+            Self::MemberJoined { .. } => KernelEventKind::MemberJoined,
+            Self::MemberLeft { .. } => KernelEventKind::MemberLeft,
+            Self::MessageRecalled { .. } => KernelEventKind::MessageRecalled,
+            Self::FriendRequest { .. } => KernelEventKind::FriendRequest,
+            Self::JoinRequest { .. } => KernelEventKind::JoinRequest,
+            Self::ReactionAdded { .. } => KernelEventKind::ReactionAdded,
+            Self::BotConnected => KernelEventKind::BotConnected,
+            Self::BotDisconnected => KernelEventKind::BotDisconnected,
+        }
+    }
+
+    /// User the event is about: the member, requester, reactor or recalled author.
+    #[must_use]
+    pub const fn user(&self) -> Option<&UserRef> {
+        match self {
+            Self::MemberJoined { user, .. }
+            | Self::MemberLeft { user, .. }
+            | Self::FriendRequest { user, .. }
+            | Self::JoinRequest { user, .. }
+            | Self::ReactionAdded { user, .. } => Some(user),
+            Self::MessageRecalled { author, .. } => author.as_ref(),
+            Self::BotConnected | Self::BotDisconnected => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn channel(&self) -> Option<&ChannelRef> {
+        match self {
+            Self::MemberJoined { channel, .. }
+            | Self::MemberLeft { channel, .. }
+            | Self::MessageRecalled { channel, .. }
+            | Self::JoinRequest { channel, .. }
+            | Self::ReactionAdded { channel, .. } => Some(channel),
+            Self::FriendRequest { .. } | Self::BotConnected | Self::BotDisconnected => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EventEnvelope {
     pub bot_id: BotId,
     pub platform: PlatformId,
     pub received_at: SystemTime,
     pub message: Option<MessageEvent>,
+    pub platform_event: Option<PlatformEvent>,
+    pub kernel_event: Option<KernelEvent>,
 }
 
 impl EventEnvelope {
@@ -273,6 +597,8 @@ impl EventEnvelope {
             platform: platform.into(),
             received_at: SystemTime::now(),
             message: None,
+            platform_event: None,
+            kernel_event: None,
         }
     }
 
@@ -282,10 +608,32 @@ impl EventEnvelope {
         self
     }
 
+    #[must_use]
+    pub fn with_platform_event(mut self, event: PlatformEvent) -> Self {
+        self.platform_event = Some(event);
+        self
+    }
+
+    #[must_use]
+    pub fn with_kernel_event(mut self, event: KernelEvent) -> Self {
+        self.kernel_event = Some(event);
+        self
+    }
+
     #[must_use]
     pub const fn message(&self) -> Option<&MessageEvent> {
         self.message.as_ref()
     }
+
+    #[must_use]
+    pub const fn platform_event(&self) -> Option<&PlatformEvent> {
+        self.platform_event.as_ref()
+    }
+
+    #[must_use]
+    pub const fn kernel_event(&self) -> Option<&KernelEvent> {
+        self.kernel_event.as_ref()
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -315,6 +663,14 @@ pub struct OutboundReceipt {
     pub message_id: Option<String>,
 }
 
+impl OutboundReceipt {
+    #[must_use]
+    pub fn with_message_id(mut self, message_id: impl Into<String>) -> Self {
+        self.message_id = Some(message_id.into());
+        self
+    }
+}
+
 #[must_use]
 pub fn plain_text_from_segments(segments: &[MessageSegment]) -> String {
     let mut text = String::new();
@@ -331,6 +687,13 @@ pub fn plain_text_from_segments(segments: &[MessageSegment]) -> String {
             }
             MessageSegment::Image { .. }
             | MessageSegment::Attachment { .. }
+            | MessageSegment::Reply { .. }
+            | MessageSegment::Emoji { .. }
+            | MessageSegment::Audio { .. }
+            | MessageSegment::Video { .. }
+            | MessageSegment::Forward { .. }
+            | MessageSegment::Link { .. }
+            | MessageSegment::Card { .. }
             | MessageSegment::Unknown { .. } => {}
         }
     }
@@ -1,17 +1,34 @@
 use std::{
-    collections::HashMap,
-    sync::Arc,
+    collections::{HashMap, VecDeque},
+    future::Future,
+    panic::AssertUnwindSafe,
+    pin::Pin,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::Poll,
     time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow};
+use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use dashmap::DashMap;
 
 use crate::core::{
-    command::parse_command_line_with_prefixes,
+    command::{ArgsParseError, ParseErrorPolicy, parse_command_line_with_prefixes},
     context::Context,
-    model::{BotId, ChannelRef, CommandInvocation, OutboundMessage, OutboundReceipt, PlatformId},
+    help::{
+        HelpEntry, HelpOptions, HelpSection, SuggestionOptions, find_command, rank_suggestions,
+        render_catalog, render_command, render_group, render_suggestions,
+    },
+    middleware::{HandlerInfo, Middleware, MiddlewareFlow, MiddlewareStack},
+    model::{
+        BotId, ChannelKind, ChannelRef, CommandInvocation, EventEnvelope, KernelEventKind,
+        MessageSegment, OutboundMessage, OutboundReceipt, PlatformEventKind, PlatformId,
+        RegexMatch,
+    },
     service::{RuntimeService, ServiceDescriptor, ServiceKey, ServiceRegistry, ServiceSnapshot},
 };
 
@@ -102,6 +119,7 @@ pub enum Capability {
     Reaction,
     GroupModeration,
     RichSegments,
+    MessageEdit,
     Custom(String),
 }
 
@@ -120,14 +138,55 @@ pub enum CapabilityNegotiation {
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum HandlerEventKind {
+    /// Every event except `Meta` ones, such as heartbeats, which only `Meta`
+    /// handlers see.
     Any,
     #[default]
     Message,
+    Notice,
+    Request,
+    Meta,
+    /// Normalized kernel event, regardless of the adapter that produced it.
+    Kernel(KernelEventKind),
+}
+
+impl HandlerEventKind {
+    /// Classify an inbound envelope; envelopes without any payload count as messages.
+    ///
+    /// Kernel-only envelopes, such as adapter lifecycle events, are classified by
+    /// their kernel event kind, so only `Kernel` and `Any` handlers see them.
+    #[must_use]
+    pub const fn of(envelope: &EventEnvelope) -> Self {
+        if envelope.message().is_some() {
+            return Self::Message;
+        }
+        match (envelope.platform_event(), envelope.kernel_event()) {
+            (Some(event), _) => match event.kind {
+                PlatformEventKind::Notice => Self::Notice,
+                PlatformEventKind::Request => Self::Request,
+                PlatformEventKind::Meta => Self::Meta,
+            },
+            (None, Some(event)) => Self::Kernel(event.kind()),
+            (None, None) => Self::Message,
+        }
+    }
+
+    #[must_use]
+    pub fn accepts(self, envelope: &EventEnvelope) -> bool {
+        match self {
+            Self::Any => !matches!(Self::of(envelope), Self::Meta),
+            Self::Kernel(kind) => envelope
+                .kernel_event()
+                .is_some_and(|event| event.kind() == kind),
+            Self::Message | Self::Notice | Self::Request | Self::Meta => self == Self::of(envelope),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CommandMeta {
     pub name: String,
+    pub group: Option<String>,
     pub aliases: Vec<String>,
     pub summary: String,
     pub usage: String,
@@ -138,6 +197,7 @@ impl CommandMeta {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
             name: name.into(),
+            group: None,
             aliases: Vec::new(),
             summary: String::new(),
             usage: String::new(),
@@ -145,12 +205,27 @@ impl CommandMeta {
         }
     }
 
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
     #[must_use]
     pub fn aliases(mut self, aliases: impl IntoIterator<Item = impl Into<String>>) -> Self {
         self.aliases = aliases.into_iter().map(Into::into).collect();
         self
     }
 
+    /// Name as typed after the prefix, e.g. `admin ban` for a group command.
+    #[must_use]
+    pub fn full_name(&self) -> String {
+        match &self.group {
+            Some(group) => format!("{group} {}", self.name),
+            None => self.name.clone(),
+        }
+    }
+
     #[must_use]
     pub fn summary(mut self, summary: impl Into<String>) -> Self {
         self.summary = summary.into();
@@ -168,20 +243,49 @@ impl CommandMeta {
         self.examples = examples.into_iter().map(Into::into).collect();
         self
     }
+
+    /// Usage and examples as shown to users, `None` when neither is set.
+    #[must_use]
+    pub fn help_text(&self) -> Option<String> {
+        let mut lines = Vec::new();
+        if !self.usage.is_empty() {
+            lines.push(format!("Usage: {}", self.usage));
+        }
+        if !self.examples.is_empty() {
+            lines.push("Examples:".to_string());
+            lines.extend(self.examples.iter().map(|example| format!("  {example}")));
+        }
+        (!lines.is_empty()).then(|| lines.join("\n"))
+    }
+
+    fn matches_name(&self, command: &str) -> bool {
+        self.name == command || self.aliases.iter().any(|alias| alias == command)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HandlerDecl {
     pub event_kind: HandlerEventKind,
+    /// Command group `commands` belong to; they are matched on the token after it.
+    pub group: Option<String>,
     pub commands: Vec<String>,
     pub command_prefixes: Vec<String>,
     pub regex_patterns: Vec<String>,
+    pub text_matchers: Vec<TextMatcher>,
+    pub filter: EventFilter,
     pub permissions: Vec<Permission>,
     pub command_meta: Vec<CommandMeta>,
     pub priority: i32,
     pub block: bool,
     pub wildcard: bool,
     pub concurrency: ConcurrencyPolicy,
+    pub parse_error: ParseErrorPolicy,
+    /// Every limit must have room for a call to run.
+    pub rate_limits: Vec<RateLimit>,
+    /// Cancel the handler after this long; `None` uses the engine default.
+    pub timeout: Option<Duration>,
+    /// What dispatch does after this handler fails; `None` uses the engine default.
+    pub on_error: Option<DispatchErrorPolicy>,
 }
 
 impl HandlerDecl {
@@ -189,51 +293,149 @@ impl HandlerDecl {
     pub const fn wildcard_message() -> Self {
         Self {
             event_kind: HandlerEventKind::Message,
+            group: None,
             commands: Vec::new(),
             command_prefixes: Vec::new(),
             regex_patterns: Vec::new(),
+            text_matchers: Vec::new(),
+            filter: EventFilter::new(),
             permissions: Vec::new(),
             command_meta: Vec::new(),
             priority: 0,
             block: false,
             wildcard: true,
             concurrency: ConcurrencyPolicy::Parallel,
+            parse_error: ParseErrorPolicy::Reply,
+            rate_limits: Vec::new(),
+            timeout: None,
+            on_error: None,
         }
     }
 
+    /// Handler invoked for every event of `event_kind`, such as notices or requests.
+    #[must_use]
+    pub const fn event(event_kind: HandlerEventKind) -> Self {
+        let mut decl = Self::wildcard_message();
+        decl.event_kind = event_kind;
+        decl
+    }
+
     pub fn message_commands(
         commands: impl IntoIterator<Item = impl Into<String>>,
         command_prefixes: impl IntoIterator<Item = impl Into<String>>,
     ) -> Self {
         Self {
             event_kind: HandlerEventKind::Message,
+            group: None,
             commands: commands.into_iter().map(Into::into).collect(),
             command_prefixes: command_prefixes.into_iter().map(Into::into).collect(),
             regex_patterns: Vec::new(),
+            text_matchers: Vec::new(),
+            filter: EventFilter::new(),
             permissions: Vec::new(),
             command_meta: Vec::new(),
             priority: 0,
             block: false,
             wildcard: false,
             concurrency: ConcurrencyPolicy::Parallel,
+            parse_error: ParseErrorPolicy::Reply,
+            rate_limits: Vec::new(),
+            timeout: None,
+            on_error: None,
         }
     }
 
     pub fn message_regex(patterns: impl IntoIterator<Item = impl Into<String>>) -> Self {
         Self {
             event_kind: HandlerEventKind::Message,
+            group: None,
             commands: Vec::new(),
             command_prefixes: Vec::new(),
             regex_patterns: patterns.into_iter().map(Into::into).collect(),
+            text_matchers: Vec::new(),
+            filter: EventFilter::new(),
             permissions: Vec::new(),
             command_meta: Vec::new(),
             priority: 0,
             block: false,
             wildcard: false,
             concurrency: ConcurrencyPolicy::Parallel,
+            parse_error: ParseErrorPolicy::Reply,
+            rate_limits: Vec::new(),
+            timeout: None,
+            on_error: None,
         }
     }
 
+    /// Handler for messages matching any of `matchers`.
+    pub fn message_text(matchers: impl IntoIterator<Item = TextMatcher>) -> Self {
+        let mut decl = Self::wildcard_message();
+        decl.wildcard = false;
+        decl.text_matchers = matchers.into_iter().collect();
+        decl
+    }
+
+    /// Handler for messages containing any of `keywords`.
+    pub fn message_keywords(keywords: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::message_text(keywords.into_iter().map(TextMatcher::keyword))
+    }
+
+    pub fn message_starts_with(prefixes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::message_text(prefixes.into_iter().map(TextMatcher::starts_with))
+    }
+
+    pub fn message_ends_with(suffixes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::message_text(suffixes.into_iter().map(TextMatcher::ends_with))
+    }
+
+    pub fn message_full_match(texts: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self::message_text(texts.into_iter().map(TextMatcher::full_match))
+    }
+
+    /// Compare every text matcher of this handler regardless of case.
+    #[must_use]
+    pub fn ignore_case(mut self) -> Self {
+        for matcher in &mut self.text_matchers {
+            matcher.ignore_case = true;
+        }
+        self
+    }
+
+    /// Route `commands` as subcommands of `group`, e.g. `/admin ban`.
+    #[must_use]
+    pub fn group(mut self, group: impl Into<String>) -> Self {
+        self.group = Some(group.into());
+        self
+    }
+
+    /// Only run in channels of these kinds, e.g. group chats only.
+    #[must_use]
+    pub fn channel_kinds(mut self, kinds: impl IntoIterator<Item = ChannelKind>) -> Self {
+        self.filter.channel_kinds.extend(kinds);
+        self
+    }
+
+    #[must_use]
+    pub fn platforms(mut self, platforms: impl IntoIterator<Item = impl Into<PlatformId>>) -> Self {
+        self.filter
+            .platforms
+            .extend(platforms.into_iter().map(Into::into));
+        self
+    }
+
+    #[must_use]
+    pub fn bots(mut self, bots: impl IntoIterator<Item = impl Into<BotId>>) -> Self {
+        self.filter.bots.extend(bots.into_iter().map(Into::into));
+        self
+    }
+
+    /// Only run for messages addressed to the bot, see [`Context::is_to_me`].
+    #[must_use]
+    pub const fn to_me(mut self) -> Self {
+        self.filter.to_me = true;
+        self
+    }
+
     #[must_use]
     pub fn require_permission(mut self, permission: Permission) -> Self {
         self.permissions.push(permission);
@@ -272,6 +474,126 @@ impl HandlerDecl {
         self.concurrency = concurrency;
         self
     }
+
+    #[must_use]
+    pub const fn parse_error(mut self, policy: ParseErrorPolicy) -> Self {
+        self.parse_error = policy;
+        self
+    }
+
+    #[must_use]
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limits.push(limit);
+        self
+    }
+
+    #[must_use]
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    #[must_use]
+    pub const fn on_error(mut self, policy: DispatchErrorPolicy) -> Self {
+        self.on_error = Some(policy);
+        self
+    }
+}
+
+/// Conditions on where an event happened, checked before anything else about
+/// a handler.
+///
+/// Empty lists accept every value. A handler whose filter rejects the event
+/// is skipped as if it had not matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    pub channel_kinds: Vec<ChannelKind>,
+    pub platforms: Vec<PlatformId>,
+    pub bots: Vec<BotId>,
+    /// Only messages addressed to the bot, see [`Context::is_to_me`].
+    pub to_me: bool,
+}
+
+impl EventFilter {
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            channel_kinds: Vec::new(),
+            platforms: Vec::new(),
+            bots: Vec::new(),
+            to_me: false,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self == &Self::new()
+    }
+
+    #[must_use]
+    pub fn accepts(&self, ctx: &Context) -> bool {
+        let event = ctx.event();
+        (self.channel_kinds.is_empty()
+            || ctx
+                .channel()
+                .is_some_and(|channel| self.channel_kinds.contains(&channel.kind())))
+            && (self.platforms.is_empty() || self.platforms.contains(&event.platform))
+            && (self.bots.is_empty() || self.bots.contains(&event.bot_id))
+            && (!self.to_me || ctx.is_to_me())
+    }
+}
+
+/// How a [`TextMatcher`] compares its text with the message text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextMatchKind {
+    /// The text appears anywhere in the message.
+    Keyword,
+    StartsWith,
+    EndsWith,
+    /// The text is the whole message.
+    FullMatch,
+}
+
+/// A fixed-string trigger, cheaper than a regex for plain words.
+///
+/// `ignore_case` compares both sides lowercased.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextMatcher {
+    pub kind: TextMatchKind,
+    pub text: String,
+    pub ignore_case: bool,
+}
+
+impl TextMatcher {
+    pub fn new(kind: TextMatchKind, text: impl Into<String>) -> Self {
+        Self {
+            kind,
+            text: text.into(),
+            ignore_case: false,
+        }
+    }
+
+    pub fn keyword(text: impl Into<String>) -> Self {
+        Self::new(TextMatchKind::Keyword, text)
+    }
+
+    pub fn starts_with(text: impl Into<String>) -> Self {
+        Self::new(TextMatchKind::StartsWith, text)
+    }
+
+    pub fn ends_with(text: impl Into<String>) -> Self {
+        Self::new(TextMatchKind::EndsWith, text)
+    }
+
+    pub fn full_match(text: impl Into<String>) -> Self {
+        Self::new(TextMatchKind::FullMatch, text)
+    }
+
+    #[must_use]
+    pub const fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -303,6 +625,24 @@ impl Permission {
     }
 }
 
+/// Invocations that share one concurrency slot.
+///
+/// `Plugin` slots are shared by all of a plugin's handlers. Under the `*Serial`
+/// policies the other scopes span every handler; under `Drop`, `Queue` and
+/// `Latest` they belong to one handler. Invocations outside a group have no
+/// `Group` slot and are never limited by it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConcurrencyScope {
+    Plugin,
+    User,
+    Group,
+    Conversation,
+}
+
+/// How invocations sharing a [`ConcurrencyScope`] slot wait for each other.
+///
+/// A rejected invocation is skipped; later handlers still run unless its
+/// route blocks.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
 pub enum ConcurrencyPolicy {
     #[default]
@@ -311,7 +651,87 @@ pub enum ConcurrencyPolicy {
     UserSerial,
     GroupSerial,
     ConversationSerial,
-    Drop,
+    /// Reject the invocation while another one holds the slot.
+    Drop(ConcurrencyScope),
+    /// Wait for the slot, rejecting the invocation once `max_waiting` already wait.
+    Queue(ConcurrencyScope, usize),
+    /// Wait for the slot; a newer invocation rejects the one already waiting.
+    Latest(ConcurrencyScope),
+}
+
+impl ConcurrencyPolicy {
+    #[must_use]
+    pub const fn scope(self) -> Option<ConcurrencyScope> {
+        match self {
+            Self::Parallel => None,
+            Self::PluginSerial => Some(ConcurrencyScope::Plugin),
+            Self::UserSerial => Some(ConcurrencyScope::User),
+            Self::GroupSerial => Some(ConcurrencyScope::Group),
+            Self::ConversationSerial => Some(ConcurrencyScope::Conversation),
+            Self::Drop(scope) | Self::Queue(scope, _) | Self::Latest(scope) => Some(scope),
+        }
+    }
+}
+
+/// Who a [`RateLimit`] counts calls for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RateLimitScope {
+    /// Each user has their own budget.
+    #[default]
+    User,
+    /// Each group shares one budget; calls outside groups are not limited.
+    Group,
+    /// All callers share one budget.
+    Global,
+}
+
+/// What dispatch does with a call over its [`RateLimit`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RateLimitAction {
+    /// Drop the call.
+    #[default]
+    Silent,
+    /// Tell the caller how long to wait.
+    Reply,
+}
+
+/// At most `calls` invocations of a handler per sliding `window`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    pub calls: u32,
+    pub window: Duration,
+    pub scope: RateLimitScope,
+    pub action: RateLimitAction,
+}
+
+impl RateLimit {
+    #[must_use]
+    pub const fn new(calls: u32, window: Duration) -> Self {
+        Self {
+            calls,
+            window,
+            scope: RateLimitScope::User,
+            action: RateLimitAction::Silent,
+        }
+    }
+
+    /// One call per `window`.
+    #[must_use]
+    pub const fn cooldown(window: Duration) -> Self {
+        Self::new(1, window)
+    }
+
+    #[must_use]
+    pub const fn scope(mut self, scope: RateLimitScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    #[must_use]
+    pub const fn action(mut self, action: RateLimitAction) -> Self {
+        self.action = action;
+        self
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -384,9 +804,179 @@ impl HandleOutcome {
     }
 }
 
+/// What dispatch does with the remaining handlers after one fails.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum DispatchErrorPolicy {
+    /// Skip the remaining handlers.
+    #[default]
+    Stop,
+    /// Keep running the remaining handlers.
+    Continue,
+}
+
+/// Why a matched handler did not run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// A middleware's `before_handler` hook stopped it.
+    Middleware,
+    CircuitOpen,
+    PermissionDenied,
+    RateLimited,
+    ConcurrencyRejected,
+}
+
+#[derive(Debug)]
+pub enum HandlerStatus {
+    Completed(HandleOutcome),
+    /// Arguments failed to parse and were answered per [`ParseErrorPolicy`].
+    InvalidArgs,
+    /// Returned an error, timed out or panicked.
+    Failed(anyhow::Error),
+    Skipped(SkipReason),
+}
+
+#[derive(Debug)]
+pub struct HandlerReport {
+    pub instance_id: String,
+    pub handler_index: usize,
+    pub status: HandlerStatus,
+}
+
+/// Outcome of every handler one event reached, in dispatch order.
+#[derive(Debug, Default)]
+pub struct DispatchReport {
+    pub handlers: Vec<HandlerReport>,
+    /// The event was consumed: a handler blocked it, or a built-in reply or
+    /// rate limit answered it.
+    pub blocked: bool,
+}
+
+impl DispatchReport {
+    fn push(&mut self, registered: &RegisteredPlugin, handler_index: usize, status: HandlerStatus) {
+        self.handlers.push(HandlerReport {
+            instance_id: registered.instance_id().to_string(),
+            handler_index,
+            status,
+        });
+    }
+
+    fn blocked(mut self) -> Self {
+        self.blocked = true;
+        self
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &anyhow::Error> {
+        self.handlers
+            .iter()
+            .filter_map(|handler| match &handler.status {
+                HandlerStatus::Failed(err) => Some(err),
+                _ => None,
+            })
+    }
+
+    /// `blocked`, or the first handler error.
+    pub fn into_result(self) -> Result<bool> {
+        let blocked = self.blocked;
+        match self
+            .handlers
+            .into_iter()
+            .find_map(|handler| match handler.status {
+                HandlerStatus::Failed(err) => Some(err),
+                _ => None,
+            }) {
+            Some(err) => Err(err),
+            None => Ok(blocked),
+        }
+    }
+}
+
 #[async_trait]
 pub trait OutboundSender: Send + Sync {
     async fn send(&self, message: OutboundMessage) -> Result<OutboundReceipt>;
+
+    /// Follow-up actions on existing messages, if the adapter supports any.
+    fn message_actions(&self) -> Option<&dyn MessageActions> {
+        None
+    }
+}
+
+/// Adapter-neutral actions on messages that were already sent or received.
+///
+/// Every method defaults to an [`UnsupportedCapability`] error, so adapters only
+/// override what their platform supports and advertise the matching [`Capability`].
+#[async_trait]
+pub trait MessageActions: Send + Sync {
+    async fn delete_message(&self, channel: &ChannelRef, message_id: &str) -> Result<()> {
+        let _ = (channel, message_id);
+        Err(UnsupportedCapability::new(Capability::MessageDelete, "delete_message").into())
+    }
+
+    async fn react(&self, channel: &ChannelRef, message_id: &str, reaction: &str) -> Result<()> {
+        let _ = (channel, message_id, reaction);
+        Err(UnsupportedCapability::new(Capability::Reaction, "react").into())
+    }
+
+    async fn edit_message(
+        &self,
+        channel: &ChannelRef,
+        message_id: &str,
+        segments: Vec<MessageSegment>,
+    ) -> Result<OutboundReceipt> {
+        let _ = (channel, message_id, segments);
+        Err(UnsupportedCapability::new(Capability::MessageEdit, "edit_message").into())
+    }
+}
+
+/// Error returned when an action needs a capability the adapter does not provide.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedCapability {
+    capability: Capability,
+    action: &'static str,
+}
+
+impl UnsupportedCapability {
+    #[must_use]
+    pub const fn new(capability: Capability, action: &'static str) -> Self {
+        Self { capability, action }
+    }
+
+    #[must_use]
+    pub const fn capability(&self) -> &Capability {
+        &self.capability
+    }
+
+    #[must_use]
+    pub const fn action(&self) -> &'static str {
+        self.action
+    }
+}
+
+impl std::fmt::Display for UnsupportedCapability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "adapter does not support `{}` (missing capability {:?})",
+            self.action, self.capability
+        )
+    }
+}
+
+impl std::error::Error for UnsupportedCapability {}
+
+/// The sender's message actions, failing up front when the adapter's advertised
+/// `capabilities`, if known, lack `capability`.
+pub(crate) fn message_actions_of<'a>(
+    sender: Option<&'a Arc<dyn OutboundSender>>,
+    capabilities: Option<&[Capability]>,
+    capability: Capability,
+    action: &'static str,
+) -> Result<&'a dyn MessageActions> {
+    if capabilities.is_some_and(|capabilities| !capabilities.contains(&capability)) {
+        return Err(UnsupportedCapability::new(capability, action).into());
+    }
+    sender
+        .and_then(|sender| sender.message_actions())
+        .ok_or_else(|| UnsupportedCapability::new(capability, action).into())
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -569,6 +1159,49 @@ impl RuntimePluginServices {
         self.send(OutboundMessage::text(target, text)).await
     }
 
+    pub async fn delete_message(&self, channel: &ChannelRef, message_id: &str) -> Result<()> {
+        message_actions_of(
+            self.sender.as_ref(),
+            self.advertised_capabilities(),
+            Capability::MessageDelete,
+            "delete_message",
+        )?
+        .delete_message(channel, message_id)
+        .await
+    }
+
+    pub async fn react(
+        &self,
+        channel: &ChannelRef,
+        message_id: &str,
+        reaction: &str,
+    ) -> Result<()> {
+        message_actions_of(
+            self.sender.as_ref(),
+            self.advertised_capabilities(),
+            Capability::Reaction,
+            "react",
+        )?
+        .react(channel, message_id, reaction)
+        .await
+    }
+
+    pub async fn edit_message(
+        &self,
+        channel: &ChannelRef,
+        message_id: &str,
+        segments: Vec<MessageSegment>,
+    ) -> Result<OutboundReceipt> {
+        message_actions_of(
+            self.sender.as_ref(),
+            self.advertised_capabilities(),
+            Capability::MessageEdit,
+            "edit_message",
+        )?
+        .edit_message(channel, message_id, segments)
+        .await
+    }
+
     #[must_use]
     pub fn with_instance_id(mut self, instance_id: impl Into<String>) -> Self {
         self.instance_id = Some(instance_id.into());
@@ -669,6 +1302,11 @@ impl RuntimePluginServices {
         self.service_registry.snapshots()
     }
 
+    /// The capabilities set through `with_capabilities`, `None` when none were.
+    fn advertised_capabilities(&self) -> Option<&[Capability]> {
+        (!self.capabilities.is_empty()).then_some(self.capabilities.as_slice())
+    }
+
     #[must_use]
     pub fn provided_capabilities(&self) -> Vec<Capability> {
         let mut capabilities = self.capabilities.clone();
@@ -695,6 +1333,8 @@ pub enum PluginLifecycleState {
     Stopping,
     Stopped,
     Failed,
+    /// Tripped by the [`CircuitBreaker`]; still started but skipped by dispatch.
+    AutoDisabled,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
@@ -714,6 +1354,12 @@ pub struct PluginInstanceState {
     pub config_lifecycle_state: ConfigLifecycleState,
     pub lifecycle_state: PluginLifecycleState,
     pub last_error: Option<String>,
+    /// Handler invocations cancelled for running past their timeout.
+    pub timeouts: u64,
+    /// Failed handler invocations since the last successful one.
+    pub consecutive_failures: u32,
+    /// When an auto-disabled plugin is let back in; `None` waits for `enable_plugin`.
+    pub auto_reenable_at: Option<Instant>,
 }
 
 impl Default for PluginInstanceState {
@@ -725,6 +1371,9 @@ impl Default for PluginInstanceState {
             config_lifecycle_state: ConfigLifecycleState::Applied,
             lifecycle_state: PluginLifecycleState::Registered,
             last_error: None,
+            timeouts: 0,
+            consecutive_failures: 0,
+            auto_reenable_at: None,
         }
     }
 }
@@ -812,8 +1461,71 @@ impl PluginRuntimeState {
         });
     }
 
+    /// Record a failed handler invocation, returning the failures in a row.
+    ///
+    /// An auto-disabled plugin keeps its state.
+    pub fn record_failure(&self, plugin: &str, error: impl Into<String>) -> u32 {
+        let mut failures = 0;
+        self.update(plugin, |state| {
+            if state.lifecycle_state != PluginLifecycleState::AutoDisabled {
+                state.lifecycle_state = PluginLifecycleState::Failed;
+            }
+            state.last_error = Some(error.into());
+            state.consecutive_failures += 1;
+            failures = state.consecutive_failures;
+        });
+        failures
+    }
+
+    /// Count a timed-out invocation and record it as a failure.
+    pub fn record_timeout(&self, plugin: &str, error: impl Into<String>) -> u32 {
+        self.update(plugin, |state| state.timeouts += 1);
+        self.record_failure(plugin, error)
+    }
+
     pub fn clear_error(&self, plugin: &str) {
-        self.update(plugin, |state| state.last_error = None);
+        self.update(plugin, |state| {
+            state.last_error = None;
+            state.consecutive_failures = 0;
+        });
+    }
+
+    /// Auto-disable a plugin until `reenable_at`, or until it is enabled again.
+    pub fn trip(&self, plugin: &str, reenable_at: Option<Instant>) {
+        self.update(plugin, |state| {
+            state.lifecycle_state = PluginLifecycleState::AutoDisabled;
+            state.auto_reenable_at = reenable_at;
+        });
+    }
+
+    /// Put an auto-disabled plugin back to `Running` and clear its failure count.
+    pub fn reset_circuit(&self, plugin: &str) {
+        self.update(plugin, |state| {
+            if state.lifecycle_state == PluginLifecycleState::AutoDisabled {
+                state.lifecycle_state = PluginLifecycleState::Running;
+            }
+            state.consecutive_failures = 0;
+            state.auto_reenable_at = None;
+        });
+    }
+
+    /// Whether dispatch may reach the plugin, re-enabling it once its
+    /// auto-disable cooldown has passed.
+    #[must_use]
+    pub fn circuit_allows(&self, plugin: &str) -> bool {
+        let reenable_at = match self.instances.get(plugin) {
+            Some(state) if state.lifecycle_state == PluginLifecycleState::AutoDisabled => {
+                state.auto_reenable_at
+            }
+            _ => return true,
+        };
+        match reenable_at {
+            Some(at) if at <= Instant::now() => {
+                self.reset_circuit(plugin);
+                true
+            }
+            _ => false,
+        }
     }
 }
 
@@ -905,25 +1617,39 @@ pub trait RuntimePlugin: Send + Sync + 'static {
         self.handle(ctx).await
     }
 
+    /// Handle a message matched by one of the handler's regex patterns.
+    async fn handle_regex(&self, ctx: &Context, regex_match: RegexMatch) -> Result<HandleOutcome> {
+        let _ = regex_match;
+        self.handle(ctx).await
+    }
+
     fn health(&self) -> PluginHealth {
         PluginHealth::healthy()
     }
 }
 
+/// A plugin instance and what it declared when it was registered.
+///
+/// Clones share the plugin; dispatch holds a read lock on it while a handler
+/// runs and lifecycle hooks take the write lock.
+#[derive(Clone)]
 pub struct RegisteredPlugin {
     instance_id: String,
+    kind: String,
+    manifest: RuntimePluginManifest,
     handlers: Arc<[HandlerDecl]>,
-    plugin: Box<dyn RuntimePlugin>,
+    plugin: Arc<tokio::sync::RwLock<Box<dyn RuntimePlugin>>>,
     reload: PluginReloadDescriptor,
 }
 
 impl RegisteredPlugin {
     pub fn new(instance_id: impl Into<String>, plugin: Box<dyn RuntimePlugin>) -> Self {
-        let handlers = plugin.declared_handlers().into();
         Self {
             instance_id: instance_id.into(),
-            handlers,
-            plugin,
+            kind: plugin.kind().to_string(),
+            manifest: plugin.manifest(),
+            handlers: plugin.declared_handlers().into(),
+            plugin: Arc::new(tokio::sync::RwLock::new(plugin)),
             reload: PluginReloadDescriptor::NotReloadable,
         }
     }
@@ -951,17 +1677,41 @@ impl RegisteredPlugin {
     }
 
     #[must_use]
-    pub fn plugin(&self) -> &dyn RuntimePlugin {
-        self.plugin.as_ref()
+    pub fn kind(&self) -> &str {
+        &self.kind
     }
 
     #[must_use]
-    pub fn handlers(&self) -> &[HandlerDecl] {
-        &self.handlers
+    pub const fn manifest(&self) -> &RuntimePluginManifest {
+        &self.manifest
     }
 
-    pub fn plugin_mut(&mut self) -> &mut dyn RuntimePlugin {
-        self.plugin.as_mut()
+    /// Shared access to the plugin, waiting for a running lifecycle hook.
+    pub async fn plugin(&self) -> tokio::sync::RwLockReadGuard<'_, Box<dyn RuntimePlugin>> {
+        self.plugin.read().await
+    }
+
+    #[must_use]
+    pub fn handlers(&self) -> &[HandlerDecl] {
+        &self.handlers
+    }
+
+    /// The plugin's health, or a pending note while a lifecycle change holds
+    /// or waits for exclusive access.
+    #[must_use]
+    pub fn health(&self) -> PluginHealth {
+        self.plugin.try_read().map_or_else(
+            |_| PluginHealth {
+                healthy: true,
+                detail: Some("lifecycle change pending".to_string()),
+            },
+            |plugin| plugin.health(),
+        )
+    }
+
+    /// Exclusive access to the plugin, waiting for its in-flight handlers.
+    pub async fn plugin_mut(&self) -> tokio::sync::RwLockWriteGuard<'_, Box<dyn RuntimePlugin>> {
+        self.plugin.write().await
     }
 }
 
@@ -1025,49 +1775,611 @@ pub struct RuntimePluginEngine {
     services: RuntimePluginServices,
     runtime_state: PluginRuntimeState,
     command_prefixes: Arc<[String]>,
-    routing_table: RoutingTable,
-    concurrency_locks: DashMap<ConcurrencyKey, Arc<tokio::sync::Semaphore>>,
+    routing_table: Arc<RoutingTable>,
     plugins: Vec<RegisteredPlugin>,
     enabled_plugins: HashMap<String, usize>,
     enabled_order: Vec<usize>,
     disabled_plugins: HashMap<String, usize>,
     service_registered: Vec<bool>,
+    options: DispatchOptions,
+    dispatcher: Dispatcher,
+    /// `stop()` calls waiting for the plugin's in-flight handlers.
+    pending_stops: HashMap<String, tokio::task::JoinHandle<()>>,
+}
+
+/// Engine settings that dispatch reads.
+#[derive(Clone, Default)]
+struct DispatchOptions {
+    help: Option<HelpOptions>,
+    suggestions: Option<SuggestionOptions>,
+    handler_timeout: Option<Duration>,
+    circuit_breaker: Option<CircuitBreaker>,
+    error_policy: DispatchErrorPolicy,
+    middleware: MiddlewareStack,
+}
+
+/// Dispatches events against the routes and plugins a [`RuntimePluginEngine`]
+/// last published.
+///
+/// Cheap to clone and never waits for the engine: lifecycle changes swap in a
+/// new snapshot, and in-flight events finish on the one they started with.
+#[derive(Clone)]
+pub struct Dispatcher {
+    snapshot: Arc<ArcSwap<DispatchSnapshot>>,
+    concurrency_locks: Arc<DashMap<ConcurrencyKey, Arc<ConcurrencySlot>>>,
+    rate_limiter: Arc<std::sync::Mutex<RateLimiter>>,
+}
+
+/// Immutable view of the engine that one dispatch works from.
+#[derive(Default)]
+struct DispatchSnapshot {
+    services: RuntimePluginServices,
+    runtime_state: PluginRuntimeState,
+    command_prefixes: Arc<[String]>,
+    routing_table: Arc<RoutingTable>,
+    plugins: Vec<RegisteredPlugin>,
+    enabled_order: Vec<usize>,
+    options: DispatchOptions,
+}
+
+/// Auto-disables plugins whose handlers keep failing, see
+/// [`PluginLifecycleState::AutoDisabled`].
+///
+/// Errors, timeouts and panics count as failures; a successful invocation
+/// resets the count.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    reenable_after: Option<Duration>,
+}
+
+impl CircuitBreaker {
+    /// Trip after `failure_threshold` failures in a row.
+    #[must_use]
+    pub fn new(failure_threshold: u32) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            reenable_after: None,
+        }
+    }
+
+    /// Let a tripped plugin back in after `cooldown` instead of waiting for
+    /// `enable_plugin`.
+    #[must_use]
+    pub const fn with_reenable_after(mut self, cooldown: Duration) -> Self {
+        self.reenable_after = Some(cooldown);
+        self
+    }
+
+    #[must_use]
+    pub const fn failure_threshold(&self) -> u32 {
+        self.failure_threshold
+    }
+
+    #[must_use]
+    pub const fn reenable_after(&self) -> Option<Duration> {
+        self.reenable_after
+    }
+}
+
+/// A handler panicked; the panic was caught and the event dropped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandlerPanic {
+    pub instance_id: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for HandlerPanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "plugin `{}` handler panicked: {}",
+            self.instance_id, self.message
+        )
+    }
+}
+
+impl std::error::Error for HandlerPanic {}
+
+/// Polls the inner future, turning a panic into `Err` with its payload.
+pub(crate) struct CatchUnwind<F>(pub(crate) F);
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = std::thread::Result<F::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+        match std::panic::catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| (*message).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+/// A handler ran past its timeout and was cancelled.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HandlerTimeout {
+    pub instance_id: String,
+    pub timeout: Duration,
+}
+
+impl std::fmt::Display for HandlerTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "plugin `{}` handler timed out after {:?}",
+            self.instance_id, self.timeout
+        )
+    }
+}
+
+impl std::error::Error for HandlerTimeout {}
+
+fn with_command_help(
+    err: ArgsParseError,
+    handler: &HandlerDecl,
+    command: Option<&str>,
+) -> ArgsParseError {
+    if err.help().is_some() {
+        return err;
+    }
+    let meta = command
+        .and_then(|command| {
+            handler
+                .command_meta
+                .iter()
+                .find(|meta| meta.matches_name(command))
+        })
+        .or_else(|| handler.command_meta.first());
+    match meta.and_then(CommandMeta::help_text) {
+        Some(help) => err.with_help(help),
+        None => err,
+    }
+}
+
+impl RoutingTable {
+    /// Turn `/admin ban 123` into subcommand `ban` of group `admin` when declared.
+    fn resolve_group(&self, invocation: CommandInvocation) -> CommandInvocation {
+        let Some(subcommands) = self.groups.get(invocation.command()) else {
+            return invocation;
+        };
+        let args = invocation.args();
+        let (subcommand, rest) = args
+            .split_once(char::is_whitespace)
+            .map_or((args, ""), |(subcommand, rest)| {
+                (subcommand, rest.trim_start())
+            });
+        if !subcommands.contains_key(subcommand) {
+            return invocation;
+        }
+        CommandInvocation::new(subcommand, rest, invocation.prefix())
+            .with_group(invocation.command())
+    }
+
+    fn command_routes(&self, invocation: &CommandInvocation) -> Option<&Vec<Route>> {
+        match invocation.group() {
+            Some(group) => self.groups.get(group)?.get(invocation.command()),
+            None => self.commands.get(invocation.command()),
+        }
+    }
 }
 
 #[derive(Default)]
 struct RoutingTable {
     command_prefixes: Arc<[String]>,
     commands: HashMap<String, Vec<Route>>,
+    groups: HashMap<String, HashMap<String, Vec<Route>>>,
     wildcard: Vec<Route>,
-    regex: Vec<RegexRoute>,
+    regex: RegexRoutes,
+    text: TextRoutes,
 }
 
 #[derive(Clone)]
 struct Route {
     plugin_index: usize,
     handler_index: usize,
+    event_kind: HandlerEventKind,
+    /// `None` for handlers without filters, which is most of them.
+    filter: Option<Arc<EventFilter>>,
     priority: i32,
     block: bool,
     concurrency: ConcurrencyPolicy,
+    parse_error: ParseErrorPolicy,
     permissions: Arc<[Permission]>,
+    rate_limits: Arc<[RateLimit]>,
+    timeout: Option<Duration>,
+    on_error: Option<DispatchErrorPolicy>,
 }
 
-struct RegexRoute {
-    route: Route,
-    regex: regex::Regex,
+impl Route {
+    fn accepts(&self, ctx: &Context) -> bool {
+        self.event_kind.accepts(ctx.event())
+            && self
+                .filter
+                .as_ref()
+                .is_none_or(|filter| filter.accepts(ctx))
+    }
+}
+
+/// Regex routes compiled into one [`regex::RegexSet`], so an inbound message
+/// is scanned once however many patterns are declared.
+///
+/// The individual patterns are kept to extract captures, which only happens
+/// for a handler that is about to run.
+#[derive(Default)]
+struct RegexRoutes {
+    set: regex::RegexSet,
+    patterns: Vec<regex::Regex>,
+    routes: Vec<Route>,
+    /// Index into `routes` for every pattern of `set`.
+    pattern_routes: Vec<usize>,
+}
+
+impl RegexRoutes {
+    /// Compile `routes`, which must already be in dispatch order.
+    fn new(routes: Vec<(Route, Vec<regex::Regex>)>) -> Result<Self> {
+        let mut patterns = Vec::new();
+        let mut pattern_routes = Vec::new();
+        let mut ordered = Vec::with_capacity(routes.len());
+        for (index, (route, route_patterns)) in routes.into_iter().enumerate() {
+            pattern_routes.extend(std::iter::repeat_n(index, route_patterns.len()));
+            patterns.extend(route_patterns);
+            ordered.push(route);
+        }
+        Ok(Self {
+            set: regex::RegexSet::new(patterns.iter().map(regex::Regex::as_str))
+                .map_err(|err| anyhow!("failed to compile regex routes: {err}"))?,
+            patterns,
+            routes: ordered,
+            pattern_routes,
+        })
+    }
+
+    /// Routes with at least one pattern matching `text`, each once, in dispatch
+    /// order, along with the first of their patterns that matched.
+    fn matches<'a>(&'a self, text: &str) -> impl Iterator<Item = (&'a Route, usize)> {
+        let mut last = None;
+        self.set
+            .matches(text)
+            .into_iter()
+            .filter_map(move |pattern| {
+                let index = self.pattern_routes[pattern];
+                (last.replace(index) != Some(index)).then(|| (&self.routes[index], pattern))
+            })
+    }
+
+    fn captures(&self, pattern: usize, text: &str) -> Option<RegexMatch> {
+        RegexMatch::capture(&self.patterns[pattern], text)
+    }
+}
+
+/// Text matcher routes, every matcher compiled into one Aho-Corasick automaton
+/// per case mode, so an inbound message is scanned at most twice however many
+/// keywords are declared.
+#[derive(Default)]
+struct TextRoutes {
+    routes: Vec<Route>,
+    exact: Option<TextAutomaton>,
+    /// Lowercased `ignore_case` matchers, run against the lowercased text.
+    folded: Option<TextAutomaton>,
 }
 
+impl TextRoutes {
+    /// Compile `routes`, which must already be in dispatch order.
+    fn new(routes: Vec<(Route, Vec<TextMatcher>)>) -> Result<Self> {
+        let mut exact = Vec::new();
+        let mut folded = Vec::new();
+        let mut ordered = Vec::with_capacity(routes.len());
+        for (index, (route, matchers)) in routes.into_iter().enumerate() {
+            for matcher in matchers {
+                if matcher.ignore_case {
+                    folded.push((index, matcher.kind, matcher.text.to_lowercase()));
+                } else {
+                    exact.push((index, matcher.kind, matcher.text));
+                }
+            }
+            ordered.push(route);
+        }
+        Ok(Self {
+            routes: ordered,
+            exact: TextAutomaton::new(exact)?,
+            folded: TextAutomaton::new(folded)?,
+        })
+    }
+
+    /// Routes with at least one matcher matching `text`, each once, in dispatch order.
+    fn matches<'a>(&'a self, text: &str) -> impl Iterator<Item = &'a Route> {
+        let mut hits = Vec::new();
+        if let Some(exact) = &self.exact {
+            exact.matches(text, &mut hits);
+        }
+        if let Some(folded) = &self.folded {
+            folded.matches(&text.to_lowercase(), &mut hits);
+        }
+        hits.sort_unstable();
+        hits.dedup();
+        hits.into_iter().map(|index| &self.routes[index])
+    }
+}
+
+struct TextAutomaton {
+    automaton: aho_corasick::AhoCorasick,
+    /// Route index and kind of every pattern of `automaton`.
+    patterns: Vec<(usize, TextMatchKind)>,
+}
+
+impl TextAutomaton {
+    fn new(matchers: Vec<(usize, TextMatchKind, String)>) -> Result<Option<Self>> {
+        if matchers.is_empty() {
+            return Ok(None);
+        }
+        let automaton = aho_corasick::AhoCorasick::new(matchers.iter().map(|(_, _, text)| text))
+            .map_err(|err| anyhow!("failed to compile text matchers: {err}"))?;
+        Ok(Some(Self {
+            automaton,
+            patterns: matchers
+                .into_iter()
+                .map(|(route, kind, _)| (route, kind))
+                .collect(),
+        }))
+    }
+
+    fn matches(&self, text: &str, hits: &mut Vec<usize>) {
+        for found in self.automaton.find_overlapping_iter(text) {
+            let (route, kind) = self.patterns[found.pattern().as_usize()];
+            let matched = match kind {
+                TextMatchKind::Keyword => true,
+                TextMatchKind::StartsWith => found.start() == 0,
+                TextMatchKind::EndsWith => found.end() == text.len(),
+                TextMatchKind::FullMatch => found.start() == 0 && found.end() == text.len(),
+            };
+            if matched {
+                hits.push(route);
+            }
+        }
+    }
+}
+
+/// Slot identity; `handler` is `None` for slots shared across handlers.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum ConcurrencyKey {
     Plugin(usize),
-    User(String),
-    Group(String),
+    User {
+        handler: Option<(usize, usize)>,
+        user_id: String,
+    },
+    Group {
+        handler: Option<(usize, usize)>,
+        group_id: String,
+    },
     Conversation {
+        handler: Option<(usize, usize)>,
         user_id: String,
         group_id: Option<String>,
     },
 }
 
+impl ConcurrencyKey {
+    fn of(ctx: &Context, route: &Route) -> Option<Self> {
+        let handler = match route.concurrency {
+            ConcurrencyPolicy::Drop(_)
+            | ConcurrencyPolicy::Queue(..)
+            | ConcurrencyPolicy::Latest(_) => Some((route.plugin_index, route.handler_index)),
+            ConcurrencyPolicy::Parallel
+            | ConcurrencyPolicy::PluginSerial
+            | ConcurrencyPolicy::UserSerial
+            | ConcurrencyPolicy::GroupSerial
+            | ConcurrencyPolicy::ConversationSerial => None,
+        };
+        Some(match route.concurrency.scope()? {
+            ConcurrencyScope::Plugin => Self::Plugin(route.plugin_index),
+            ConcurrencyScope::User => Self::User {
+                handler,
+                user_id: ctx.user_id().into_owned(),
+            },
+            ConcurrencyScope::Group => Self::Group {
+                handler,
+                group_id: ctx.group_id()?.into_owned(),
+            },
+            ConcurrencyScope::Conversation => Self::Conversation {
+                handler,
+                user_id: ctx.user_id().into_owned(),
+                group_id: ctx.group_id().map(std::borrow::Cow::into_owned),
+            },
+        })
+    }
+}
+
+struct ConcurrencySlot {
+    semaphore: Arc<tokio::sync::Semaphore>,
+    waiting: AtomicUsize,
+    /// Ticket of the newest `Latest` waiter.
+    latest: tokio::sync::watch::Sender<u64>,
+}
+
+impl Default for ConcurrencySlot {
+    fn default() -> Self {
+        Self {
+            semaphore: Arc::new(tokio::sync::Semaphore::new(1)),
+            waiting: AtomicUsize::new(0),
+            latest: tokio::sync::watch::Sender::new(0),
+        }
+    }
+}
+
+/// One invocation's use of a slot; the last one out removes the slot from the map.
+struct SlotLease<'a> {
+    locks: &'a DashMap<ConcurrencyKey, Arc<ConcurrencySlot>>,
+    key: ConcurrencyKey,
+    slot: Option<Arc<ConcurrencySlot>>,
+    permit: Option<tokio::sync::OwnedSemaphorePermit>,
+}
+
+impl SlotLease<'_> {
+    fn slot(&self) -> &Arc<ConcurrencySlot> {
+        self.slot
+            .as_ref()
+            .expect("slot is held until the lease drops")
+    }
+}
+
+impl Drop for SlotLease<'_> {
+    fn drop(&mut self) {
+        self.permit.take();
+        self.slot.take();
+        self.locks
+            .remove_if(&self.key, |_, slot| Arc::strong_count(slot) == 1);
+    }
+}
+
+struct WaitingGuard<'a>(&'a AtomicUsize);
+
+impl Drop for WaitingGuard<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+enum Admission<'a> {
+    Unlimited,
+    Acquired(SlotLease<'a>),
+    Rejected,
+}
+
+/// Resolve once a newer `Latest` waiter took a ticket after `ticket`.
+///
+/// Checks the current ticket first, so a waiter that came in before this one
+/// subscribed still counts.
+async fn superseded(latest: &mut tokio::sync::watch::Receiver<u64>, ticket: u64) {
+    loop {
+        if *latest.borrow_and_update() != ticket {
+            return;
+        }
+        if latest.changed().await.is_err() {
+            return std::future::pending().await;
+        }
+    }
+}
+
+/// Checks between sweeps of rate-limit windows that have gone idle.
+const RATE_LIMIT_SWEEP_INTERVAL: u64 = 1024;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct RateLimitKey {
+    plugin_index: usize,
+    handler_index: usize,
+    limit_index: usize,
+    /// User or group id, `None` for global limits.
+    subject: Option<String>,
+}
+
+struct RateWindow {
+    window: Duration,
+    calls: VecDeque<Instant>,
+}
+
+impl RateWindow {
+    fn expire(&mut self, now: Instant) {
+        while self
+            .calls
+            .front()
+            .is_some_and(|call| now.duration_since(*call) >= self.window)
+        {
+            self.calls.pop_front();
+        }
+    }
+}
+
+#[derive(Default)]
+struct RateLimiter {
+    windows: HashMap<RateLimitKey, RateWindow>,
+    checks: u64,
+}
+
+impl RateLimiter {
+    /// Record a call against `route`'s limits when all of them have room.
+    ///
+    /// Otherwise returns the exceeded limit that frees up last, with the wait.
+    fn take(&mut self, ctx: &Context, route: &Route) -> Option<(RateLimitAction, Duration)> {
+        let now = Instant::now();
+        self.checks += 1;
+        if self.checks.is_multiple_of(RATE_LIMIT_SWEEP_INTERVAL) {
+            self.windows.retain(|_, window| {
+                window.expire(now);
+                !window.calls.is_empty()
+            });
+        }
+
+        let mut keys = Vec::with_capacity(route.rate_limits.len());
+        let mut exceeded: Option<(RateLimitAction, Duration)> = None;
+        for (limit_index, limit) in route.rate_limits.iter().enumerate() {
+            let subject = match limit.scope {
+                RateLimitScope::User => Some(ctx.user_id().into_owned()),
+                RateLimitScope::Group => match ctx.group_id() {
+                    Some(group_id) => Some(group_id.into_owned()),
+                    None => continue,
+                },
+                RateLimitScope::Global => None,
+            };
+            let key = RateLimitKey {
+                plugin_index: route.plugin_index,
+                handler_index: route.handler_index,
+                limit_index,
+                subject,
+            };
+            let window = self
+                .windows
+                .entry(key.clone())
+                .or_insert_with(|| RateWindow {
+                    window: limit.window,
+                    calls: VecDeque::new(),
+                });
+            window.expire(now);
+            if window.calls.len() >= limit.calls as usize {
+                let retry_after = window.calls.front().map_or(limit.window, |first| {
+                    (*first + limit.window).saturating_duration_since(now)
+                });
+                if exceeded.is_none_or(|(_, longest)| retry_after > longest) {
+                    exceeded = Some((limit.action, retry_after));
+                }
+            }
+            keys.push(key);
+        }
+
+        if exceeded.is_none() {
+            for key in keys {
+                if let Some(window) = self.windows.get_mut(&key) {
+                    window.calls.push_back(now);
+                }
+            }
+        }
+        exceeded
+    }
+}
+
+fn record_stop(
+    runtime_state: &PluginRuntimeState,
+    instance_id: &str,
+    stop_result: Result<()>,
+) -> Result<()> {
+    if let Err(err) = stop_result {
+        runtime_state.record_error(instance_id, err.to_string());
+        return Err(err);
+    }
+    runtime_state.set_lifecycle(instance_id, PluginLifecycleState::Stopped);
+    runtime_state.clear_error(instance_id);
+    Ok(())
+}
+
 fn compare_routes(left: &Route, right: &Route) -> std::cmp::Ordering {
     left.priority
         .cmp(&right.priority)
@@ -1091,18 +2403,96 @@ impl RuntimePluginEngine {
         runtime_state: PluginRuntimeState,
         command_prefixes: Arc<[String]>,
     ) -> Self {
-        Self {
+        let engine = Self {
             services,
             runtime_state,
             command_prefixes,
-            routing_table: RoutingTable::default(),
-            concurrency_locks: DashMap::new(),
+            routing_table: Arc::default(),
             plugins: Vec::new(),
             enabled_plugins: HashMap::new(),
             enabled_order: Vec::new(),
             disabled_plugins: HashMap::new(),
             service_registered: Vec::new(),
-        }
+            options: DispatchOptions::default(),
+            dispatcher: Dispatcher {
+                snapshot: Arc::default(),
+                concurrency_locks: Arc::default(),
+                rate_limiter: Arc::default(),
+            },
+            pending_stops: HashMap::new(),
+        };
+        engine.publish();
+        engine
+    }
+
+    /// Answer the built-in help command, see [`HelpOptions`].
+    #[must_use]
+    pub fn with_help(mut self, options: HelpOptions) -> Self {
+        self.options.help = Some(options);
+        self.publish();
+        self
+    }
+
+    /// Reply with close matches to unknown prefixed commands, see [`SuggestionOptions`].
+    #[must_use]
+    pub fn with_suggestions(mut self, options: SuggestionOptions) -> Self {
+        self.options.suggestions = Some(options);
+        self.publish();
+        self
+    }
+
+    /// Timeout for handlers that do not declare their own.
+    #[must_use]
+    pub fn with_handler_timeout(mut self, timeout: Duration) -> Self {
+        self.options.handler_timeout = Some(timeout);
+        self.publish();
+        self
+    }
+
+    /// Auto-disable plugins that keep failing, see [`CircuitBreaker`].
+    #[must_use]
+    pub fn with_circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.options.circuit_breaker = Some(breaker);
+        self.publish();
+        self
+    }
+
+    /// What dispatch does after a handler without its own policy fails.
+    #[must_use]
+    pub fn with_error_policy(mut self, policy: DispatchErrorPolicy) -> Self {
+        self.options.error_policy = policy;
+        self.publish();
+        self
+    }
+
+    /// Run `middleware` around every event and handler, see [`Middleware`].
+    ///
+    /// Lower priorities run first; equal priorities keep registration order.
+    #[must_use]
+    pub fn with_middleware(mut self, priority: i32, middleware: Arc<dyn Middleware>) -> Self {
+        self.options.middleware.insert(priority, middleware);
+        self.publish();
+        self
+    }
+
+    /// Handle that dispatches from the latest published snapshot, so workers
+    /// never wait for lifecycle changes on the engine.
+    #[must_use]
+    pub fn dispatcher(&self) -> Dispatcher {
+        self.dispatcher.clone()
+    }
+
+    /// Make the current routes, plugins and options visible to dispatch.
+    fn publish(&self) {
+        self.dispatcher.snapshot.store(Arc::new(DispatchSnapshot {
+            services: self.services.clone(),
+            runtime_state: self.runtime_state.clone(),
+            command_prefixes: self.command_prefixes.clone(),
+            routing_table: self.routing_table.clone(),
+            plugins: self.plugins.clone(),
+            enabled_order: self.enabled_order.clone(),
+            options: self.options.clone(),
+        }));
     }
 
     pub fn push(&mut self, plugin: Box<dyn RuntimePlugin>) {
@@ -1137,22 +2527,39 @@ impl RuntimePluginEngine {
     }
 
     fn rebuild_routing_table(&mut self) -> Result<()> {
+        self.routing_table = Arc::new(self.build_routing_table()?);
+        self.publish();
+        Ok(())
+    }
+
+    fn build_routing_table(&self) -> Result<RoutingTable> {
         let mut command_prefixes: Vec<String> = self.command_prefixes.iter().cloned().collect();
         let mut table = RoutingTable::default();
+        let mut regex_routes = Vec::new();
+        let mut text_routes = Vec::new();
         for plugin_index in self.enabled_order.iter().copied() {
             let registered = &self.plugins[plugin_index];
             for (handler_index, handler) in registered.handlers().iter().enumerate() {
                 let route = Route {
                     plugin_index,
                     handler_index,
+                    event_kind: handler.event_kind,
+                    filter: (!handler.filter.is_empty()).then(|| Arc::new(handler.filter.clone())),
                     priority: handler.priority,
                     block: handler.block,
                     concurrency: handler.concurrency,
+                    parse_error: handler.parse_error,
                     permissions: handler.permissions.clone().into(),
+                    rate_limits: handler.rate_limits.clone().into(),
+                    timeout: handler.timeout,
+                    on_error: handler.on_error,
+                };
+                let commands = match &handler.group {
+                    Some(group) => table.groups.entry(group.clone()).or_default(),
+                    None => &mut table.commands,
                 };
                 for command in &handler.commands {
-                    table
-                        .commands
+                    commands
                         .entry(command.clone())
                         .or_default()
                         .push(route.clone());
@@ -1161,32 +2568,42 @@ impl RuntimePluginEngine {
                 if handler.wildcard {
                     table.wildcard.push(route.clone());
                 }
-                for pattern in &handler.regex_patterns {
-                    let regex = regex::Regex::new(pattern).map_err(|err| {
-                        anyhow!(
-                            "plugin `{}` declared invalid regex `{}`: {}",
-                            registered.instance_id(),
-                            pattern,
-                            err
-                        )
-                    })?;
-                    table.regex.push(RegexRoute {
-                        route: route.clone(),
-                        regex,
-                    });
+                let patterns = handler
+                    .regex_patterns
+                    .iter()
+                    .map(|pattern| {
+                        regex::Regex::new(pattern).map_err(|err| {
+                            anyhow!(
+                                "plugin `{}` declared invalid regex `{}`: {}",
+                                registered.instance_id(),
+                                pattern,
+                                err
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                if !patterns.is_empty() {
+                    regex_routes.push((route.clone(), patterns));
+                }
+                if !handler.text_matchers.is_empty() {
+                    text_routes.push((route.clone(), handler.text_matchers.clone()));
                 }
             }
         }
         table.command_prefixes = normalize_command_prefixes(command_prefixes);
-        for routes in table.commands.values_mut() {
+        for routes in table
+            .commands
+            .values_mut()
+            .chain(table.groups.values_mut().flat_map(HashMap::values_mut))
+        {
             sort_routes(routes);
         }
         sort_routes(&mut table.wildcard);
-        table
-            .regex
-            .sort_by(|left, right| compare_routes(&left.route, &right.route));
-        self.routing_table = table;
-        Ok(())
+        regex_routes.sort_by(|left, right| compare_routes(&left.0, &right.0));
+        table.regex = RegexRoutes::new(regex_routes)?;
+        text_routes.sort_by(|left, right| compare_routes(&left.0, &right.0));
+        table.text = TextRoutes::new(text_routes)?;
+        Ok(table)
     }
 
     #[must_use]
@@ -1194,30 +2611,27 @@ impl RuntimePluginEngine {
         &self.plugins
     }
 
-    #[must_use]
+    /// Never waits on a plugin: one with a stop or config change queued
+    /// behind its in-flight handlers reports that instead of its own health.
     pub fn plugin_snapshots(&self) -> Vec<RuntimePluginSnapshot> {
-        let mut snapshots: Vec<_> = self
-            .plugins
-            .iter()
-            .map(|registered| {
-                let plugin = registered.plugin();
-                let instance_id = registered.instance_id().to_string();
-                RuntimePluginSnapshot {
-                    lifecycle: self.runtime_state.snapshot(&instance_id),
-                    instance_id,
-                    kind: plugin.kind().to_string(),
-                    manifest: plugin.manifest(),
-                    health: plugin.health(),
-                    reloadable: registered.reload_descriptor().is_reloadable(),
-                }
-            })
-            .collect();
+        let mut snapshots = Vec::with_capacity(self.plugins.len());
+        for registered in &self.plugins {
+            let instance_id = registered.instance_id().to_string();
+            snapshots.push(RuntimePluginSnapshot {
+                lifecycle: self.runtime_state.snapshot(&instance_id),
+                instance_id,
+                kind: registered.kind().to_string(),
+                manifest: registered.manifest().clone(),
+                health: registered.health(),
+                reloadable: registered.reload_descriptor().is_reloadable(),
+            });
+        }
         snapshots.sort_by(|left, right| left.instance_id.cmp(&right.instance_id));
         snapshots
     }
 
     pub async fn init_all(&mut self) -> Result<()> {
-        self.register_plugin_services()?;
+        self.register_plugin_services().await?;
         self.rebuild_routing_table()?;
         self.preflight_startup_requirements()?;
 
@@ -1229,6 +2643,7 @@ impl RuntimePluginEngine {
 
             if let Err(err) = self.plugins[plugin_index]
                 .plugin_mut()
+                .await
                 .init(self.services.clone().with_instance_id(instance_id.clone()))
                 .await
             {
@@ -1243,7 +2658,7 @@ impl RuntimePluginEngine {
         Ok(())
     }
 
-    fn register_plugin_services(&mut self) -> Result<()> {
+    async fn register_plugin_services(&mut self) -> Result<()> {
         for order_index in 0..self.enabled_order.len() {
             let plugin_index = self.enabled_order[order_index];
             if self.service_registered[plugin_index] {
@@ -1253,6 +2668,7 @@ impl RuntimePluginEngine {
             let instance_id = self.plugins[plugin_index].instance_id().to_string();
             if let Err(err) = self.plugins[plugin_index]
                 .plugin_mut()
+                .await
                 .register_services(&mut self.services.service_registry)
             {
                 let err = anyhow!("plugin `{instance_id}` service registration failed: {err}");
@@ -1311,10 +2727,10 @@ impl RuntimePluginEngine {
             .copied()
             .filter_map(|plugin_index| {
                 let registered = &self.plugins[plugin_index];
-                let manifest = registered.plugin().manifest();
+                let manifest = registered.manifest();
                 let missing_capabilities =
                     if let CapabilityNegotiation::Failed { missing_required } =
-                        negotiate_capabilities(&manifest, &provided_capabilities)
+                        negotiate_capabilities(manifest, &provided_capabilities)
                     {
                         missing_required
                     } else {
@@ -1347,6 +2763,7 @@ impl RuntimePluginEngine {
 
             if let Err(err) = self.plugins[plugin_index]
                 .plugin_mut()
+                .await
                 .start(self.services.clone().with_instance_id(instance_id.clone()))
                 .await
             {
@@ -1380,6 +2797,10 @@ impl RuntimePluginEngine {
             PluginLifecycleState::Initializing
             | PluginLifecycleState::Starting
             | PluginLifecycleState::Running => Ok(()),
+            PluginLifecycleState::AutoDisabled => {
+                self.runtime_state.reset_circuit(instance_id);
+                Ok(())
+            }
             PluginLifecycleState::Registered
             | PluginLifecycleState::Stopping
             | PluginLifecycleState::Stopped
@@ -1402,6 +2823,10 @@ impl RuntimePluginEngine {
             PluginLifecycleState::Starting | PluginLifecycleState::Running => {
                 self.stop_plugin(instance_id).await
             }
+            PluginLifecycleState::AutoDisabled => {
+                self.runtime_state.reset_circuit(instance_id);
+                self.stop_plugin(instance_id).await
+            }
             PluginLifecycleState::Registered
             | PluginLifecycleState::Initializing
             | PluginLifecycleState::Stopping
@@ -1415,6 +2840,7 @@ impl RuntimePluginEngine {
         if !self.enabled_plugins.contains_key(instance_id) {
             return Ok(());
         }
+        self.finish_pending_stop(instance_id).await;
 
         if self.runtime_state.snapshot(instance_id).lifecycle_state
             == PluginLifecycleState::Registered
@@ -1431,6 +2857,7 @@ impl RuntimePluginEngine {
 
         let start_result = self.plugins[plugin_index]
             .plugin_mut()
+            .await
             .start(services)
             .await;
         if let Err(err) = start_result {
@@ -1447,7 +2874,8 @@ impl RuntimePluginEngine {
 
     pub async fn init_plugin(&mut self, instance_id: &str) -> Result<()> {
         let plugin_index = self.plugin_index(instance_id)?;
-        self.register_plugin_services()?;
+        self.finish_pending_stop(instance_id).await;
+        self.register_plugin_services().await?;
         self.preflight_startup_requirements()?;
 
         let services = self
@@ -1457,7 +2885,11 @@ impl RuntimePluginEngine {
         self.runtime_state
             .set_lifecycle(instance_id, PluginLifecycleState::Initializing);
 
-        let init_result = self.plugins[plugin_index].plugin_mut().init(services).await;
+        let init_result = self.plugins[plugin_index]
+            .plugin_mut()
+            .await
+            .init(services)
+            .await;
         if let Err(err) = init_result {
             self.runtime_state
                 .record_error(instance_id, err.to_string());
@@ -1468,24 +2900,46 @@ impl RuntimePluginEngine {
         Ok(())
     }
 
+    /// Stop the plugin right away when it is idle.
+    ///
+    /// With handlers in flight the plugin stays `Stopping` and `stop()` runs on
+    /// a background task once they finish, so the caller never waits on a slow
+    /// handler; its outcome is recorded in the runtime state.
     pub async fn stop_plugin(&mut self, instance_id: &str) -> Result<()> {
         let plugin_index = self.plugin_index(instance_id)?;
+        if self
+            .pending_stops
+            .get(instance_id)
+            .is_some_and(|pending| !pending.is_finished())
+        {
+            return Ok(());
+        }
         self.runtime_state
             .set_lifecycle(instance_id, PluginLifecycleState::Stopping);
 
-        let stop_result = self.plugins[plugin_index].plugin_mut().stop().await;
-        if let Err(err) = stop_result {
-            self.runtime_state
-                .record_error(instance_id, err.to_string());
-            return Err(err);
+        let registered = self.plugins[plugin_index].clone();
+        if let Ok(mut plugin) = registered.plugin.try_write() {
+            let stop_result = plugin.stop().await;
+            return record_stop(&self.runtime_state, instance_id, stop_result);
         }
 
-        self.runtime_state
-            .set_lifecycle(instance_id, PluginLifecycleState::Stopped);
-        self.runtime_state.clear_error(instance_id);
+        let runtime_state = self.runtime_state.clone();
+        let owned_id = instance_id.to_string();
+        let pending = tokio::spawn(async move {
+            let stop_result = registered.plugin_mut().await.stop().await;
+            let _ = record_stop(&runtime_state, &owned_id, stop_result);
+        });
+        self.pending_stops.insert(instance_id.to_string(), pending);
         Ok(())
     }
 
+    /// Wait for a background `stop()` of `instance_id` before touching it again.
+    async fn finish_pending_stop(&mut self, instance_id: &str) {
+        if let Some(pending) = self.pending_stops.remove(instance_id) {
+            let _ = pending.await;
+        }
+    }
+
     pub async fn reload_plugin(&mut self, instance_id: &str) -> Result<()> {
         let plugin_index = self.plugin_index(instance_id)?;
         let PluginReloadDescriptor::Reloadable(reloader) =
@@ -1519,29 +2973,39 @@ impl RuntimePluginEngine {
         let was_enabled = self.enabled_plugins.contains_key(instance_id);
         let was_running = self.runtime_state.snapshot(instance_id).lifecycle_state
             == PluginLifecycleState::Running;
+        // Dispatch keeps using the published old instance until the candidate
+        // is ready and its routes are published.
         let old = std::mem::replace(&mut self.plugins[plugin_index], candidate);
-        if let Err(err) = self.rebuild_routing_table() {
-            self.plugins[plugin_index] = old;
-            let _ = self.rebuild_routing_table();
-            self.runtime_state
-                .record_error(instance_id, err.to_string());
-            return Err(err);
-        }
+        let routing_table = match self.build_routing_table() {
+            Ok(routing_table) => routing_table,
+            Err(err) => {
+                self.plugins[plugin_index] = old;
+                self.runtime_state
+                    .record_error(instance_id, err.to_string());
+                return Err(err);
+            }
+        };
 
         if was_enabled {
             if let Err(err) = self.init_plugin(instance_id).await {
                 self.plugins[plugin_index] = old;
-                let _ = self.rebuild_routing_table();
                 return Err(err);
             }
             if was_running && let Err(err) = self.start_plugin(instance_id).await {
                 self.plugins[plugin_index] = old;
-                let _ = self.rebuild_routing_table();
                 return Err(err);
             }
         }
-        let mut old = old;
-        let _ = old.plugin_mut().stop().await;
+        self.routing_table = Arc::new(routing_table);
+        self.publish();
+        // The old instance is unpublished; stop it once its in-flight handlers finish.
+        if let Ok(mut plugin) = old.plugin.try_write() {
+            let _ = plugin.stop().await;
+        } else {
+            tokio::spawn(async move {
+                let _ = old.plugin_mut().await.stop().await;
+            });
+        }
         self.runtime_state.clear_error(instance_id);
         Ok(())
     }
@@ -1555,14 +3019,17 @@ impl RuntimePluginEngine {
     }
 
     pub async fn stop_all(&mut self) -> Result<()> {
+        for (_, pending) in self.pending_stops.drain() {
+            let _ = pending.await;
+        }
         let mut first_error = None;
 
-        for registered in self.plugins.iter_mut().rev() {
+        for registered in self.plugins.iter().rev() {
             let instance_id = registered.instance_id().to_string();
             self.runtime_state
                 .set_lifecycle(&instance_id, PluginLifecycleState::Stopping);
 
-            if let Err(err) = registered.plugin_mut().stop().await {
+            if let Err(err) = registered.plugin_mut().await.stop().await {
                 self.runtime_state
                     .record_error(&instance_id, err.to_string());
                 if first_error.is_none() {
@@ -1587,72 +3054,212 @@ impl RuntimePluginEngine {
         instance_id: &str,
         update: ConfigUpdate,
     ) -> Result<ApplyConfigOutcome> {
+        self.prepare_config(instance_id, update)
+            .await?
+            .apply()
+            .await
+    }
+
+    /// Record `update` as the desired config and return the part that waits
+    /// for the plugin's in-flight handlers, so callers can release the engine
+    /// before applying it.
+    pub(crate) async fn prepare_config(
+        &mut self,
+        instance_id: &str,
+        update: ConfigUpdate,
+    ) -> Result<ConfigApplication> {
         let plugin_index = self.plugin_index(instance_id)?;
+        self.finish_pending_stop(instance_id).await;
 
         self.runtime_state
             .set_desired_config_version(instance_id, update.version);
-        if update.dry_run {
+        let update = if update.dry_run {
             self.runtime_state
                 .mark_config_validated(instance_id, update.version);
             self.runtime_state.clear_error(instance_id);
+            None
+        } else {
+            Some(update)
+        };
+        Ok(ConfigApplication {
+            instance_id: instance_id.to_string(),
+            registered: self.plugins[plugin_index].clone(),
+            runtime_state: self.runtime_state.clone(),
+            update,
+        })
+    }
+}
+
+/// A config update waiting for exclusive access to its plugin.
+pub(crate) struct ConfigApplication {
+    instance_id: String,
+    registered: RegisteredPlugin,
+    runtime_state: PluginRuntimeState,
+    /// `None` for a dry run, which is already recorded.
+    update: Option<ConfigUpdate>,
+}
+
+impl ConfigApplication {
+    pub(crate) async fn apply(self) -> Result<ApplyConfigOutcome> {
+        let Some(update) = self.update else {
             return Ok(ApplyConfigOutcome::skipped());
-        }
-        let outcome = self.plugins[plugin_index]
+        };
+        let outcome = self
+            .registered
             .plugin_mut()
+            .await
             .apply_config(update)
             .await?;
 
         if let Some(version) = outcome.applied_version {
-            self.runtime_state.mark_config_applied(instance_id, version);
+            self.runtime_state
+                .mark_config_applied(&self.instance_id, version);
         }
 
-        self.runtime_state.clear_error(instance_id);
+        self.runtime_state.clear_error(&self.instance_id);
         Ok(outcome)
     }
 }
 
 impl RuntimePluginEngine {
+    /// Dispatch `ctx`, returning whether it was blocked or the first handler error.
+    ///
+    /// See [`Self::dispatch`] for the outcome of every handler.
+    pub async fn handle_all(&self, ctx: &Context) -> Result<bool> {
+        self.dispatcher.handle_all(ctx).await
+    }
+
+    /// Dispatch `ctx` from the latest snapshot, see [`Dispatcher::dispatch`].
+    pub async fn dispatch(&self, ctx: &Context) -> Result<DispatchReport> {
+        self.dispatcher.dispatch(ctx).await
+    }
+
+    /// Commands the sender of `ctx` may run, grouped by enabled plugin.
+    pub async fn command_catalog(&self, ctx: &Context) -> Result<Vec<HelpSection>> {
+        self.dispatcher.command_catalog(ctx).await
+    }
+}
+
+impl Dispatcher {
+    /// Dispatch `ctx`, returning whether it was blocked or the first handler error.
+    ///
+    /// See [`Self::dispatch`] for the outcome of every handler.
     pub async fn handle_all(&self, ctx: &Context) -> Result<bool> {
+        self.dispatch(ctx).await?.into_result()
+    }
+
+    /// Dispatch `ctx` and report what every matched handler did.
+    ///
+    /// Handler failures are part of the report; `Err` means dispatch itself
+    /// failed, such as a middleware, a permission check or an automatic reply.
+    pub async fn dispatch(&self, ctx: &Context) -> Result<DispatchReport> {
+        let snapshot = self.snapshot.load_full();
+        let middleware = &snapshot.options.middleware;
+        if middleware.is_empty() {
+            return self.route(&snapshot, ctx).await;
+        }
+
+        let mut ctx = ctx.clone();
+        let report = match middleware.before_event(&mut ctx).await? {
+            MiddlewareFlow::Continue => self.route(&snapshot, &ctx).await?,
+            MiddlewareFlow::Stop => DispatchReport::default().blocked(),
+        };
+        middleware.after_event(&ctx, &report).await;
+        Ok(report)
+    }
+
+    /// Commands the sender of `ctx` may run, grouped by enabled plugin.
+    pub async fn command_catalog(&self, ctx: &Context) -> Result<Vec<HelpSection>> {
+        self.snapshot.load_full().command_catalog(ctx).await
+    }
+
+    async fn route(&self, snapshot: &DispatchSnapshot, ctx: &Context) -> Result<DispatchReport> {
+        let mut report = DispatchReport::default();
         let text = ctx.text();
         let invocation = parse_command_line_with_prefixes(
             &text,
-            self.routing_table
+            snapshot
+                .routing_table
                 .command_prefixes
                 .iter()
                 .map(String::as_str),
         )
-        .or_else(|| parse_command_line_with_prefixes(&text, std::iter::empty::<&str>()));
+        .or_else(|| parse_command_line_with_prefixes(&text, std::iter::empty::<&str>()))
+        .map(|invocation| snapshot.routing_table.resolve_group(invocation));
+
+        if let Some(help) = &snapshot.options.help
+            && let Some(invocation) = &invocation
+            && invocation.group().is_none()
+            && help.matches(invocation.command())
+            && !snapshot
+                .routing_table
+                .commands
+                .contains_key(invocation.command())
+            && (invocation.prefix().is_some() || snapshot.routing_table.command_prefixes.is_empty())
+        {
+            snapshot.reply_help(ctx, invocation).await?;
+            return Ok(report.blocked());
+        }
 
         let mut matched = Vec::new();
-        if let Some(invocation) = invocation
-            && let Some(routes) = self.routing_table.commands.get(invocation.command())
+        if let Some(invocation) = &invocation
+            && let Some(routes) = snapshot.routing_table.command_routes(invocation)
         {
             matched.extend(routes.iter().cloned().map(|route| MatchedHandler {
                 invocation: Some(invocation.clone()),
+                regex_pattern: None,
                 match_rank: 0,
                 route,
             }));
         }
 
+        if matched.is_empty()
+            && let Some(options) = &snapshot.options.suggestions
+            && let Some(invocation) = &invocation
+            && invocation.prefix().is_some()
+            && snapshot.reply_suggestions(ctx, invocation, options).await?
+        {
+            return Ok(report.blocked());
+        }
+
         if matched.is_empty() {
-            matched.extend(self.routing_table.wildcard.iter().cloned().map(|route| {
-                MatchedHandler {
-                    invocation: None,
-                    match_rank: 1,
-                    route,
-                }
-            }));
+            matched.extend(
+                snapshot
+                    .routing_table
+                    .wildcard
+                    .iter()
+                    .cloned()
+                    .map(|route| MatchedHandler {
+                        invocation: None,
+                        regex_pattern: None,
+                        match_rank: 1,
+                        route,
+                    }),
+            );
         }
 
         matched.extend(
-            self.routing_table
+            snapshot
+                .routing_table
                 .regex
-                .iter()
-                .filter(|route| route.regex.is_match(&text))
+                .matches(&text)
+                .map(|(route, pattern)| MatchedHandler {
+                    invocation: None,
+                    regex_pattern: Some(pattern),
+                    match_rank: 2,
+                    route: route.clone(),
+                }),
+        );
+        matched.extend(
+            snapshot
+                .routing_table
+                .text
+                .matches(&text)
                 .map(|route| MatchedHandler {
                     invocation: None,
+                    regex_pattern: None,
                     match_rank: 2,
-                    route: route.route.clone(),
+                    route: route.clone(),
                 }),
         );
 
@@ -1664,69 +3271,461 @@ impl RuntimePluginEngine {
                 .then(left.route.plugin_index.cmp(&right.route.plugin_index))
                 .then(left.route.handler_index.cmp(&right.route.handler_index))
         });
+        // A handler matched by both a regex and a text matcher runs once, with its captures.
+        matched.dedup_by(|later, earlier| {
+            later.match_rank == earlier.match_rank
+                && later.route.plugin_index == earlier.route.plugin_index
+                && later.route.handler_index == earlier.route.handler_index
+        });
 
         for candidate in matched {
+            if !candidate.route.accepts(ctx) {
+                continue;
+            }
             let plugin_index = candidate.route.plugin_index;
-            let registered = &self.plugins[plugin_index];
-            if !self.permissions_match(ctx, &candidate.route).await? {
+            let handler_index = candidate.route.handler_index;
+            let registered = &snapshot.plugins[plugin_index];
+            if !snapshot
+                .runtime_state
+                .circuit_allows(registered.instance_id())
+            {
+                report.push(
+                    registered,
+                    handler_index,
+                    HandlerStatus::Skipped(SkipReason::CircuitOpen),
+                );
+                continue;
+            }
+            if !snapshot
+                .permissions_match(ctx, &candidate.route.permissions)
+                .await?
+            {
+                report.push(
+                    registered,
+                    handler_index,
+                    HandlerStatus::Skipped(SkipReason::PermissionDenied),
+                );
                 continue;
             }
 
-            let _permit = self.acquire_concurrency(ctx, &candidate.route).await?;
-
-            match registered
-                .plugin()
-                .handle_with_invocation(ctx, candidate.invocation)
-                .await
+            let command = candidate
+                .invocation
+                .as_ref()
+                .map(|invocation| invocation.command().to_string());
+            let info = HandlerInfo {
+                instance_id: registered.instance_id(),
+                handler_index,
+                decl: &registered.handlers()[handler_index],
+                command: command.as_deref(),
+            };
+            if snapshot
+                .options
+                .middleware
+                .before_handler(ctx, info)
+                .await?
+                == MiddlewareFlow::Stop
             {
+                report.push(
+                    registered,
+                    handler_index,
+                    HandlerStatus::Skipped(SkipReason::Middleware),
+                );
+                continue;
+            }
+
+            let limited = self
+                .rate_limiter
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .take(ctx, &candidate.route);
+            if let Some((action, retry_after)) = limited {
+                if action == RateLimitAction::Reply
+                    && let Err(err) = ctx
+                        .reply_text(format!(
+                            "This command is on cooldown, try again in {}s.",
+                            retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0)
+                        ))
+                        .await
+                {
+                    log::warn!(
+                        "Failed to send cooldown notice for plugin `{}`: {err}",
+                        registered.instance_id()
+                    );
+                }
+                report.push(
+                    registered,
+                    handler_index,
+                    HandlerStatus::Skipped(SkipReason::RateLimited),
+                );
+                if candidate.route.block {
+                    return Ok(report.blocked());
+                }
+                continue;
+            }
+
+            let _lease = match self.acquire_concurrency(ctx, &candidate.route).await? {
+                Admission::Rejected => {
+                    report.push(
+                        registered,
+                        handler_index,
+                        HandlerStatus::Skipped(SkipReason::ConcurrencyRejected),
+                    );
+                    if candidate.route.block {
+                        return Ok(report.blocked());
+                    }
+                    continue;
+                }
+                Admission::Acquired(lease) => Some(lease),
+                Admission::Unlimited => None,
+            };
+
+            let regex_match = candidate
+                .regex_pattern
+                .and_then(|pattern| snapshot.routing_table.regex.captures(pattern, &text));
+            let handled = async {
+                let plugin = registered.plugin().await;
+                let handled = match regex_match {
+                    Some(regex_match) => plugin.handle_regex(ctx, regex_match),
+                    None => plugin.handle_with_invocation(ctx, candidate.invocation),
+                };
+                CatchUnwind(handled).await.unwrap_or_else(|payload| {
+                    Err(anyhow::Error::new(HandlerPanic {
+                        instance_id: registered.instance_id().to_string(),
+                        message: panic_message(payload.as_ref()),
+                    }))
+                })
+            };
+            let result = match candidate.route.timeout.or(snapshot.options.handler_timeout) {
+                Some(timeout) => {
+                    tokio::time::timeout(timeout, handled)
+                        .await
+                        .unwrap_or_else(|_| {
+                            Err(anyhow::Error::new(HandlerTimeout {
+                                instance_id: registered.instance_id().to_string(),
+                                timeout,
+                            }))
+                        })
+                }
+                None => handled.await,
+            };
+            snapshot
+                .options
+                .middleware
+                .after_handler(ctx, info, &result)
+                .await;
+
+            match result {
                 Ok(outcome) => {
-                    self.runtime_state.clear_error(registered.instance_id());
+                    snapshot.runtime_state.clear_error(registered.instance_id());
+                    report.push(registered, handler_index, HandlerStatus::Completed(outcome));
                     if outcome.block || candidate.route.block {
-                        return Ok(true);
+                        return Ok(report.blocked());
                     }
                 }
                 Err(err) => {
-                    self.runtime_state
-                        .record_error(registered.instance_id(), err.to_string());
-                    return Err(err);
+                    let err = match err.downcast::<ArgsParseError>() {
+                        Ok(parse_err) => {
+                            let parse_err = with_command_help(
+                                parse_err,
+                                &registered.handlers()[handler_index],
+                                command.as_deref(),
+                            );
+                            match candidate.route.parse_error {
+                                ParseErrorPolicy::Reply | ParseErrorPolicy::Silent => {
+                                    if candidate.route.parse_error == ParseErrorPolicy::Reply {
+                                        let reply = match parse_err.help() {
+                                            Some(help) => {
+                                                format!("{}\n{help}", parse_err.message())
+                                            }
+                                            None => parse_err.message().to_string(),
+                                        };
+                                        if let Err(err) = ctx.reply_text(reply).await {
+                                            log::warn!(
+                                                "Failed to send argument error for plugin `{}`: {err}",
+                                                registered.instance_id()
+                                            );
+                                        }
+                                    }
+                                    report.push(
+                                        registered,
+                                        handler_index,
+                                        HandlerStatus::InvalidArgs,
+                                    );
+                                    if candidate.route.block {
+                                        return Ok(report.blocked());
+                                    }
+                                    continue;
+                                }
+                                ParseErrorPolicy::Propagate => anyhow::Error::new(parse_err),
+                            }
+                        }
+                        Err(err) => err,
+                    };
+                    let instance_id = registered.instance_id();
+                    let failures = if err.is::<HandlerTimeout>() {
+                        snapshot
+                            .runtime_state
+                            .record_timeout(instance_id, err.to_string())
+                    } else {
+                        snapshot
+                            .runtime_state
+                            .record_failure(instance_id, err.to_string())
+                    };
+                    if let Some(breaker) = &snapshot.options.circuit_breaker
+                        && failures >= breaker.failure_threshold()
+                    {
+                        snapshot.runtime_state.trip(
+                            instance_id,
+                            breaker
+                                .reenable_after()
+                                .map(|cooldown| Instant::now() + cooldown),
+                        );
+                    }
+                    report.push(registered, handler_index, HandlerStatus::Failed(err));
+                    if candidate
+                        .route
+                        .on_error
+                        .unwrap_or(snapshot.options.error_policy)
+                        == DispatchErrorPolicy::Stop
+                    {
+                        return Ok(report);
+                    }
                 }
             }
         }
 
-        Ok(false)
+        Ok(report)
+    }
+
+    async fn acquire_concurrency(&self, ctx: &Context, route: &Route) -> Result<Admission<'_>> {
+        let Some(key) = ConcurrencyKey::of(ctx, route) else {
+            return Ok(Admission::Unlimited);
+        };
+        let slot = self
+            .concurrency_locks
+            .entry(key.clone())
+            .or_default()
+            .clone();
+        let mut lease = SlotLease {
+            locks: &self.concurrency_locks,
+            key,
+            slot: Some(slot),
+            permit: None,
+        };
+        let semaphore = lease.slot().semaphore.clone();
+        if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+            lease.permit = Some(permit);
+            return Ok(Admission::Acquired(lease));
+        }
+
+        let permit = match route.concurrency {
+            ConcurrencyPolicy::Drop(_) => None,
+            ConcurrencyPolicy::Queue(_, max_waiting) => {
+                let waiting = &lease.slot().waiting;
+                if waiting
+                    .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                        (count < max_waiting).then_some(count + 1)
+                    })
+                    .is_ok()
+                {
+                    let _waiting = WaitingGuard(waiting);
+                    Some(semaphore.acquire_owned().await?)
+                } else {
+                    None
+                }
+            }
+            ConcurrencyPolicy::Latest(_) => {
+                let mut latest = lease.slot().latest.subscribe();
+                let mut ticket = 0;
+                lease.slot().latest.send_modify(|latest| {
+                    *latest += 1;
+                    ticket = *latest;
+                });
+                tokio::select! {
+                    permit = semaphore.acquire_owned() => Some(permit?),
+                    () = superseded(&mut latest, ticket) => None,
+                }
+            }
+            ConcurrencyPolicy::Parallel
+            | ConcurrencyPolicy::PluginSerial
+            | ConcurrencyPolicy::UserSerial
+            | ConcurrencyPolicy::GroupSerial
+            | ConcurrencyPolicy::ConversationSerial => Some(semaphore.acquire_owned().await?),
+        };
+
+        Ok(match permit {
+            Some(permit) => {
+                lease.permit = Some(permit);
+                Admission::Acquired(lease)
+            }
+            None => Admission::Rejected,
+        })
+    }
+}
+
+impl DispatchSnapshot {
+    async fn command_catalog(&self, ctx: &Context) -> Result<Vec<HelpSection>> {
+        let mut sections = Vec::new();
+        for plugin_index in self.enabled_order.iter().copied() {
+            let registered = &self.plugins[plugin_index];
+            let mut commands = Vec::new();
+            for handler in registered.handlers() {
+                if handler.event_kind != HandlerEventKind::Message
+                    || handler.command_meta.is_empty()
+                    || !handler.filter.accepts(ctx)
+                    || !self.permissions_match(ctx, &handler.permissions).await?
+                {
+                    continue;
+                }
+                let prefix = handler
+                    .command_prefixes
+                    .first()
+                    .or_else(|| self.command_prefixes.first())
+                    .cloned()
+                    .unwrap_or_default();
+                commands.extend(handler.command_meta.iter().map(|meta| HelpEntry {
+                    prefix: prefix.clone(),
+                    meta: meta.clone(),
+                }));
+            }
+            if !commands.is_empty() {
+                sections.push(HelpSection {
+                    plugin_id: registered.instance_id().to_string(),
+                    description: registered.manifest().description.clone(),
+                    commands,
+                });
+            }
+        }
+        Ok(sections)
     }
 
-    async fn acquire_concurrency(
+    /// Reply with known commands close to the unknown one, returning whether there were any.
+    async fn reply_suggestions(
         &self,
         ctx: &Context,
-        route: &Route,
-    ) -> Result<Option<tokio::sync::OwnedSemaphorePermit>> {
-        let key = match route.concurrency {
-            ConcurrencyPolicy::Parallel => return Ok(None),
-            ConcurrencyPolicy::Drop => return Ok(None),
-            ConcurrencyPolicy::PluginSerial => ConcurrencyKey::Plugin(route.plugin_index),
-            ConcurrencyPolicy::UserSerial => ConcurrencyKey::User(ctx.user_id().into_owned()),
-            ConcurrencyPolicy::GroupSerial => {
-                let Some(group_id) = ctx.group_id() else {
-                    return Ok(None);
-                };
-                ConcurrencyKey::Group(group_id.into_owned())
+        invocation: &CommandInvocation,
+        options: &SuggestionOptions,
+    ) -> Result<bool> {
+        let prefix = invocation.prefix().unwrap_or_default();
+        let group = self
+            .routing_table
+            .groups
+            .get_key_value(invocation.command());
+        let (label_prefix, query, names): (_, _, Vec<&str>) = match group {
+            Some((group, subcommands)) => (
+                format!("{prefix}{group} "),
+                invocation
+                    .args()
+                    .split_whitespace()
+                    .next()
+                    .unwrap_or_default(),
+                subcommands.keys().map(String::as_str).collect(),
+            ),
+            None => (
+                prefix.to_string(),
+                invocation.command(),
+                self.routing_table
+                    .commands
+                    .keys()
+                    .chain(self.routing_table.groups.keys())
+                    .map(String::as_str)
+                    .chain(self.options.help.iter().flat_map(HelpOptions::names))
+                    .collect(),
+            ),
+        };
+        if query.is_empty() {
+            return Ok(false);
+        }
+
+        let mut suggestions = Vec::new();
+        for name in rank_suggestions(query, names, options.max_distance()) {
+            if suggestions.len() == options.limit() {
+                break;
             }
-            ConcurrencyPolicy::ConversationSerial => ConcurrencyKey::Conversation {
-                user_id: ctx.user_id().into_owned(),
-                group_id: ctx.group_id().map(std::borrow::Cow::into_owned),
-            },
+            if self
+                .command_visible(ctx, prefix, group.map(|(group, _)| group.as_str()), name)
+                .await?
+            {
+                suggestions.push(format!("{label_prefix}{name}"));
+            }
+        }
+        if suggestions.is_empty() {
+            return Ok(false);
+        }
+
+        let label = format!("{label_prefix}{query}");
+        ctx.reply_text(render_suggestions(&label, &suggestions))
+            .await?;
+        Ok(true)
+    }
+
+    /// Whether the sender of `ctx` may run at least one route of a suggested
+    /// name under the prefix they typed.
+    async fn command_visible(
+        &self,
+        ctx: &Context,
+        prefix: &str,
+        group: Option<&str>,
+        name: &str,
+    ) -> Result<bool> {
+        let routes: Vec<&Route> = if let Some(group) = group {
+            self.routing_table.groups[group][name].iter().collect()
+        } else if let Some(routes) = self.routing_table.commands.get(name) {
+            routes.iter().collect()
+        } else if let Some(subcommands) = self.routing_table.groups.get(name) {
+            subcommands.values().flatten().collect()
+        } else {
+            // The built-in help command.
+            return Ok(true);
         };
-        let semaphore = self
-            .concurrency_locks
-            .entry(key)
-            .or_insert_with(|| Arc::new(tokio::sync::Semaphore::new(1)))
-            .clone();
-        Ok(Some(semaphore.acquire_owned().await?))
+
+        for route in routes {
+            if self
+                .route_prefixes(route)
+                .iter()
+                .any(|known| known == prefix)
+                && route.accepts(ctx)
+                && self.permissions_match(ctx, &route.permissions).await?
+            {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Prefixes a command route answers to: its handler's own, else the engine's.
+    fn route_prefixes(&self, route: &Route) -> &[String] {
+        let handler = &self.plugins[route.plugin_index].handlers()[route.handler_index];
+        if handler.command_prefixes.is_empty() {
+            &self.command_prefixes
+        } else {
+            &handler.command_prefixes
+        }
+    }
+
+    async fn reply_help(&self, ctx: &Context, invocation: &CommandInvocation) -> Result<()> {
+        let sections = self.command_catalog(ctx).await?;
+        let help_label = format!(
+            "{}{}",
+            invocation.prefix().unwrap_or_default(),
+            invocation.command()
+        );
+        let query = invocation.args().trim();
+        let text = if query.is_empty() {
+            render_catalog(&sections, &help_label)
+        } else if let Some(entry) = find_command(&sections, query) {
+            render_command(entry)
+        } else if let Some(group) = render_group(&sections, query) {
+            group
+        } else {
+            format!("Unknown command `{query}`. Send {help_label} for the list.")
+        };
+        ctx.reply_text(text).await?;
+        Ok(())
     }
 
-    async fn permissions_match(&self, ctx: &Context, route: &Route) -> Result<bool> {
-        for permission in route.permissions.iter() {
+    async fn permissions_match(&self, ctx: &Context, permissions: &[Permission]) -> Result<bool> {
+        for permission in permissions {
             if !self.permission_matches(ctx, permission).await? {
                 return Ok(false);
             }
@@ -1764,6 +3763,8 @@ impl RuntimePluginEngine {
 
 struct MatchedHandler {
     invocation: Option<CommandInvocation>,
+    /// Pattern of the regex routing table that selected this handler.
+    regex_pattern: Option<usize>,
     match_rank: u8,
     route: Route,
 }
@@ -1777,7 +3778,8 @@ mod tests {
     use crate::core::{
         context::Context,
         model::{
-            BotId, ChannelRef, EventEnvelope, OutboundMessage, OutboundReceipt, PlatformId, UserRef,
+            BotId, ChannelRef, EventEnvelope, KernelEvent, OutboundMessage, OutboundReceipt,
+            PlatformEvent, PlatformId, UserRef,
         },
         plugin::OutboundSender,
         service::{RuntimeService, ServiceKey, ServiceRegistry},
@@ -1914,6 +3916,81 @@ mod tests {
         assert_eq!(services.service_snapshots(), vec![snapshot]);
     }
 
+    #[tokio::test]
+    async fn message_actions_report_missing_capabilities() {
+        let sender: Arc<dyn OutboundSender> = Arc::new(NoopSender);
+        let services = RuntimePluginServices::new().with_sender(Some(sender.clone()));
+        let channel = ChannelRef::group("test", "g1");
+
+        let err = services
+            .delete_message(&channel, "1")
+            .await
+            .expect_err("noop sender has no message actions");
+        let unsupported = err
+            .downcast_ref::<UnsupportedCapability>()
+            .expect("capability error");
+        assert_eq!(unsupported.capability(), &Capability::MessageDelete);
+
+        struct DeleteOnly;
+
+        #[async_trait]
+        impl MessageActions for DeleteOnly {
+            async fn delete_message(&self, _channel: &ChannelRef, _message_id: &str) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        #[async_trait]
+        impl OutboundSender for DeleteOnly {
+            async fn send(&self, _message: OutboundMessage) -> Result<OutboundReceipt> {
+                Ok(OutboundReceipt::default())
+            }
+
+            fn message_actions(&self) -> Option<&dyn MessageActions> {
+                Some(self)
+            }
+        }
+
+        let ctx = Context::new(
+            EventEnvelope::new(BotId::new("test-bot"), PlatformId::new("test")).with_message(
+                crate::core::model::MessageEvent::new(
+                    UserRef::new("test", "user"),
+                    channel.clone(),
+                    "hi",
+                ),
+            ),
+            Some(Arc::new(DeleteOnly)),
+            (),
+        );
+        ctx.delete_message("1").await.unwrap();
+        let err = ctx
+            .react("1", "👍")
+            .await
+            .expect_err("reactions unsupported");
+        assert_eq!(
+            err.downcast_ref::<UnsupportedCapability>()
+                .map(UnsupportedCapability::capability),
+            Some(&Capability::Reaction)
+        );
+
+        // Advertised capabilities decide before the sender is asked.
+        let err = ctx
+            .with_capabilities(Arc::from([Capability::Reaction]))
+            .delete_message("1")
+            .await
+            .expect_err("delete is not advertised");
+        assert_eq!(
+            err.downcast_ref::<UnsupportedCapability>()
+                .map(UnsupportedCapability::capability),
+            Some(&Capability::MessageDelete)
+        );
+        let sender: Arc<dyn OutboundSender> = Arc::new(DeleteOnly);
+        let services = RuntimePluginServices::new().with_sender(Some(sender));
+        services.delete_message(&channel, "1").await.unwrap();
+        let services = services.with_capabilities([Capability::Reaction]);
+        assert!(services.delete_message(&channel, "1").await.is_err());
+    }
+
     #[test]
     fn runtime_plugin_services_infer_proactive_send_from_host_sender() {
         let sender: Arc<dyn OutboundSender> = Arc::new(NoopSender);
@@ -2142,6 +4219,285 @@ mod tests {
         }
     }
 
+    struct GatedPlugin {
+        concurrency: ConcurrencyPolicy,
+        hits: Arc<std::sync::Mutex<Vec<String>>>,
+        gate: Arc<tokio::sync::Semaphore>,
+    }
+
+    #[async_trait]
+    impl RuntimePlugin for GatedPlugin {
+        fn kind(&self) -> &'static str {
+            "gated"
+        }
+
+        fn declared_handlers(&self) -> Vec<HandlerDecl> {
+            vec![HandlerDecl::message_commands(["slow"], ["/"]).concurrency(self.concurrency)]
+        }
+
+        async fn handle(&self, ctx: &Context) -> Result<HandleOutcome> {
+            self.hits.lock().unwrap().push(ctx.text().into_owned());
+            self.gate.acquire().await?.forget();
+            Ok(HandleOutcome::block())
+        }
+    }
+
+    struct GatedEngine {
+        engine: Arc<RuntimePluginEngine>,
+        hits: Arc<std::sync::Mutex<Vec<String>>>,
+        gate: Arc<tokio::sync::Semaphore>,
+    }
+
+    impl GatedEngine {
+        async fn new(concurrency: ConcurrencyPolicy) -> Self {
+            let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let gate = Arc::new(tokio::sync::Semaphore::new(0));
+            let mut engine = RuntimePluginEngine::new(
+                RuntimePluginServices::new(),
+                PluginRuntimeState::default(),
+            );
+            engine.push(Box::new(GatedPlugin {
+                concurrency,
+                hits: hits.clone(),
+                gate: gate.clone(),
+            }));
+            engine.init_all().await.unwrap();
+            engine.start_all().await.unwrap();
+            Self {
+                engine: Arc::new(engine),
+                hits,
+                gate,
+            }
+        }
+
+        fn spawn(
+            &self,
+            text: &'static str,
+            user_id: &'static str,
+        ) -> tokio::task::JoinHandle<bool> {
+            let engine = self.engine.clone();
+            tokio::spawn(async move {
+                engine
+                    .handle_all(&test_ctx(text, user_id, None))
+                    .await
+                    .unwrap()
+            })
+        }
+
+        async fn wait_until(&self, ready: impl Fn(&RuntimePluginEngine) -> bool) {
+            while !ready(&self.engine) {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        fn hits(&self) -> Vec<String> {
+            self.hits.lock().unwrap().clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn drop_policy_rejects_invocations_while_the_slot_is_busy() {
+        let gated = GatedEngine::new(ConcurrencyPolicy::Drop(ConcurrencyScope::User)).await;
+        let first = gated.spawn("/slow a", "alice");
+        gated.wait_until(|_| !gated.hits().is_empty()).await;
+
+        assert!(!gated.spawn("/slow b", "alice").await.unwrap());
+        assert_eq!(gated.hits(), ["/slow a"]);
+        let other_user = gated.spawn("/slow c", "bob");
+        gated.wait_until(|_| gated.hits().len() == 2).await;
+
+        gated.gate.add_permits(2);
+        assert!(first.await.unwrap());
+        assert!(other_user.await.unwrap());
+        assert!(gated.engine.dispatcher.concurrency_locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn queue_policy_bounds_the_number_of_waiters() {
+        let gated = GatedEngine::new(ConcurrencyPolicy::Queue(ConcurrencyScope::Plugin, 1)).await;
+        let first = gated.spawn("/slow a", "alice");
+        gated.wait_until(|_| !gated.hits().is_empty()).await;
+        let queued = gated.spawn("/slow b", "bob");
+        gated
+            .wait_until(|engine| {
+                engine
+                    .dispatcher
+                    .concurrency_locks
+                    .iter()
+                    .any(|slot| slot.waiting.load(Ordering::SeqCst) == 1)
+            })
+            .await;
+
+        assert!(!gated.spawn("/slow c", "carol").await.unwrap());
+        gated.gate.add_permits(2);
+        assert!(first.await.unwrap());
+        assert!(queued.await.unwrap());
+        assert_eq!(gated.hits(), ["/slow a", "/slow b"]);
+        assert!(gated.engine.dispatcher.concurrency_locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn latest_policy_keeps_only_the_newest_waiter() {
+        let gated = GatedEngine::new(ConcurrencyPolicy::Latest(ConcurrencyScope::Plugin)).await;
+        let latest_ticket = |engine: &RuntimePluginEngine| {
+            engine
+                .dispatcher
+                .concurrency_locks
+                .iter()
+                .map(|slot| *slot.latest.borrow())
+                .max()
+                .unwrap_or_default()
+        };
+        let first = gated.spawn("/slow a", "alice");
+        gated.wait_until(|_| !gated.hits().is_empty()).await;
+        let superseded = gated.spawn("/slow b", "alice");
+        gated.wait_until(|engine| latest_ticket(engine) == 1).await;
+        let newest = gated.spawn("/slow c", "alice");
+
+        assert!(!superseded.await.unwrap());
+        gated.gate.add_permits(2);
+        assert!(first.await.unwrap());
+        assert!(newest.await.unwrap());
+        assert_eq!(gated.hits(), ["/slow a", "/slow c"]);
+        assert!(gated.engine.dispatcher.concurrency_locks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn serial_policies_share_user_slots_across_plugins() {
+        struct Other(Arc<std::sync::Mutex<Vec<String>>>);
+
+        #[async_trait]
+        impl RuntimePlugin for Other {
+            fn kind(&self) -> &'static str {
+                "other"
+            }
+
+            fn declared_handlers(&self) -> Vec<HandlerDecl> {
+                vec![
+                    HandlerDecl::message_commands(["other"], ["/"])
+                        .concurrency(ConcurrencyPolicy::UserSerial),
+                ]
+            }
+
+            async fn handle(&self, ctx: &Context) -> Result<HandleOutcome> {
+                self.0.lock().unwrap().push(ctx.user_id().into_owned());
+                Ok(HandleOutcome::block())
+            }
+        }
+
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let others = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        engine.push(Box::new(GatedPlugin {
+            concurrency: ConcurrencyPolicy::UserSerial,
+            hits: hits.clone(),
+            gate: gate.clone(),
+        }));
+        engine.push(Box::new(Other(others.clone())));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+        let engine = Arc::new(engine);
+
+        let slow = tokio::spawn({
+            let engine = engine.clone();
+            async move {
+                engine
+                    .handle_all(&test_ctx("/slow a", "alice", None))
+                    .await
+                    .unwrap()
+            }
+        });
+        while hits.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let alice = tokio::spawn({
+            let engine = engine.clone();
+            async move {
+                engine
+                    .handle_all(&test_ctx("/other", "alice", None))
+                    .await
+                    .unwrap()
+            }
+        });
+        assert!(
+            engine
+                .handle_all(&test_ctx("/other", "bob", None))
+                .await
+                .unwrap()
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(*others.lock().unwrap(), ["bob"]);
+
+        gate.add_permits(1);
+        assert!(slow.await.unwrap());
+        assert!(alice.await.unwrap());
+        assert_eq!(*others.lock().unwrap(), ["bob", "alice"]);
+    }
+
+    #[tokio::test]
+    async fn latest_waiters_see_tickets_taken_before_they_wait() {
+        let (sender, mut latest) = tokio::sync::watch::channel(1);
+        sender.send_replace(2);
+        latest.borrow_and_update();
+
+        tokio::time::timeout(Duration::from_secs(1), superseded(&mut latest, 1))
+            .await
+            .expect("ticket 2 supersedes ticket 1");
+    }
+
+    #[tokio::test]
+    async fn rejected_invocations_fall_through_to_other_plugins_slots() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for instance_id in ["gated", "second"] {
+            engine.push_as(
+                instance_id,
+                Box::new(GatedPlugin {
+                    concurrency: ConcurrencyPolicy::Drop(ConcurrencyScope::User),
+                    hits: hits.clone(),
+                    gate: gate.clone(),
+                }),
+            );
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+        let engine = Arc::new(engine);
+        let spawn = |text: &'static str| {
+            let engine = engine.clone();
+            tokio::spawn(async move {
+                engine
+                    .dispatch(&test_ctx(text, "alice", None))
+                    .await
+                    .unwrap()
+            })
+        };
+
+        let first = spawn("/slow a");
+        while hits.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+        let second = spawn("/slow b");
+        while hits.lock().unwrap().len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        gate.add_permits(2);
+        assert!(first.await.unwrap().blocked);
+        let report = second.await.unwrap();
+        assert!(report.blocked);
+        assert!(matches!(
+            report.handlers[0].status,
+            HandlerStatus::Skipped(SkipReason::ConcurrencyRejected)
+        ));
+        assert_eq!(report.handlers[1].instance_id, "second");
+        assert_eq!(*hits.lock().unwrap(), ["/slow a", "/slow b"]);
+    }
+
     #[tokio::test]
     async fn disabled_plugin_is_stopped_and_skipped_by_dispatch() {
         let services = RuntimePluginServices::new();
@@ -2172,6 +4528,102 @@ mod tests {
         assert_eq!(*handled.lock().unwrap(), 0);
     }
 
+    #[tokio::test]
+    async fn lifecycle_changes_do_not_wait_for_in_flight_handlers() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let stopped = Arc::new(std::sync::Mutex::new(0));
+        let handled = Arc::new(std::sync::Mutex::new(0));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        engine.push(Box::new(GatedPlugin {
+            concurrency: ConcurrencyPolicy::Parallel,
+            hits: hits.clone(),
+            gate: gate.clone(),
+        }));
+        engine.push_as(
+            "switchable",
+            Box::new(CountingLifecyclePlugin {
+                instance_id: "switchable",
+                handled: handled.clone(),
+                stopped: stopped.clone(),
+            }),
+        );
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let dispatcher = engine.dispatcher();
+        let slow = tokio::spawn({
+            let dispatcher = dispatcher.clone();
+            async move {
+                dispatcher
+                    .handle_all(&test_ctx("/slow a", "alice", None))
+                    .await
+                    .unwrap()
+            }
+        });
+        while hits.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        engine.disable_plugin("switchable").await.unwrap();
+        assert_eq!(*stopped.lock().unwrap(), 1);
+        assert!(
+            !dispatcher
+                .handle_all(&test_ctx("hello", "bob", None))
+                .await
+                .unwrap()
+        );
+        assert_eq!(*handled.lock().unwrap(), 0);
+
+        gate.add_permits(1);
+        assert!(slow.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn disabling_a_busy_plugin_stops_it_after_its_handlers_finish() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let gate = Arc::new(tokio::sync::Semaphore::new(0));
+        let state = PluginRuntimeState::default();
+        let mut engine = RuntimePluginEngine::new(RuntimePluginServices::new(), state.clone());
+        engine.push(Box::new(GatedPlugin {
+            concurrency: ConcurrencyPolicy::Parallel,
+            hits: hits.clone(),
+            gate: gate.clone(),
+        }));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let dispatcher = engine.dispatcher();
+        let slow = tokio::spawn(async move {
+            dispatcher
+                .handle_all(&test_ctx("/slow a", "alice", None))
+                .await
+                .unwrap()
+        });
+        while hits.lock().unwrap().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        tokio::time::timeout(Duration::from_secs(1), engine.disable_plugin("gated"))
+            .await
+            .expect("disable must not wait for the in-flight handler")
+            .unwrap();
+        assert_eq!(
+            state.snapshot("gated").lifecycle_state,
+            PluginLifecycleState::Stopping
+        );
+        assert!(!engine.plugin_snapshots()[0].lifecycle.enabled);
+
+        gate.add_permits(1);
+        assert!(slow.await.unwrap());
+        engine.stop_all().await.unwrap();
+        assert_eq!(
+            state.snapshot("gated").lifecycle_state,
+            PluginLifecycleState::Stopped
+        );
+    }
+
     #[tokio::test]
     async fn disabled_plugin_is_not_preflighted_or_initialized() {
         let services = RuntimePluginServices::new();
@@ -2909,6 +5361,67 @@ mod tests {
         assert_eq!(*hits.lock().unwrap(), vec!["first"]);
     }
 
+    #[tokio::test]
+    async fn rate_limited_handlers_only_skip_themselves_unless_they_block() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for (instance_id, priority, block_decl, limited) in [
+            ("limited", 0, false, true),
+            ("later", 10, false, false),
+            ("blocking", 20, true, true),
+            ("never", 30, false, false),
+        ] {
+            let mut handler = HandlerDecl::wildcard_message();
+            if limited {
+                // No sender: the cooldown notice fails without failing dispatch.
+                handler = handler.rate_limit(
+                    RateLimit::new(1, Duration::from_secs(3600)).action(RateLimitAction::Reply),
+                );
+            }
+            engine.push(Box::new(PriorityPlugin {
+                instance_id,
+                priority,
+                block_decl,
+                handler,
+                manifest: RuntimePluginManifest::new(instance_id),
+                hits: hits.clone(),
+            }));
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        assert!(
+            engine
+                .handle_all(&test_ctx("hi", "user", None))
+                .await
+                .unwrap()
+        );
+        assert_eq!(
+            std::mem::take(&mut *hits.lock().unwrap()),
+            vec!["limited", "later", "blocking"]
+        );
+
+        let report = engine
+            .dispatch(&test_ctx("hi", "user", None))
+            .await
+            .unwrap();
+        assert!(report.blocked);
+        assert_eq!(*hits.lock().unwrap(), vec!["later"]);
+        let skipped: Vec<_> = report
+            .handlers
+            .iter()
+            .filter(|handler| {
+                matches!(
+                    handler.status,
+                    HandlerStatus::Skipped(SkipReason::RateLimited)
+                )
+            })
+            .map(|handler| handler.instance_id.as_str())
+            .collect();
+        assert_eq!(skipped, vec!["limited", "blocking"]);
+    }
+
     #[tokio::test]
     async fn runtime_plugin_engine_filters_by_regex_and_permission() {
         let services = RuntimePluginServices::new();
@@ -2943,6 +5456,405 @@ mod tests {
         assert_eq!(*hits.lock().unwrap(), vec!["regex"]);
     }
 
+    #[tokio::test]
+    async fn regex_routes_keep_priority_order_and_run_once_per_handler() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for (instance_id, priority, patterns) in [
+            ("late", 10, vec!["world"]),
+            ("both", 0, vec!["^hello", "world$"]),
+            ("early", -5, vec!["o w"]),
+            ("miss", -10, vec!["^bye"]),
+        ] {
+            engine.push(Box::new(PriorityPlugin {
+                instance_id,
+                priority,
+                block_decl: false,
+                handler: HandlerDecl::message_regex(patterns),
+                manifest: RuntimePluginManifest::new(instance_id),
+                hits: hits.clone(),
+            }));
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        engine
+            .handle_all(&test_ctx("hello world", "user", None))
+            .await
+            .unwrap();
+
+        assert_eq!(*hits.lock().unwrap(), vec!["early", "both", "late"]);
+    }
+
+    #[tokio::test]
+    async fn text_matchers_route_by_keyword_affix_and_full_match() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for (instance_id, handler) in [
+            (
+                "keyword",
+                HandlerDecl::message_keywords(["早安", "morning"]),
+            ),
+            ("prefix", HandlerDecl::message_starts_with(["查询"])),
+            ("suffix", HandlerDecl::message_ends_with(["吗"])),
+            ("full", HandlerDecl::message_full_match(["ping"])),
+            (
+                "folded",
+                HandlerDecl::message_keywords(["MORNING"]).ignore_case(),
+            ),
+            ("mixed", {
+                let mut decl = HandlerDecl::message_text([
+                    TextMatcher::full_match("PING").ignore_case(),
+                    TextMatcher::keyword("ping"),
+                ]);
+                decl.regex_patterns.push("^pi".to_string());
+                decl
+            }),
+        ] {
+            engine.push(Box::new(PriorityPlugin {
+                instance_id,
+                priority: 0,
+                block_decl: false,
+                handler,
+                manifest: RuntimePluginManifest::new(instance_id),
+                hits: hits.clone(),
+            }));
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let mut routed = Vec::new();
+        for text in [
+            "大家早安",
+            "Good Morning",
+            "good morning",
+            "查询天气",
+            "天气查询",
+            "今天下雨吗",
+            "ping",
+            "ping pong",
+        ] {
+            engine
+                .handle_all(&test_ctx(text, "user", None))
+                .await
+                .unwrap();
+            routed.push(std::mem::take(&mut *hits.lock().unwrap()).join(","));
+        }
+
+        assert_eq!(
+            routed,
+            vec![
+                "keyword",
+                "folded",
+                "keyword,folded",
+                "prefix",
+                "",
+                "suffix",
+                "full,mixed",
+                "mixed",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn event_filters_skip_handlers_outside_their_channels_platforms_and_bots() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for (instance_id, handler) in [
+            (
+                "group",
+                HandlerDecl::wildcard_message().channel_kinds([ChannelKind::Group]),
+            ),
+            (
+                "direct",
+                HandlerDecl::wildcard_message().channel_kinds([ChannelKind::Direct]),
+            ),
+            (
+                "onebot",
+                HandlerDecl::wildcard_message().platforms(["onebot"]),
+            ),
+            (
+                "test-bot",
+                HandlerDecl::wildcard_message().bots(["test-bot"]),
+            ),
+            ("to-me", HandlerDecl::wildcard_message().to_me()),
+        ] {
+            engine.push(Box::new(PriorityPlugin {
+                instance_id,
+                priority: 0,
+                block_decl: false,
+                handler,
+                manifest: RuntimePluginManifest::new(instance_id),
+                hits: hits.clone(),
+            }));
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let mentioned = {
+            let platform = PlatformId::new("test");
+            let message = crate::core::model::MessageEvent::new(
+                UserRef::new(platform.clone(), "user"),
+                ChannelRef::group(platform.clone(), "g1"),
+                "hi",
+            )
+            .with_segments(vec![
+                MessageSegment::Mention {
+                    user_id: "test-bot".to_string(),
+                },
+                MessageSegment::text(" hi"),
+            ]);
+            Context::new(
+                EventEnvelope::new(BotId::new("test-bot"), platform).with_message(message),
+                None,
+                (),
+            )
+        };
+        let mut routed = Vec::new();
+        for ctx in [
+            test_ctx("hi", "user", Some("g1")),
+            test_ctx("hi", "user", None),
+            mentioned,
+        ] {
+            let report = engine.dispatch(&ctx).await.unwrap();
+            assert_eq!(report.handlers.len(), hits.lock().unwrap().len());
+            routed.push(std::mem::take(&mut *hits.lock().unwrap()).join(","));
+        }
+
+        assert_eq!(
+            routed,
+            vec![
+                "group,test-bot",
+                "direct,test-bot,to-me",
+                "group,test-bot,to-me"
+            ]
+        );
+    }
+
+    struct CapturePlugin {
+        matches: Arc<std::sync::Mutex<Vec<RegexMatch>>>,
+    }
+
+    #[async_trait]
+    impl RuntimePlugin for CapturePlugin {
+        fn kind(&self) -> &'static str {
+            "capture"
+        }
+
+        fn declared_handlers(&self) -> Vec<HandlerDecl> {
+            vec![HandlerDecl::message_regex([
+                r"^weather (?<city>\w+)(?: (\d+)d)?$",
+                r"^(?<city>\w+) weather$",
+            ])]
+        }
+
+        async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+            Ok(HandleOutcome::pass())
+        }
+
+        async fn handle_regex(
+            &self,
+            _ctx: &Context,
+            regex_match: RegexMatch,
+        ) -> Result<HandleOutcome> {
+            self.matches.lock().unwrap().push(regex_match);
+            Ok(HandleOutcome::pass())
+        }
+    }
+
+    #[tokio::test]
+    async fn regex_handlers_receive_the_pattern_and_its_captures() {
+        let matches = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        engine.push(Box::new(CapturePlugin {
+            matches: matches.clone(),
+        }));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        for text in ["weather paris 3d", "weather oslo", "tokyo weather"] {
+            engine
+                .handle_all(&test_ctx(text, "user", None))
+                .await
+                .unwrap();
+        }
+
+        let matches = matches.lock().unwrap();
+        assert_eq!(matches.len(), 3);
+        assert_eq!(matches[0].pattern(), r"^weather (?<city>\w+)(?: (\d+)d)?$");
+        assert_eq!(matches[0].matched(), "weather paris 3d");
+        assert_eq!(matches[0].name("city"), Some("paris"));
+        assert_eq!(matches[0].get(2), Some("3"));
+        assert_eq!(matches[1].name("city"), Some("oslo"));
+        assert_eq!(matches[1].get(2), None);
+        assert_eq!(matches[2].pattern(), r"^(?<city>\w+) weather$");
+        assert_eq!(matches[2].name("city"), Some("tokyo"));
+    }
+
+    #[tokio::test]
+    async fn runtime_plugin_engine_routes_platform_events_by_kind() {
+        let services = RuntimePluginServices::new();
+        let state = PluginRuntimeState::default();
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut engine = RuntimePluginEngine::new(services, state);
+        engine.push(Box::new(PriorityPlugin {
+            instance_id: "message",
+            priority: 0,
+            block_decl: false,
+            handler: HandlerDecl::wildcard_message(),
+            manifest: RuntimePluginManifest::new("message"),
+            hits: hits.clone(),
+        }));
+        engine.push(Box::new(PriorityPlugin {
+            instance_id: "notice",
+            priority: 0,
+            block_decl: false,
+            handler: HandlerDecl::event(HandlerEventKind::Notice),
+            manifest: RuntimePluginManifest::new("notice"),
+            hits: hits.clone(),
+        }));
+        engine.push(Box::new(PriorityPlugin {
+            instance_id: "any",
+            priority: 0,
+            block_decl: false,
+            handler: HandlerDecl::event(HandlerEventKind::Any),
+            manifest: RuntimePluginManifest::new("any"),
+            hits: hits.clone(),
+        }));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let platform = PlatformId::new("test");
+        let notice = PlatformEvent::new(PlatformEventKind::Notice, "group_increase")
+            .with_user(UserRef::new(platform.clone(), "10001"))
+            .with_channel(ChannelRef::group(platform.clone(), "g1"));
+        let ctx = Context::new(
+            EventEnvelope::new(BotId::new("test-bot"), platform).with_platform_event(notice),
+            None,
+            (),
+        );
+        engine.handle_all(&ctx).await.unwrap();
+        assert_eq!(*hits.lock().unwrap(), vec!["notice", "any"]);
+
+        hits.lock().unwrap().clear();
+        engine
+            .handle_all(&test_ctx("hello", "user", None))
+            .await
+            .unwrap();
+        assert_eq!(*hits.lock().unwrap(), vec!["message", "any"]);
+
+        hits.lock().unwrap().clear();
+        let heartbeat = Context::new(
+            EventEnvelope::new(BotId::new("test-bot"), PlatformId::new("test"))
+                .with_platform_event(PlatformEvent::new(PlatformEventKind::Meta, "heartbeat")),
+            None,
+            (),
+        );
+        engine.handle_all(&heartbeat).await.unwrap();
+        assert!(hits.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn runtime_plugin_engine_routes_kernel_events_across_adapters() {
+        let services = RuntimePluginServices::new();
+        let state = PluginRuntimeState::default();
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut engine = RuntimePluginEngine::new(services, state);
+        engine.push(Box::new(PriorityPlugin {
+            instance_id: "welcome",
+            priority: 0,
+            block_decl: false,
+            handler: HandlerDecl::event(HandlerEventKind::Kernel(KernelEventKind::MemberJoined)),
+            manifest: RuntimePluginManifest::new("welcome"),
+            hits: hits.clone(),
+        }));
+        engine.push(Box::new(PriorityPlugin {
+            instance_id: "notice",
+            priority: 1,
+            block_decl: false,
+            handler: HandlerDecl::event(HandlerEventKind::Notice),
+            manifest: RuntimePluginManifest::new("notice"),
+            hits: hits.clone(),
+        }));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let platform = PlatformId::new("test");
+        let joined = KernelEvent::MemberJoined {
+            channel: ChannelRef::group(platform.clone(), "g1"),
+            user: UserRef::new(platform.clone(), "10001"),
+            operator: None,
+        };
+        let ctx = Context::new(
+            EventEnvelope::new(BotId::new("test-bot"), platform.clone())
+                .with_kernel_event(joined.clone()),
+            None,
+            (),
+        );
+        engine.handle_all(&ctx).await.unwrap();
+        assert_eq!(*hits.lock().unwrap(), vec!["welcome"]);
+        assert_eq!(ctx.user_id(), "10001");
+        assert_eq!(ctx.group_id().as_deref(), Some("g1"));
+
+        hits.lock().unwrap().clear();
+        let native = Context::new(
+            EventEnvelope::new(BotId::new("test-bot"), platform)
+                .with_platform_event(PlatformEvent::new(
+                    PlatformEventKind::Notice,
+                    "group_increase",
+                ))
+                .with_kernel_event(joined),
+            None,
+            (),
+        );
+        engine.handle_all(&native).await.unwrap();
+        assert_eq!(*hits.lock().unwrap(), vec!["welcome", "notice"]);
+    }
+
+    #[tokio::test]
+    async fn lifecycle_events_do_not_reach_wildcard_message_handlers() {
+        let hits = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        for (instance_id, handler) in [
+            ("message", HandlerDecl::wildcard_message()),
+            (
+                "connected",
+                HandlerDecl::event(HandlerEventKind::Kernel(KernelEventKind::BotConnected)),
+            ),
+            ("any", HandlerDecl::event(HandlerEventKind::Any)),
+        ] {
+            engine.push(Box::new(PriorityPlugin {
+                instance_id,
+                priority: 0,
+                block_decl: false,
+                handler,
+                manifest: RuntimePluginManifest::new(instance_id),
+                hits: hits.clone(),
+            }));
+        }
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        for event in [KernelEvent::BotConnected, KernelEvent::BotDisconnected] {
+            let ctx = Context::new(
+                EventEnvelope::new(BotId::new("test-bot"), PlatformId::new("test"))
+                    .with_kernel_event(event),
+                None,
+                (),
+            );
+            engine.handle_all(&ctx).await.unwrap();
+        }
+
+        assert_eq!(*hits.lock().unwrap(), vec!["connected", "any", "any"]);
+    }
+
     #[tokio::test]
     async fn runtime_plugin_engine_uses_context_ids_for_permission_checks() {
         let services = RuntimePluginServices::new();
@@ -13,7 +13,8 @@ pub mod core;
 ))]
 pub mod driver;
 
-pub use ayiou_macros::{command, plugin};
+pub use anyhow;
+pub use ayiou_macros::{Form, command, plugin, regex};
 #[cfg(feature = "adapter-console")]
 pub use bot::ConsoleBot;
 #[cfg(feature = "adapter-onebot-v11")]
@@ -18,7 +18,7 @@ use ayiou::{
     },
 };
 use serde_json::Value;
-use tokio::sync::RwLock;
+use tokio::sync::Mutex;
 use tower::ServiceExt;
 
 struct TestPlugin;
@@ -38,7 +38,7 @@ fn app() -> Router {
     let state = PluginRuntimeState::default();
     let mut engine = RuntimePluginEngine::new(services, state);
     engine.push_as("test-plugin", Box::new(TestPlugin));
-    let handle = RuntimeControlHandle::new(Arc::new(RwLock::new(engine)));
+    let handle = RuntimeControlHandle::new(Arc::new(Mutex::new(engine)));
     control_plane::router(handle, "secret")
 }
 
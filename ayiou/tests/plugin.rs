@@ -7,12 +7,20 @@ use std::time::Duration;
 use anyhow::Result;
 use async_trait::async_trait;
 use ayiou::core::adapter::{Adapter, AdapterRuntime};
-use ayiou::core::model::{BotId, ChannelRef, EventEnvelope, MessageEvent, PlatformId, UserRef};
+use ayiou::core::command::{ArgsParseError, ParseErrorPolicy};
+use ayiou::core::help::{HelpOptions, SuggestionOptions};
+use ayiou::core::middleware::{HandlerInfo, Middleware, MiddlewareFlow};
+use ayiou::core::model::{
+    BotId, ChannelRef, EventEnvelope, MessageEvent, OutboundMessage, OutboundReceipt, PlatformId,
+    UserRef,
+};
 use ayiou::core::plugin::{
-    ApplyConfigOutcome, CommandMeta, ConfigUpdate, ConversationKey, ConversationStore,
-    HandleOutcome, HandlerDecl, MemoryConversationStore, Permission, PermissionDecision,
-    PermissionService, PluginRuntimeState, RuntimePlugin, RuntimePluginEngine,
-    RuntimePluginManifest, RuntimePluginServices,
+    ApplyConfigOutcome, CircuitBreaker, CommandMeta, ConfigUpdate, ConversationKey,
+    ConversationStore, DispatchErrorPolicy, DispatchReport, HandleOutcome, HandlerDecl,
+    HandlerStatus, MemoryConversationStore, OutboundSender, Permission, PermissionDecision,
+    PermissionService, PluginLifecycleState, PluginRuntimeState, RateLimit, RateLimitAction,
+    RateLimitScope, RuntimePlugin, RuntimePluginEngine, RuntimePluginManifest,
+    RuntimePluginServices, SkipReason,
 };
 use ayiou::core::service::{RuntimeService, ServiceRegistry};
 use ayiou::plugin;
@@ -112,6 +120,308 @@ impl MetadataPlugin {
     }
 }
 
+struct MathPlugin;
+
+#[plugin(name = "math", prefix = "/", register = false)]
+impl MathPlugin {
+    #[command(summary = "Add two numbers", examples = ["/add 1 2"])]
+    async fn add(&self, _ctx: &Context, _left: i64, _right: i64) -> Result<()> {
+        Ok(())
+    }
+
+    /// Negate a number.
+    ///
+    /// Not part of the summary.
+    #[command(parse_error = "silent")]
+    async fn neg(&self, _ctx: &Context, _value: i64) -> Result<()> {
+        Ok(())
+    }
+
+    #[command(parse_error = "propagate")]
+    async fn sqrt(&self, _ctx: &Context, _value: f64, _precision: Option<u8>) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct LenientMathPlugin;
+
+#[plugin(name = "lenient-math", prefix = "/", register = false)]
+impl LenientMathPlugin {
+    #[command(block = false)]
+    async fn double(&self, _ctx: &Context, _value: i64) -> Result<()> {
+        Ok(())
+    }
+
+    #[command(block = false, parse_error = "silent")]
+    async fn half(&self, _ctx: &Context, _value: i64) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct AdminPlugin {
+    seen: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[plugin(name = "admin", prefix = "/", register = false)]
+impl AdminPlugin {
+    /// Ban a user.
+    #[command(group = "admin", name = "ban", permissions = ["admin"])]
+    async fn ban(&self, _ctx: &Context, user_id: i64) -> Result<()> {
+        self.seen.lock().unwrap().push(format!("ban {user_id}"));
+        Ok(())
+    }
+
+    /// Mute a user.
+    #[command(group = "admin", alias = "silence")]
+    async fn mute(&self, _ctx: &Context, user_id: i64, minutes: Option<u32>) -> Result<()> {
+        let minutes = minutes.unwrap_or(10);
+        self.seen
+            .lock()
+            .unwrap()
+            .push(format!("mute {user_id} {minutes}"));
+        Ok(())
+    }
+
+    #[command(group = "admin", permissions = ["owner"])]
+    async fn kick(&self, _ctx: &Context, user_id: i64) -> Result<()> {
+        self.seen.lock().unwrap().push(format!("kick {user_id}"));
+        Ok(())
+    }
+
+    async fn status(&self, _ctx: &Context) -> Result<()> {
+        self.seen.lock().unwrap().push("status".to_string());
+        Ok(())
+    }
+}
+
+struct QuotaPlugin {
+    calls: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[plugin(name = "quota", prefix = "/", register = false)]
+impl QuotaPlugin {
+    #[command(cooldown = "1m", rate_limit_action = "reply")]
+    async fn draw(&self, ctx: &Context) -> Result<()> {
+        self.calls
+            .lock()
+            .unwrap()
+            .push(format!("draw {}", ctx.user_id()));
+        Ok(())
+    }
+
+    #[command(rate_limit = "2/1h", rate_limit_scope = "global")]
+    async fn search(&self, _ctx: &Context, query: String) -> Result<()> {
+        self.calls.lock().unwrap().push(format!("search {query}"));
+        Ok(())
+    }
+
+    #[command(rate_limit = "1/50ms")]
+    async fn ping(&self, _ctx: &Context) -> Result<()> {
+        self.calls.lock().unwrap().push("ping".to_string());
+        Ok(())
+    }
+}
+
+struct SlowPlugin;
+
+#[plugin(name = "slow", prefix = "/", register = false)]
+impl SlowPlugin {
+    #[command(timeout = "20ms")]
+    async fn hang(&self, _ctx: &Context) -> Result<()> {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        Ok(())
+    }
+
+    async fn nap(&self, _ctx: &Context, millis: u64) -> Result<()> {
+        tokio::time::sleep(Duration::from_millis(millis)).await;
+        Ok(())
+    }
+}
+
+struct FlakyPlugin;
+
+#[plugin(name = "flaky", prefix = "/", register = false)]
+impl FlakyPlugin {
+    async fn boom(&self, _ctx: &Context) -> Result<()> {
+        panic!("boom");
+    }
+
+    #[command(on_error = "continue")]
+    async fn fail(&self, _ctx: &Context) -> Result<()> {
+        anyhow::bail!("backend down")
+    }
+
+    async fn ok(&self, _ctx: &Context) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct BrokenListener(Option<DispatchErrorPolicy>);
+
+#[async_trait]
+impl RuntimePlugin for BrokenListener {
+    fn kind(&self) -> &'static str {
+        "broken-listener"
+    }
+
+    fn declared_handlers(&self) -> Vec<HandlerDecl> {
+        let decl = HandlerDecl::wildcard_message().priority(-1);
+        vec![match self.0 {
+            Some(policy) => decl.on_error(policy),
+            None => decl,
+        }]
+    }
+
+    async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+        anyhow::bail!("listener broke")
+    }
+}
+
+struct CountingListener(Arc<AtomicUsize>);
+
+#[async_trait]
+impl RuntimePlugin for CountingListener {
+    fn kind(&self) -> &'static str {
+        "counting-listener"
+    }
+
+    fn declared_handlers(&self) -> Vec<HandlerDecl> {
+        vec![HandlerDecl::wildcard_message()]
+    }
+
+    async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(HandleOutcome::pass())
+    }
+}
+
+#[derive(Clone, Default)]
+struct Trace(Arc<std::sync::Mutex<Vec<String>>>);
+
+impl Trace {
+    fn push(&self, entry: impl Into<String>) {
+        self.0.lock().unwrap().push(entry.into());
+    }
+
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+struct Locale(&'static str);
+
+struct Blocklist(Trace);
+
+#[async_trait]
+impl Middleware for Blocklist {
+    async fn before_event(&self, ctx: &mut Context) -> Result<MiddlewareFlow> {
+        self.0.push("blocklist");
+        Ok(if ctx.user_id() == "spammer" {
+            MiddlewareFlow::Stop
+        } else {
+            MiddlewareFlow::Continue
+        })
+    }
+}
+
+struct LocaleSetup(Trace);
+
+#[async_trait]
+impl Middleware for LocaleSetup {
+    async fn before_event(&self, ctx: &mut Context) -> Result<MiddlewareFlow> {
+        self.0.push("locale");
+        ctx.set_extension(Locale("zh"));
+        Ok(MiddlewareFlow::Continue)
+    }
+}
+
+struct Audit(Trace);
+
+#[async_trait]
+impl Middleware for Audit {
+    async fn before_event(&self, _ctx: &mut Context) -> Result<MiddlewareFlow> {
+        self.0.push("audit");
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    async fn after_event(&self, _ctx: &Context, report: &DispatchReport) {
+        self.0
+            .push(format!("audit done, blocked = {}", report.blocked));
+    }
+
+    async fn before_handler(
+        &self,
+        _ctx: &Context,
+        handler: HandlerInfo<'_>,
+    ) -> Result<MiddlewareFlow> {
+        if handler.command == Some("secret") {
+            self.0.push("skip secret");
+            return Ok(MiddlewareFlow::Stop);
+        }
+        Ok(MiddlewareFlow::Continue)
+    }
+
+    async fn after_handler(
+        &self,
+        _ctx: &Context,
+        handler: HandlerInfo<'_>,
+        result: &Result<HandleOutcome>,
+    ) {
+        self.0.push(format!(
+            "{}/{} ok = {}",
+            handler.instance_id,
+            handler.command.unwrap_or("-"),
+            result.is_ok()
+        ));
+    }
+}
+
+struct GreetPlugin {
+    trace: Trace,
+}
+
+#[plugin(name = "greet", prefix = "/", register = false)]
+impl GreetPlugin {
+    async fn greet(&self, ctx: &Context) -> Result<()> {
+        let locale = ctx.extension::<Locale>().map_or("none", |locale| locale.0);
+        self.trace.push(format!("greet in {locale}"));
+        Ok(())
+    }
+
+    async fn secret(&self, _ctx: &Context) -> Result<()> {
+        self.trace.push("secret ran");
+        Ok(())
+    }
+}
+
+struct RecordingSender(std::sync::Mutex<Vec<String>>);
+
+#[async_trait]
+impl OutboundSender for RecordingSender {
+    async fn send(&self, message: OutboundMessage) -> Result<OutboundReceipt> {
+        self.0.lock().unwrap().push(message.plain_text());
+        Ok(OutboundReceipt::default())
+    }
+}
+
+fn recorded_context(text: &str, sender: &Arc<RecordingSender>) -> Context {
+    recorded_context_from("user", text, sender)
+}
+
+fn recorded_context_from(user_id: &str, text: &str, sender: &Arc<RecordingSender>) -> Context {
+    let platform = PlatformId::new("test");
+    let message = MessageEvent::new(
+        UserRef::new(platform.clone(), user_id),
+        ChannelRef::direct(platform.clone(), user_id),
+        text,
+    );
+    Context::new(
+        EventEnvelope::new(BotId::new("test-bot"), platform).with_message(message),
+        Some(sender.clone()),
+        (),
+    )
+}
+
 struct AllowAdminService;
 
 impl RuntimeService for AllowAdminService {
@@ -279,6 +589,41 @@ impl RuntimePlugin for HandlePlugin {
     }
 }
 
+struct MathFallbackPlugin {
+    handles: Arc<AtomicUsize>,
+}
+
+#[async_trait]
+impl RuntimePlugin for MathFallbackPlugin {
+    fn kind(&self) -> &'static str {
+        "math-fallback"
+    }
+    fn declared_handlers(&self) -> Vec<HandlerDecl> {
+        vec![HandlerDecl::message_commands(["double", "half"], ["/"])]
+    }
+
+    async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+        self.handles.fetch_add(1, Ordering::SeqCst);
+        Ok(HandleOutcome::pass())
+    }
+}
+
+struct DicePlugin;
+
+#[async_trait]
+impl RuntimePlugin for DicePlugin {
+    fn kind(&self) -> &'static str {
+        "dice"
+    }
+    fn declared_handlers(&self) -> Vec<HandlerDecl> {
+        vec![HandlerDecl::message_commands(["roll"], ["!"])]
+    }
+
+    async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+        Ok(HandleOutcome::pass())
+    }
+}
+
 struct TestAclService {
     allowed_user: String,
 }
@@ -482,3 +827,522 @@ async fn runtime_applies_structured_plugin_config_values() {
     assert_eq!(outcome, ApplyConfigOutcome::applied(7));
     assert_eq!(*default_city.lock().unwrap(), Some("Taipei".to_string()));
 }
+
+#[tokio::test]
+async fn argument_parse_errors_follow_the_command_policy() {
+    let handlers = RuntimePlugin::declared_handlers(&MathPlugin);
+    assert_eq!(handlers[0].command_meta[0].usage, "/add <left> <right>");
+    assert_eq!(handlers[1].parse_error, ParseErrorPolicy::Silent);
+    assert_eq!(
+        handlers[2].command_meta[0].usage,
+        "/sqrt <value> [precision]"
+    );
+
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+    engine.push(Box::new(MathPlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    let context = |text: &str| recorded_context(text, &sender);
+
+    assert!(engine.handle_all(&context("/add 1 two")).await.unwrap());
+    assert!(engine.handle_all(&context("/neg")).await.unwrap());
+    assert_eq!(
+        *sender.0.lock().unwrap(),
+        vec![
+            "Failed to parse argument `right`: invalid digit found in string\n\
+             Usage: /add <left> <right>\nExamples:\n  /add 1 2"
+                .to_string()
+        ]
+    );
+
+    let Err(err) = engine.handle_all(&context("/sqrt")).await else {
+        panic!("sqrt propagates parse errors");
+    };
+    let parse_err = err.downcast_ref::<ArgsParseError>().unwrap();
+    assert_eq!(parse_err.message(), "Missing argument: value");
+    assert_eq!(parse_err.help(), Some("Usage: /sqrt <value> [precision]"));
+    assert_eq!(err.to_string(), "Missing argument: value");
+}
+
+#[tokio::test]
+async fn parse_errors_in_non_blocking_commands_reach_later_handlers() {
+    let handles = Arc::new(AtomicUsize::new(0));
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+    engine.push(Box::new(LenientMathPlugin));
+    engine.push(Box::new(MathFallbackPlugin {
+        handles: handles.clone(),
+    }));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    for text in ["/double two", "/half two"] {
+        let report = engine
+            .dispatch(&recorded_context(text, &sender))
+            .await
+            .unwrap();
+        assert!(!report.blocked);
+        assert!(matches!(
+            report.handlers[0].status,
+            HandlerStatus::InvalidArgs
+        ));
+    }
+
+    assert_eq!(handles.load(Ordering::SeqCst), 2);
+    assert_eq!(sender.0.lock().unwrap().len(), 1);
+
+    // A reply that cannot be sent still leaves the handler as InvalidArgs.
+    let silent = Context::new(
+        EventEnvelope::new(BotId::new("test-bot"), PlatformId::new("test")).with_message(
+            MessageEvent::new(
+                UserRef::new("test", "user"),
+                ChannelRef::direct("test", "user"),
+                "/double two",
+            ),
+        ),
+        None,
+        (),
+    );
+    let report = engine.dispatch(&silent).await.unwrap();
+    assert!(matches!(
+        report.handlers[0].status,
+        HandlerStatus::InvalidArgs
+    ));
+    assert_eq!(handles.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn help_lists_enabled_commands_the_user_may_run() {
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default())
+            .with_help(HelpOptions::new());
+    engine.push(Box::new(MathPlugin));
+    engine.push(Box::new(MetadataPlugin));
+    engine.push_as("auto", Box::new(AutoDiscoveredPlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+    engine.disable_plugin("auto").await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    for text in ["/help", "/help add", "/help /weather", "help"] {
+        engine
+            .handle_all(&recorded_context(text, &sender))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(
+        *sender.0.lock().unwrap(),
+        vec![
+            "Available commands:\n\
+             math\n  \
+             /add  Add two numbers\n  \
+             /neg  Negate a number.\n  \
+             /sqrt\n\
+             Send /help <command> for details."
+                .to_string(),
+            "/add\nAdd two numbers\nUsage: /add <left> <right>\nExamples:\n  /add 1 2".to_string(),
+            "Unknown command `/weather`. Send /help for the list.".to_string(),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn unknown_prefixed_commands_get_visible_suggestions() {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let permission_service: Arc<dyn PermissionService> = Arc::new(AllowAdminService);
+    let mut engine = RuntimePluginEngine::new(
+        RuntimePluginServices::new().with_permission_service(Some(permission_service)),
+        PluginRuntimeState::default(),
+    )
+    .with_help(HelpOptions::new())
+    .with_suggestions(SuggestionOptions::new());
+    engine.push(Box::new(AdminPlugin { seen: seen.clone() }));
+    engine.push(Box::new(MathPlugin));
+    engine.push(Box::new(DicePlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    let mut handled = Vec::new();
+    for text in [
+        "/stauts",
+        "/ad 1 2",
+        "/hepl",
+        "/admin bam 1",
+        "/admin kik 1",
+        "/zzz",
+        "stauts",
+        "/rol",
+        "!rol",
+    ] {
+        handled.push(
+            engine
+                .handle_all(&recorded_context(text, &sender))
+                .await
+                .unwrap(),
+        );
+    }
+
+    assert!(seen.lock().unwrap().is_empty());
+    assert_eq!(
+        handled,
+        [true, true, true, true, false, false, false, false, true]
+    );
+    assert_eq!(
+        *sender.0.lock().unwrap(),
+        vec![
+            "Unknown command /stauts. Did you mean /status?",
+            "Unknown command /ad. Did you mean /add?",
+            "Unknown command /hepl. Did you mean /help?",
+            "Unknown command /admin bam. Did you mean /admin ban?",
+            "Unknown command !rol. Did you mean !roll?",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rate_limited_commands_are_dropped_or_answered_with_the_wait() {
+    let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let plugin = QuotaPlugin {
+        calls: calls.clone(),
+    };
+    let handlers = RuntimePlugin::declared_handlers(&plugin);
+    assert_eq!(
+        handlers[0].rate_limits,
+        vec![RateLimit::cooldown(Duration::from_secs(60)).action(RateLimitAction::Reply)]
+    );
+    assert_eq!(
+        handlers[1].rate_limits,
+        vec![RateLimit::new(2, Duration::from_secs(3600)).scope(RateLimitScope::Global)]
+    );
+
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+    engine.push(Box::new(plugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    for (user_id, text) in [
+        ("alice", "/draw"),
+        ("alice", "/draw"),
+        ("bob", "/draw"),
+        ("alice", "/search a"),
+        ("bob", "/search b"),
+        ("carol", "/search c"),
+        ("alice", "/ping"),
+        ("alice", "/ping"),
+    ] {
+        assert!(
+            engine
+                .handle_all(&recorded_context_from(user_id, text, &sender))
+                .await
+                .unwrap()
+        );
+    }
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    engine
+        .handle_all(&recorded_context_from("alice", "/ping", &sender))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        *calls.lock().unwrap(),
+        vec![
+            "draw alice",
+            "draw bob",
+            "search a",
+            "search b",
+            "ping",
+            "ping"
+        ]
+    );
+    assert_eq!(
+        *sender.0.lock().unwrap(),
+        vec!["This command is on cooldown, try again in 60s."]
+    );
+}
+
+#[tokio::test]
+async fn handlers_past_their_timeout_are_cancelled_and_recorded() {
+    let handlers = RuntimePlugin::declared_handlers(&SlowPlugin);
+    assert_eq!(handlers[0].timeout, Some(Duration::from_millis(20)));
+    assert_eq!(handlers[1].timeout, None);
+
+    let state = PluginRuntimeState::default();
+    let mut engine = RuntimePluginEngine::new(RuntimePluginServices::new(), state.clone())
+        .with_handler_timeout(Duration::from_millis(200));
+    engine.push(Box::new(SlowPlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    assert!(
+        engine
+            .handle_all(&recorded_context("/nap 1", &sender))
+            .await
+            .unwrap()
+    );
+    for (text, timeout) in [("/hang", "20ms"), ("/nap 60000", "200ms")] {
+        let Err(err) = engine.handle_all(&recorded_context(text, &sender)).await else {
+            panic!("{text} runs past its timeout");
+        };
+        assert_eq!(
+            err.to_string(),
+            format!("plugin `slow` handler timed out after {timeout}")
+        );
+    }
+
+    let snapshot = state.snapshot("slow");
+    assert_eq!(snapshot.timeouts, 2);
+    assert_eq!(
+        snapshot.last_error.as_deref(),
+        Some("plugin `slow` handler timed out after 200ms")
+    );
+}
+
+#[tokio::test]
+async fn handler_panics_are_recorded_and_trip_the_circuit_breaker() {
+    let state = PluginRuntimeState::default();
+    let mut engine = RuntimePluginEngine::new(RuntimePluginServices::new(), state.clone())
+        .with_circuit_breaker(
+            CircuitBreaker::new(2).with_reenable_after(Duration::from_millis(50)),
+        );
+    engine.push(Box::new(FlakyPlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let Err(err) = engine.handle_all(&test_context("/boom")).await else {
+        panic!("the handler panics");
+    };
+    assert_eq!(err.to_string(), "plugin `flaky` handler panicked: boom");
+    let snapshot = state.snapshot("flaky");
+    assert_eq!(snapshot.lifecycle_state, PluginLifecycleState::Failed);
+    assert_eq!(snapshot.consecutive_failures, 1);
+
+    assert!(engine.handle_all(&test_context("/ok")).await.unwrap());
+    assert_eq!(state.snapshot("flaky").consecutive_failures, 0);
+
+    assert!(engine.handle_all(&test_context("/fail")).await.is_err());
+    assert!(engine.handle_all(&test_context("/boom")).await.is_err());
+    let snapshot = &engine.plugin_snapshots()[0];
+    assert_eq!(
+        snapshot.lifecycle.lifecycle_state,
+        PluginLifecycleState::AutoDisabled
+    );
+    assert_eq!(snapshot.lifecycle.consecutive_failures, 2);
+    assert!(snapshot.lifecycle.auto_reenable_at.is_some());
+    assert!(!engine.handle_all(&test_context("/ok")).await.unwrap());
+
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert!(engine.handle_all(&test_context("/ok")).await.unwrap());
+    let snapshot = state.snapshot("flaky");
+    assert_eq!(snapshot.lifecycle_state, PluginLifecycleState::Running);
+    assert_eq!(snapshot.auto_reenable_at, None);
+}
+
+#[tokio::test]
+async fn tripped_plugins_without_a_cooldown_wait_for_enable_plugin() {
+    let state = PluginRuntimeState::default();
+    let mut engine = RuntimePluginEngine::new(RuntimePluginServices::new(), state.clone())
+        .with_circuit_breaker(CircuitBreaker::new(1));
+    engine.push(Box::new(FlakyPlugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    assert!(engine.handle_all(&test_context("/fail")).await.is_err());
+    let snapshot = state.snapshot("flaky");
+    assert_eq!(snapshot.lifecycle_state, PluginLifecycleState::AutoDisabled);
+    assert_eq!(snapshot.auto_reenable_at, None);
+    assert_eq!(snapshot.last_error.as_deref(), Some("backend down"));
+    assert!(!engine.handle_all(&test_context("/ok")).await.unwrap());
+
+    engine.enable_plugin("flaky").await.unwrap();
+    assert_eq!(
+        state.snapshot("flaky").lifecycle_state,
+        PluginLifecycleState::Running
+    );
+    assert!(engine.handle_all(&test_context("/ok")).await.unwrap());
+}
+
+#[tokio::test]
+async fn dispatch_error_policy_decides_whether_later_handlers_run() {
+    async fn dispatch(
+        engine_policy: Option<DispatchErrorPolicy>,
+        handler_policy: Option<DispatchErrorPolicy>,
+    ) -> (DispatchReport, usize) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let mut engine =
+            RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+        if let Some(policy) = engine_policy {
+            engine = engine.with_error_policy(policy);
+        }
+        engine.push(Box::new(BrokenListener(handler_policy)));
+        engine.push(Box::new(CountingListener(hits.clone())));
+        engine.init_all().await.unwrap();
+        engine.start_all().await.unwrap();
+
+        let report = engine.dispatch(&test_context("hello")).await.unwrap();
+        (report, hits.load(Ordering::SeqCst))
+    }
+
+    let (report, hits) = dispatch(None, None).await;
+    assert_eq!(hits, 0);
+    assert_eq!(report.handlers.len(), 1);
+    assert_eq!(report.handlers[0].instance_id, "broken-listener");
+    assert_eq!(
+        report.errors().map(ToString::to_string).collect::<Vec<_>>(),
+        vec!["listener broke"]
+    );
+
+    let (report, hits) = dispatch(Some(DispatchErrorPolicy::Continue), None).await;
+    assert_eq!(hits, 1);
+    assert!(matches!(
+        report.handlers[0].status,
+        HandlerStatus::Failed(_)
+    ));
+    assert_eq!(report.handlers[1].instance_id, "counting-listener");
+    assert!(matches!(
+        report.handlers[1].status,
+        HandlerStatus::Completed(HandleOutcome { block: false })
+    ));
+    assert!(!report.blocked);
+    assert_eq!(
+        report.into_result().unwrap_err().to_string(),
+        "listener broke"
+    );
+
+    let (report, hits) = dispatch(
+        Some(DispatchErrorPolicy::Stop),
+        Some(DispatchErrorPolicy::Continue),
+    )
+    .await;
+    assert_eq!(hits, 1);
+    assert_eq!(report.handlers.len(), 2);
+
+    let (_, hits) = dispatch(
+        Some(DispatchErrorPolicy::Continue),
+        Some(DispatchErrorPolicy::Stop),
+    )
+    .await;
+    assert_eq!(hits, 0);
+
+    let handlers = RuntimePlugin::declared_handlers(&FlakyPlugin);
+    assert_eq!(handlers[0].on_error, None);
+    assert_eq!(handlers[1].on_error, Some(DispatchErrorPolicy::Continue));
+}
+
+#[tokio::test]
+async fn middleware_wraps_events_and_handlers_in_priority_order() {
+    let trace = Trace::default();
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default())
+            .with_middleware(5, Arc::new(Audit(trace.clone())))
+            .with_middleware(-10, Arc::new(Blocklist(trace.clone())))
+            .with_middleware(0, Arc::new(LocaleSetup(trace.clone())));
+    engine.push(Box::new(GreetPlugin {
+        trace: trace.clone(),
+    }));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let report = engine.dispatch(&test_context("/greet")).await.unwrap();
+    assert!(report.blocked);
+    assert_eq!(
+        trace.take(),
+        vec![
+            "blocklist",
+            "locale",
+            "audit",
+            "greet in zh",
+            "greet/greet ok = true",
+            "audit done, blocked = true",
+        ]
+    );
+
+    let report = engine.dispatch(&test_context("/secret")).await.unwrap();
+    assert!(matches!(
+        report.handlers[0].status,
+        HandlerStatus::Skipped(SkipReason::Middleware)
+    ));
+    assert_eq!(
+        trace.take(),
+        vec![
+            "blocklist",
+            "locale",
+            "audit",
+            "skip secret",
+            "audit done, blocked = false",
+        ]
+    );
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    let report = engine
+        .dispatch(&recorded_context_from("spammer", "/greet", &sender))
+        .await
+        .unwrap();
+    assert!(report.blocked);
+    assert!(report.handlers.is_empty());
+    assert_eq!(
+        trace.take(),
+        vec!["blocklist", "audit done, blocked = true"]
+    );
+}
+
+#[tokio::test]
+async fn group_subcommands_route_through_the_engine() {
+    let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let plugin = AdminPlugin { seen: seen.clone() };
+    let handlers = RuntimePlugin::declared_handlers(&plugin);
+    assert_eq!(handlers[0].group.as_deref(), Some("admin"));
+    assert_eq!(
+        handlers[0].command_meta,
+        vec![
+            CommandMeta::new("ban")
+                .group("admin")
+                .summary("Ban a user.")
+                .usage("/admin ban <user_id>")
+        ]
+    );
+
+    let permission_service: Arc<dyn PermissionService> = Arc::new(AllowAdminService);
+    let mut engine = RuntimePluginEngine::new(
+        RuntimePluginServices::new().with_permission_service(Some(permission_service)),
+        PluginRuntimeState::default(),
+    )
+    .with_help(HelpOptions::new());
+    engine.push(Box::new(plugin));
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+
+    let sender = Arc::new(RecordingSender(std::sync::Mutex::new(Vec::new())));
+    for text in [
+        "/admin ban 42",
+        "/admin  silence 7 30",
+        "/admin kick 1",
+        "/admin unban 1",
+        "/status",
+        "/help admin",
+    ] {
+        engine
+            .handle_all(&recorded_context(text, &sender))
+            .await
+            .unwrap();
+    }
+
+    assert_eq!(*seen.lock().unwrap(), vec!["ban 42", "mute 7 30", "status"]);
+    assert_eq!(
+        *sender.0.lock().unwrap(),
+        vec![
+            "/admin subcommands:\n  \
+             /admin ban  Ban a user.\n  \
+             /admin mute  Mute a user."
+                .to_string()
+        ]
+    );
+}
@@ -2,10 +2,15 @@ use std::sync::{Arc, Mutex};
 
 use anyhow::Result;
 use ayiou::Context;
-use ayiou::core::model::{BotId, CommandInvocation, EventEnvelope, PlatformId};
+use ayiou::core::command::{AtBot, Attachment, HumanDuration};
+use ayiou::core::form::{Form, FormAnswers, FormField};
+use ayiou::core::model::{
+    BotId, ChannelKind, ChannelRef, CommandInvocation, EventEnvelope, MessageEvent, MessageSegment,
+    PlatformId, RegexMatch, UserRef,
+};
 use ayiou::core::plugin::{CommandMeta, HandlerDecl, Permission, RuntimePlugin};
 #[allow(unused_imports)]
-use ayiou::{command, plugin};
+use ayiou::{command, plugin, regex};
 
 struct ToolPlugin {
     seen: Arc<Mutex<Vec<String>>>,
@@ -44,13 +49,130 @@ impl SimplePlugin {
     }
 }
 
-fn test_context() -> Context {
+struct GreetPlugin;
+
+#[plugin(name = "greet", prefix = "/", register = false)]
+impl GreetPlugin {
+    #[command(
+        channel = "group",
+        channel = "channel",
+        platform = "onebot",
+        to_me = true
+    )]
+    async fn greet(&self, _ctx: &Context) -> Result<()> {
+        Ok(())
+    }
+
+    #[regex("^gm$")]
+    #[command(channel = "direct", bot = "10001")]
+    async fn gm(&self, _ctx: &Context) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct DicePlugin {
+    rolls: Arc<Mutex<Vec<String>>>,
+}
+
+#[plugin(name = "dice", prefix = "/", register = false)]
+impl DicePlugin {
+    async fn roll(
+        &self,
+        _ctx: &Context,
+        sides: u32,
+        #[arg(short = 'c', default = "1")] count: u32,
+        #[arg(short = 'v')] verbose: bool,
+        #[arg] label: Option<String>,
+    ) -> Result<()> {
+        self.rolls
+            .lock()
+            .unwrap()
+            .push(format!("{sides} {count} {verbose} {label:?}"));
+        Ok(())
+    }
+}
+
+struct ModPlugin {
+    actions: Arc<Mutex<Vec<String>>>,
+}
+
+#[plugin(name = "mod", prefix = "/", register = false)]
+impl ModPlugin {
+    async fn mute(
+        &self,
+        _ctx: &Context,
+        at: AtBot,
+        user: UserRef,
+        duration: HumanDuration,
+        proof: Option<Attachment>,
+    ) -> Result<()> {
+        self.actions.lock().unwrap().push(format!(
+            "{} {} {:?} {:?}",
+            at.0,
+            user.user_id(),
+            duration.0,
+            proof.and_then(|proof| proof.url)
+        ));
+        Ok(())
+    }
+}
+
+struct WeatherPlugin {
+    forecasts: Arc<Mutex<Vec<String>>>,
+}
+
+#[plugin(name = "weather", register = false)]
+impl WeatherPlugin {
+    #[regex(r"^weather (?<city>\w+)(?: (?<days>\d+)d)?$")]
+    #[regex(r"^(?<city>\w+) weather$")]
+    #[command(priority = 5, block = false)]
+    async fn forecast(
+        &self,
+        _ctx: &Context,
+        city: String,
+        days: Option<u8>,
+        matched: RegexMatch,
+    ) -> Result<()> {
+        self.forecasts
+            .lock()
+            .unwrap()
+            .push(format!("{city} {days:?} {}", matched.matched()));
+        Ok(())
+    }
+}
+
+#[derive(ayiou::Form)]
+struct SignupForm {
+    /// What should we call you?
+    name: String,
+    #[form(prompt = "How old are you?", validate = adult)]
+    age: u8,
+    city: String,
+}
+
+fn adult(age: &u8) -> Result<(), String> {
+    if *age >= 18 {
+        Ok(())
+    } else {
+        Err("must be an adult".to_string())
+    }
+}
+
+/// A bare event, or a group message from `alice` when `segments` are given.
+fn test_context(segments: Vec<MessageSegment>) -> Context {
     let platform = PlatformId::new("test");
-    Context::new(
-        EventEnvelope::new(BotId::new("test-bot"), platform),
-        None,
-        (),
-    )
+    let mut envelope = EventEnvelope::new(BotId::new("test-bot"), platform.clone());
+    if !segments.is_empty() {
+        envelope = envelope.with_message(
+            MessageEvent::new(
+                UserRef::new(platform.clone(), "alice"),
+                ChannelRef::group(platform, "g1"),
+                "",
+            )
+            .with_segments(segments),
+        );
+    }
+    Context::new(envelope, None, ())
 }
 
 #[tokio::test]
@@ -78,7 +200,7 @@ async fn plugin_macro_registers_commands_and_dispatches_invocations() {
         ]
     );
 
-    let ctx = test_context();
+    let ctx = test_context(Vec::new());
     let outcome = RuntimePlugin::handle_with_invocation(
         &plugin,
         &ctx,
@@ -105,7 +227,7 @@ async fn plugin_macro_treats_async_methods_as_commands() {
         ]
     );
 
-    let ctx = test_context();
+    let ctx = test_context(Vec::new());
     let outcome = RuntimePlugin::handle_with_invocation(
         &plugin,
         &ctx,
@@ -116,3 +238,237 @@ async fn plugin_macro_treats_async_methods_as_commands() {
 
     assert!(outcome.block);
 }
+
+#[test]
+fn plugin_macro_declares_event_filters() {
+    assert_eq!(
+        RuntimePlugin::declared_handlers(&GreetPlugin),
+        vec![
+            HandlerDecl::message_commands(["greet"], ["/"])
+                .command_meta([CommandMeta::new("greet")])
+                .block(true)
+                .channel_kinds([ChannelKind::Group, ChannelKind::Channel])
+                .platforms(["onebot"])
+                .to_me(),
+            HandlerDecl::message_regex(["^gm$"])
+                .block(true)
+                .channel_kinds([ChannelKind::Direct])
+                .bots(["10001"]),
+        ]
+    );
+}
+
+#[test]
+fn form_derive_reads_prompts_validators_and_parses_answers() {
+    assert_eq!(
+        SignupForm::fields(),
+        &[
+            FormField::new("name", "What should we call you?"),
+            FormField::new("age", "How old are you?"),
+            FormField::new("city", "city?"),
+        ]
+    );
+
+    assert!(SignupForm::validate("age", "twenty").is_err());
+    assert_eq!(
+        SignupForm::validate("age", "12"),
+        Err("must be an adult".to_string())
+    );
+    assert_eq!(SignupForm::validate("age", " 30 "), Ok(()));
+
+    let answers = FormAnswers::from([
+        ("name".to_string(), "Alice".to_string()),
+        ("age".to_string(), "30".to_string()),
+        ("city".to_string(), "Paris".to_string()),
+    ]);
+    let form = SignupForm::build(&answers).unwrap();
+    assert_eq!(
+        (form.name.as_str(), form.age, form.city.as_str()),
+        ("Alice", 30, "Paris")
+    );
+}
+
+#[tokio::test]
+async fn plugin_macro_parses_flags_and_options() {
+    let rolls = Arc::new(Mutex::new(Vec::new()));
+    let plugin = DicePlugin {
+        rolls: rolls.clone(),
+    };
+
+    assert_eq!(
+        RuntimePlugin::declared_handlers(&plugin)[0].command_meta[0].usage,
+        "/roll [-c|--count <count=1>] [-v|--verbose] [--label <label>] <sides>"
+    );
+
+    let ctx = test_context(Vec::new());
+    for args in ["6", "-v --count=3 20 --label dmg", "-c 2 -- 12"] {
+        RuntimePlugin::handle_with_invocation(
+            &plugin,
+            &ctx,
+            Some(CommandInvocation::new("roll", args, Some("/"))),
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(
+        *rolls.lock().unwrap(),
+        vec![
+            "6 1 false None",
+            "20 3 true Some(\"dmg\")",
+            "12 2 false None"
+        ]
+    );
+
+    let Err(err) = RuntimePlugin::handle_with_invocation(
+        &plugin,
+        &ctx,
+        Some(CommandInvocation::new("roll", "--bogus 6", Some("/"))),
+    )
+    .await
+    else {
+        panic!("unknown options are rejected");
+    };
+    assert_eq!(err.to_string(), "Unknown option: --bogus");
+}
+
+#[tokio::test]
+async fn plugin_macro_extracts_context_aware_arguments() {
+    let actions = Arc::new(Mutex::new(Vec::new()));
+    let plugin = ModPlugin {
+        actions: actions.clone(),
+    };
+
+    assert_eq!(
+        RuntimePlugin::declared_handlers(&plugin)[0].command_meta[0].usage,
+        "/mute <user> <duration> [proof]"
+    );
+
+    let mention = |user_id: &str| MessageSegment::Mention {
+        user_id: user_id.to_string(),
+    };
+    let ctx = test_context(vec![
+        MessageSegment::text("/mute"),
+        mention("bob"),
+        MessageSegment::text(" 10m"),
+        mention("test-bot"),
+        MessageSegment::Image {
+            url: "https://proof".to_string(),
+        },
+    ]);
+    RuntimePlugin::handle_with_invocation(
+        &plugin,
+        &ctx,
+        Some(CommandInvocation::new("mute", "10m", Some("/"))),
+    )
+    .await
+    .unwrap();
+
+    let ctx = test_context(vec![MessageSegment::text("/mute carol 45s")]);
+    RuntimePlugin::handle_with_invocation(
+        &plugin,
+        &ctx,
+        Some(CommandInvocation::new("mute", "carol 45s", Some("/"))),
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        *actions.lock().unwrap(),
+        vec![
+            "true bob 600s Some(\"https://proof\")",
+            "false carol 45s None"
+        ]
+    );
+
+    let Err(err) = RuntimePlugin::handle_with_invocation(
+        &plugin,
+        &ctx,
+        Some(CommandInvocation::new("mute", "bob soon", Some("/"))),
+    )
+    .await
+    else {
+        panic!("durations are validated");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse argument `duration`: invalid duration `soon`"
+    );
+}
+
+#[tokio::test]
+async fn plugin_macro_binds_regex_captures_to_arguments() {
+    let forecasts = Arc::new(Mutex::new(Vec::new()));
+    let plugin = WeatherPlugin {
+        forecasts: forecasts.clone(),
+    };
+
+    assert_eq!(
+        RuntimePlugin::declared_handlers(&plugin),
+        vec![
+            HandlerDecl::message_regex([
+                r"^weather (?<city>\w+)(?: (?<days>\d+)d)?$",
+                r"^(?<city>\w+) weather$",
+            ])
+            .priority(5)
+            .block(false)
+        ]
+    );
+
+    let ctx = test_context(Vec::new());
+    let forecast = |pattern: &str, groups: Vec<Option<&str>>, days: Option<usize>| {
+        let regex_match = RegexMatch::new(pattern, groups).with_name("city", 1);
+        match days {
+            Some(index) => regex_match.with_name("days", index),
+            None => regex_match,
+        }
+    };
+    let weekly = r"^weather (?<city>\w+)(?: (?<days>\d+)d)?$";
+    for regex_match in [
+        forecast(
+            weekly,
+            vec![Some("weather paris 3d"), Some("paris"), Some("3")],
+            Some(2),
+        ),
+        forecast(
+            weekly,
+            vec![Some("weather oslo"), Some("oslo"), None],
+            Some(2),
+        ),
+        forecast(
+            r"^(?<city>\w+) weather$",
+            vec![Some("tokyo weather"), Some("tokyo")],
+            None,
+        ),
+    ] {
+        let outcome = RuntimePlugin::handle_regex(&plugin, &ctx, regex_match)
+            .await
+            .unwrap();
+        assert!(!outcome.block);
+    }
+    assert_eq!(
+        *forecasts.lock().unwrap(),
+        vec![
+            "paris Some(3) weather paris 3d",
+            "oslo None weather oslo",
+            "tokyo None tokyo weather"
+        ]
+    );
+
+    let Err(err) = RuntimePlugin::handle_regex(
+        &plugin,
+        &ctx,
+        forecast(
+            weekly,
+            vec![Some("weather rome 900d"), Some("rome"), Some("900")],
+            Some(2),
+        ),
+    )
+    .await
+    else {
+        panic!("captures are parsed");
+    };
+    assert_eq!(
+        err.to_string(),
+        "Failed to parse capture `days`: number too large to fit in target type"
+    );
+}
@@ -0,0 +1,102 @@
+//! Dispatch cost against the number of regex and keyword routes.
+//!
+//! `cargo test -p ayiou --release --test regex_dispatch -- --ignored --nocapture`
+
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ayiou::Context;
+use ayiou::core::model::{BotId, ChannelRef, EventEnvelope, MessageEvent, PlatformId, UserRef};
+use ayiou::core::plugin::{
+    HandleOutcome, HandlerDecl, PluginRuntimeState, RuntimePlugin, RuntimePluginEngine,
+    RuntimePluginServices,
+};
+
+const ROUNDS: u32 = 2_000;
+
+fn test_context(text: &str) -> Context {
+    let platform = PlatformId::new("test");
+    let user = UserRef::new(platform.clone(), "user");
+    let channel = ChannelRef::direct(platform.clone(), "user");
+    let message = MessageEvent::new(user, channel, text);
+    Context::new(
+        EventEnvelope::new(BotId::new("test-bot"), platform).with_message(message),
+        None,
+        (),
+    )
+}
+
+struct MatcherPlugin {
+    handler: HandlerDecl,
+}
+
+#[async_trait]
+impl RuntimePlugin for MatcherPlugin {
+    fn kind(&self) -> &'static str {
+        "matcher"
+    }
+
+    fn declared_handlers(&self) -> Vec<HandlerDecl> {
+        vec![self.handler.clone()]
+    }
+
+    async fn handle(&self, _ctx: &Context) -> Result<HandleOutcome> {
+        Ok(HandleOutcome::pass())
+    }
+}
+
+async fn engine_with(count: usize, handler: impl Fn(usize) -> HandlerDecl) -> RuntimePluginEngine {
+    let mut engine =
+        RuntimePluginEngine::new(RuntimePluginServices::new(), PluginRuntimeState::default());
+    for index in 0..count {
+        engine.push_as(
+            format!("matcher-{index}"),
+            Box::new(MatcherPlugin {
+                handler: handler(index),
+            }),
+        );
+    }
+    engine.init_all().await.unwrap();
+    engine.start_all().await.unwrap();
+    engine
+}
+
+async fn per_dispatch(engine: &RuntimePluginEngine, text: &str) -> Duration {
+    let ctx = test_context(text);
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+        engine.dispatch(&ctx).await.unwrap();
+    }
+    started.elapsed() / ROUNDS
+}
+
+#[tokio::test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+async fn regex_dispatch_cost_by_pattern_count() {
+    println!("{:>8} {:>12} {:>12}", "patterns", "no match", "one match");
+    for count in [1, 10, 100, 1_000] {
+        let engine = engine_with(count, |index| {
+            HandlerDecl::message_regex([format!(r"^(?:查询|query)\s*item-{index}\b")])
+        })
+        .await;
+        let miss = per_dispatch(&engine, "good morning everyone").await;
+        let hit = per_dispatch(&engine, "查询 item-0 price").await;
+        println!("{count:>8} {miss:>12.2?} {hit:>12.2?}");
+    }
+}
+
+#[tokio::test]
+#[ignore = "benchmark, run with --ignored --nocapture"]
+async fn keyword_dispatch_cost_by_keyword_count() {
+    println!("{:>8} {:>12} {:>12}", "keywords", "no match", "one match");
+    for count in [1, 10, 100, 1_000] {
+        let engine = engine_with(count, |index| {
+            HandlerDecl::message_keywords([format!("item-{index}!")]).ignore_case()
+        })
+        .await;
+        let miss = per_dispatch(&engine, "good morning everyone").await;
+        let hit = per_dispatch(&engine, "查询 ITEM-0! price").await;
+        println!("{count:>8} {miss:>12.2?} {hit:>12.2?}");
+    }
+}
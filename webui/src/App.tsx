@@ -13,7 +13,14 @@ import { listPlugins, pluginAction, type PluginAction, type PluginSnapshot } fro
 
 const tokenStorageKey = 'ayiou.control.token'
 
-type UserPluginStatusKind = 'running' | 'disabled' | 'failed' | 'starting' | 'stopped' | 'pending'
+type UserPluginStatusKind =
+  | 'running'
+  | 'disabled'
+  | 'failed'
+  | 'tripped'
+  | 'starting'
+  | 'stopped'
+  | 'pending'
 
 type UserPluginStatus = {
   kind: UserPluginStatusKind
@@ -25,6 +32,19 @@ type UserPluginStatus = {
 type UserPluginAction = Extract<PluginAction, 'enable' | 'disable' | 'reload'>
 
 function getPluginStatus(plugin: PluginSnapshot): UserPluginStatus {
+  if (plugin.lifecycle.lifecycle_state === 'AutoDisabled') {
+    const reenableIn = plugin.lifecycle.auto_reenable_in_ms
+    return {
+      kind: 'tripped',
+      label: '已熔断',
+      detail:
+        reenableIn === null
+          ? `连续失败 ${plugin.lifecycle.consecutive_failures} 次，需手动重新启用。`
+          : `连续失败 ${plugin.lifecycle.consecutive_failures} 次，约 ${Math.ceil(reenableIn / 1000)} 秒后自动恢复。`,
+      tone: 'border-amber-400/40 bg-amber-400/10 text-amber-200',
+    }
+  }
+
   if (plugin.lifecycle.lifecycle_state === 'Failed' || !plugin.health.healthy) {
     return {
       kind: 'failed',
@@ -410,12 +430,14 @@ function PluginDetails({
   }
 
   const status = getPluginStatus(plugin)
-  const primaryAction: UserPluginAction = plugin.lifecycle.enabled ? 'disable' : 'enable'
-  const primaryLabel = plugin.lifecycle.enabled
-    ? '停用插件'
-    : status.kind === 'failed'
-      ? '重新启用'
-      : '启用插件'
+  const tripped = status.kind === 'tripped'
+  const primaryAction: UserPluginAction = plugin.lifecycle.enabled && !tripped ? 'disable' : 'enable'
+  const primaryLabel =
+    plugin.lifecycle.enabled && !tripped
+      ? '停用插件'
+      : status.kind === 'failed' || tripped
+        ? '重新启用'
+        : '启用插件'
   const isUnhealthy = !plugin.health.healthy
   const errorDetail =
     plugin.lifecycle.last_error ?? (isUnhealthy ? plugin.health.detail : undefined)
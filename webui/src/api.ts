@@ -6,6 +6,7 @@ export type PluginLifecycleState =
   | 'Stopping'
   | 'Stopped'
   | 'Failed'
+  | 'AutoDisabled'
 
 export type PluginAction = 'enable' | 'disable' | 'start' | 'stop' | 'reload'
 
@@ -29,6 +30,9 @@ export interface PluginSnapshot {
     config_lifecycle_state: string
     lifecycle_state: PluginLifecycleState
     last_error: string | null
+    timeouts: number
+    consecutive_failures: number
+    auto_reenable_in_ms: number | null
   }
   health: {
     healthy: boolean